@@ -0,0 +1,146 @@
+//! An RTIC app running on the host, with the `host-backend` feature
+//!
+//! `cargo test` runs the app in `tests/`, pending its interrupts and checking what the tasks log.
+
+pub mod log {
+    use std::sync::Mutex;
+
+    static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Appends a line to the log
+    pub fn push(line: String) {
+        LOG.lock().unwrap().push(line);
+    }
+
+    /// Takes the lines logged so far
+    pub fn take() -> Vec<String> {
+        std::mem::take(&mut LOG.lock().unwrap())
+    }
+}
+
+/// Work for `#[idle]`, handed over by the tests
+pub mod idle_work {
+    use std::sync::{Condvar, Mutex};
+
+    use crate::app::Interrupt;
+
+    struct State {
+        work: Option<Vec<Interrupt>>,
+        busy: bool,
+    }
+
+    static STATE: Mutex<State> = Mutex::new(State {
+        work: None,
+        busy: false,
+    });
+    static CHANGED: Condvar = Condvar::new();
+
+    /// Has `#[idle]` pend `interrupts` while it holds a lock, and waits for it to release the lock
+    pub fn pend_in_lock(interrupts: &[Interrupt]) {
+        let mut state = STATE.lock().unwrap();
+        state.work = Some(interrupts.to_vec());
+        state.busy = true;
+        CHANGED.notify_all();
+
+        while state.busy {
+            state = CHANGED.wait(state).unwrap();
+        }
+    }
+
+    /// Waits for work to do
+    pub(crate) fn next() -> Vec<Interrupt> {
+        let mut state = STATE.lock().unwrap();
+        loop {
+            if let Some(work) = state.work.take() {
+                return work;
+            }
+            state = CHANGED.wait(state).unwrap();
+        }
+    }
+
+    /// Reports the work as done
+    pub(crate) fn done() {
+        STATE.lock().unwrap().busy = false;
+        CHANGED.notify_all();
+    }
+}
+
+#[rtic::app(dispatchers = [SWI0])]
+mod app {
+    use std::{thread, time::Duration};
+
+    use crate::{idle_work, log};
+
+    #[shared]
+    struct Shared {
+        presses: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        log::push("init".into());
+
+        (Shared { presses: 0 }, Local {})
+    }
+
+    #[idle(shared = [presses])]
+    fn idle(mut cx: idle::Context) -> ! {
+        loop {
+            let interrupts = idle_work::next();
+
+            cx.shared.presses.lock(|_| {
+                for interrupt in interrupts {
+                    rtic::host::pend(interrupt);
+                }
+
+                // Handlers that run while the lock is held would log in the meantime
+                thread::sleep(Duration::from_millis(20));
+                log::push("idle: unlocking".into());
+            });
+
+            idle_work::done();
+        }
+    }
+
+    #[task(binds = BUTTON, shared = [presses])]
+    fn button(mut cx: button::Context) {
+        cx.shared.presses.lock(|presses| {
+            *presses += 1;
+
+            // `report` has a higher priority, but runs once the lock is released
+            report::spawn().unwrap();
+
+            log::push(format!("button: presses = {presses}"));
+        });
+
+        log::push("button: done".into());
+    }
+
+    #[task(priority = 2, shared = [presses])]
+    async fn report(mut cx: report::Context) {
+        let presses = cx.shared.presses.lock(|presses| *presses);
+
+        log::push(format!("report: presses = {presses}"));
+    }
+
+    #[task(binds = LOW, priority = 1)]
+    fn low(_: low::Context) {
+        log::push("low".into());
+    }
+
+    #[task(binds = MID, priority = 2)]
+    fn mid(_: mid::Context) {
+        log::push("mid".into());
+    }
+
+    // Accessing `presses` raises its ceiling to 3, `#[idle]` keeps all handlers out when it locks it
+    #[task(binds = HIGH, priority = 3, shared = [presses])]
+    fn high(mut cx: high::Context) {
+        let presses = cx.shared.presses.lock(|presses| *presses);
+
+        log::push(format!("high: presses = {presses}"));
+    }
+}
@@ -0,0 +1,21 @@
+use rtic_host::{app::Interrupt, idle_work, log};
+
+#[test]
+fn idle_lock_keeps_handlers_out() {
+    rtic::host::start(rtic_host::app::main);
+    assert_eq!(log::take(), ["init"]);
+
+    // `button` is pended from `#[idle]` and only runs once `#[idle]` releases `presses`
+    idle_work::pend_in_lock(&[Interrupt::BUTTON]);
+    rtic::host::wait_idle();
+
+    assert_eq!(
+        log::take(),
+        [
+            "idle: unlocking",
+            "button: presses = 1",
+            "report: presses = 1",
+            "button: done",
+        ]
+    );
+}
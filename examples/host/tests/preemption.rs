@@ -0,0 +1,15 @@
+use rtic_host::{app, log};
+
+#[test]
+fn report_preempts_button_after_the_lock() {
+    rtic::host::start(app::main);
+    assert_eq!(log::take(), ["init"]);
+
+    rtic::host::pend(app::Interrupt::BUTTON);
+    rtic::host::wait_idle();
+
+    assert_eq!(
+        log::take(),
+        ["button: presses = 1", "report: presses = 1", "button: done",]
+    );
+}
@@ -0,0 +1,16 @@
+use rtic_host::{app::Interrupt, idle_work, log};
+
+#[test]
+fn pending_handlers_run_by_priority() {
+    rtic::host::start(rtic_host::app::main);
+    assert_eq!(log::take(), ["init"]);
+
+    // All three are pending when `#[idle]` releases its lock, the highest priority goes first
+    idle_work::pend_in_lock(&[Interrupt::LOW, Interrupt::MID, Interrupt::HIGH]);
+    rtic::host::wait_idle();
+
+    assert_eq!(
+        log::take(),
+        ["idle: unlocking", "high: presses = 0", "mid", "low"]
+    );
+}
@@ -0,0 +1,54 @@
+//! examples/binds_multiple.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965)]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+
+    #[shared]
+    struct Shared {
+        bytes: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        rtic::pend(Interrupt::UART0);
+
+        hprintln!("init");
+
+        (Shared { bytes: 0 }, Local {})
+    }
+
+    // One task body, and one set of resources, for both lines of the peripheral
+    #[task(binds = [UART0, UART1], shared = [bytes], local = [runs: u32 = 0])]
+    fn uart(mut cx: uart::Context) {
+        *cx.local.runs += 1;
+        let bytes = cx.shared.bytes.lock(|bytes| {
+            *bytes += 1;
+            *bytes
+        });
+
+        hprintln!(
+            "uart - {:?}, run {}, {} bytes",
+            cx.source,
+            cx.local.runs,
+            bytes
+        );
+
+        match cx.source {
+            uart::Source::UART0 => rtic::pend(Interrupt::UART1),
+            uart::Source::UART1 => debug::exit(debug::EXIT_SUCCESS), // Exit QEMU simulator
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! examples/component.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+const BLINKS: u32 = 3;
+
+// A component brings its own tasks, resources and dispatchers
+#[rtic::component(dispatchers = [SSI0])]
+mod blinker {
+    #[shared]
+    struct Shared {
+        blinks: u32,
+    }
+
+    #[local]
+    struct Local {
+        on: bool,
+    }
+
+    #[task(shared = [blinks], local = [on])]
+    async fn blink(mut cx: blink::Context) {
+        // `crate::` paths refer to the crate of the component
+        for _ in 0..crate::BLINKS {
+            *cx.local.on = !*cx.local.on;
+            let blinks = cx.shared.blinks.lock(|blinks| {
+                *blinks += 1;
+                *blinks
+            });
+            cortex_m_semihosting::hprintln!("blink {} (on = {})", blinks, cx.local.on);
+        }
+    }
+}
+
+#[rtic::app(device = lm3s6965, dispatchers = [QEI0], components = [blinker])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        blink::spawn().unwrap();
+        report::spawn().unwrap();
+
+        // The resources of the component are initialized by the app
+        (Shared { blinks: 0 }, Local { on: false })
+    }
+
+    #[task(shared = [blinks])]
+    async fn report(mut cx: report::Context) {
+        let blinks = cx.shared.blinks.lock(|blinks| *blinks);
+        hprintln!("report: {} blinks", blinks);
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+}
@@ -0,0 +1,67 @@
+//! examples/const-priorities.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+/// Priorities of the application, shared with drivers
+pub mod prio {
+    /// Priority of the communication tasks
+    pub const COMMS: u8 = 2;
+
+    /// Priority of the sensor interrupt
+    pub const SENSOR: u8 = COMMS + 1;
+}
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0, QEI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+
+    #[shared]
+    struct Shared {
+        samples: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        background::spawn().unwrap();
+
+        (Shared { samples: 0 }, Local {})
+    }
+
+    #[task(priority = 1, shared = [samples])]
+    async fn background(mut cx: background::Context) {
+        // `samples` has the ceiling `prio::SENSOR`, so `sensor` can't run while it's locked
+        cx.shared.samples.lock(|samples| {
+            rtic::pend(Interrupt::UART0);
+            hprintln!("background: {} samples", samples);
+        });
+
+        hprintln!("background: done");
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+
+    #[task(priority = crate::prio::COMMS, shared = [samples])]
+    async fn comms(mut cx: comms::Context) {
+        let samples = cx.shared.samples.lock(|samples| *samples);
+        hprintln!("comms: sending {} samples", samples);
+    }
+
+    #[task(binds = UART0, priority = crate::prio::SENSOR, shared = [samples])]
+    fn sensor(mut cx: sensor::Context) {
+        cx.shared.samples.lock(|samples| *samples += 1);
+        hprintln!("sensor");
+
+        // `comms` has a lower priority and runs once `sensor` returns
+        comms::spawn().unwrap();
+        hprintln!("sensor: spawned comms");
+    }
+}
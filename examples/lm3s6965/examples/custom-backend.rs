@@ -0,0 +1,117 @@
+//! examples/custom-backend.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+use cortex_m::{
+    interrupt,
+    peripheral::NVIC,
+    register::{basepri, basepri_max},
+};
+use lm3s6965::{Interrupt, NVIC_PRIO_BITS};
+use panic_semihosting as _;
+
+/// A BASEPRI based backend, implemented outside of RTIC
+pub struct Basepri;
+
+// Maps a logical priority to the NVIC, where a lower value means a higher priority
+fn hw(priority: u8) -> u8 {
+    ((1 << NVIC_PRIO_BITS) - priority) << (8 - NVIC_PRIO_BITS)
+}
+
+unsafe impl rtic::Backend for Basepri {
+    type Interrupt = Interrupt;
+
+    const MAX_PRIORITY: u8 = 1 << NVIC_PRIO_BITS;
+
+    fn interrupt_disable() {
+        interrupt::disable();
+    }
+
+    unsafe fn interrupt_enable() {
+        unsafe { interrupt::enable() }
+    }
+
+    unsafe fn enable_interrupt(interrupt: Interrupt, priority: u8) {
+        unsafe {
+            let mut nvic: NVIC = core::mem::transmute(());
+            nvic.set_priority(interrupt, hw(priority));
+            NVIC::unmask(interrupt);
+        }
+    }
+
+    fn pend(interrupt: Interrupt) {
+        NVIC::pend(interrupt);
+    }
+
+    fn run(_priority: u8, f: impl FnOnce()) {
+        let initial = basepri::read();
+        f();
+        unsafe { basepri::write(initial) }
+    }
+
+    unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+        unsafe {
+            if ceiling == Self::MAX_PRIORITY {
+                interrupt::free(|_| f(&mut *ptr))
+            } else {
+                let current = basepri::read();
+                basepri_max::write(hw(ceiling));
+                let r = f(&mut *ptr);
+                basepri::write(current);
+                r
+            }
+        }
+    }
+}
+
+#[rtic::app(device = lm3s6965, backend = crate::Basepri, dispatchers = [GPIOA, GPIOB])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {
+        shared: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        foo::spawn().unwrap();
+
+        (Shared { shared: 0 }, Local {})
+    }
+
+    #[task(shared = [shared])]
+    async fn foo(mut c: foo::Context) {
+        hprintln!("A");
+
+        c.shared.shared.lock(|shared| {
+            *shared += 1;
+
+            // bar will *not* run right now, the backend raised BASEPRI to the ceiling
+            bar::spawn().unwrap();
+
+            hprintln!("B - shared = {}", *shared);
+        });
+
+        hprintln!("D");
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+
+    #[task(priority = 2, shared = [shared])]
+    async fn bar(mut c: bar::Context) {
+        let shared = c.shared.shared.lock(|shared| {
+            *shared += 1;
+
+            *shared
+        });
+
+        hprintln!("C - shared = {}", shared);
+    }
+}
@@ -0,0 +1,45 @@
+//! examples/executor_section.rs
+//!
+//! Places the executor of a task, and a local resource, in sections of their own.
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        foo::spawn().unwrap();
+
+        (Shared {}, Local {})
+    }
+
+    // The executor starts out zeroed, so any section the runtime zeroes (or loads) will do.
+    #[task(
+        priority = 1,
+        executor_section = ".bss.executors",
+        local = [
+            // Copied from flash by the runtime, like the rest of `.data`
+            #[link_section = ".data.counters"]
+            runs: u32 = 40,
+        ],
+    )]
+    async fn foo(cx: foo::Context) {
+        *cx.local.runs += 2;
+        hprintln!("foo runs = {}", cx.local.runs);
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+}
@@ -0,0 +1,77 @@
+//! examples/init-in-place.rs
+//!
+//! Initializes large resources directly in their `static` storage rather than returning them
+//! from `#[init]`, which would build them on the stack and then copy them.
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+/// Some big struct
+pub struct BigStruct {
+    /// Big content
+    pub data: [u8; 2048],
+}
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use super::BigStruct;
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+
+    #[shared]
+    struct Shared {
+        #[init_in_place]
+        big_struct: BigStruct,
+    }
+
+    #[local]
+    struct Local {
+        #[init_in_place]
+        buffer: [u32; 1024],
+    }
+
+    #[init]
+    fn init(mut cx: init::Context) -> (Shared, Local) {
+        // write the fields one by one, without a `BigStruct` ever being on the stack
+        let big_struct = unsafe {
+            let big_struct = cx.slots.big_struct.as_mut_ptr();
+            core::ptr::addr_of_mut!((*big_struct).data).write_bytes(22, 1);
+            cx.slots.big_struct.assume_init()
+        };
+
+        let mut buffer = cx.slots.buffer.write([0; 1024]);
+        buffer[0] = 1;
+
+        rtic::pend(Interrupt::UART0);
+        async_task::spawn().unwrap();
+
+        (Shared { big_struct }, Local { buffer })
+    }
+
+    #[idle]
+    fn idle(_: idle::Context) -> ! {
+        loop {
+            hprintln!("idle");
+            debug::exit(debug::EXIT_SUCCESS);
+        }
+    }
+
+    #[task(binds = UART0, shared = [big_struct], local = [buffer])]
+    fn uart0(mut cx: uart0::Context) {
+        cx.shared
+            .big_struct
+            .lock(|b| hprintln!("uart0 data:{:?}", &b.data[0..5]));
+        hprintln!("uart0 buffer:{:?}", &cx.local.buffer[0..2]);
+    }
+
+    #[task(shared = [big_struct], priority = 2)]
+    async fn async_task(mut cx: async_task::Context) {
+        cx.shared
+            .big_struct
+            .lock(|b| hprintln!("async_task data:{:?}", &b.data[0..5]));
+    }
+}
@@ -0,0 +1,67 @@
+//! examples/mpu.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0], mpu = true)]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+
+    #[shared]
+    struct Shared {
+        counter: u32,
+        log: [u8; 64],
+    }
+
+    #[local]
+    struct Local {
+        buffer: [u8; 16],
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        rtic::pend(Interrupt::UART0);
+
+        (
+            Shared {
+                counter: 0,
+                log: [0; 64],
+            },
+            Local { buffer: [0; 16] },
+        )
+    }
+
+    #[idle(shared = [counter])]
+    fn idle(mut cx: idle::Context) -> ! {
+        // `idle` can't reach `log` nor `buffer`, any access to them would fault
+        let counter = cx.shared.counter.lock(|counter| *counter);
+        hprintln!("idle: counter = {}", counter);
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+
+        loop {}
+    }
+
+    #[task(binds = UART0, shared = [counter, log], local = [buffer])]
+    fn uart0(mut cx: uart0::Context) {
+        cx.local.buffer[0] = 1;
+        cx.shared.log.lock(|log| log[0] = cx.local.buffer[0]);
+        cx.shared.counter.lock(|counter| *counter += 1);
+        hprintln!("uart0");
+
+        foo::spawn().unwrap();
+    }
+
+    #[task(priority = 2, shared = [counter], local = [times: u32 = 0])]
+    async fn foo(mut cx: foo::Context) {
+        *cx.local.times += 1;
+        cx.shared.counter.lock(|counter| *counter += 1);
+        hprintln!("foo: {}", cx.local.times);
+    }
+}
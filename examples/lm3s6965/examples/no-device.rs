@@ -0,0 +1,45 @@
+//! examples/no-device.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+// The app doesn't name a device crate, but something still has to provide the vector table
+use lm3s6965 as _;
+
+#[rtic::app(interrupts = lm3s6965::Interrupt, prio_bits = 3, dispatchers = [SSI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+        rtic::pend(Interrupt::UART0);
+
+        (Shared {}, Local {})
+    }
+
+    #[task(binds = UART0, priority = 2)]
+    fn uart0(_: uart0::Context) {
+        hprintln!("UART0");
+        foo::spawn().unwrap();
+    }
+
+    #[task(priority = 1)]
+    async fn foo(_: foo::Context) {
+        hprintln!("foo");
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+}
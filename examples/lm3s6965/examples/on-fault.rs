@@ -0,0 +1,80 @@
+//! examples/on-fault.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+    use rtic::fault::{ActiveTask, FaultFrame};
+
+    #[shared]
+    struct Shared {
+        // The address of the last instruction that faulted, kept across resets
+        #[persistent]
+        last_fault: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+
+        let last_fault = cx.persisted.last_fault.map_or(0, |pc| *pc);
+
+        (Shared { last_fault }, Local {})
+    }
+
+    #[idle(shared = [last_fault])]
+    fn idle(mut cx: idle::Context) -> ! {
+        let last_fault = cx.shared.last_fault.lock(|pc| *pc);
+        hprintln!("idle - last fault at {:#010x}", last_fault);
+
+        low::spawn().unwrap();
+
+        loop {
+            cortex_m::asm::nop();
+        }
+    }
+
+    #[task(priority = 1)]
+    async fn low(_: low::Context) {
+        hprintln!("low - start");
+
+        rtic::pend(Interrupt::UART0);
+
+        hprintln!("low - end");
+    }
+
+    #[task(binds = UART0, priority = 2)]
+    fn high(_: high::Context) {
+        hprintln!("high - undefined instruction");
+
+        cortex_m::asm::udf();
+    }
+
+    #[on_fault]
+    fn on_fault(frame: &FaultFrame, tasks: &[ActiveTask], persisted: on_fault::Persisted) -> ! {
+        // Nothing else runs anymore, so the hook can write and seal the resources
+        *persisted.last_fault = frame.pc;
+        seal::last_fault(persisted.last_fault);
+
+
+        // From the lowest priority level to the task that faulted
+        for task in tasks {
+            hprintln!("fault - {} at priority {}", task.name, task.priority);
+        }
+
+        loop {
+            debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+        }
+    }
+}
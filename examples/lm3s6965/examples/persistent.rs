@@ -0,0 +1,64 @@
+//! examples/persistent.rs
+//!
+//! Keeps a boot counter, and the number of times a task ran, across warm resets.
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {
+        #[persistent]
+        boot_count: u32,
+    }
+
+    #[local]
+    struct Local {
+        #[persistent]
+        ticks: u32,
+    }
+
+    #[init]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        // `None` after a cold reset
+        let boot_count = cx.persisted.boot_count.map_or(1, |count| *count + 1);
+        let ticks = cx.persisted.ticks.map_or(0, |ticks| *ticks);
+
+        hprintln!("init - boot {}, ticks {}", boot_count, ticks);
+
+        (Shared { boot_count }, Local { ticks })
+    }
+
+    #[idle(shared = [boot_count])]
+    fn idle(mut cx: idle::Context) -> ! {
+        tick::spawn().unwrap();
+
+        if cx.shared.boot_count.lock(|count| *count) < 2 {
+            // QEMU clears the RAM on a system reset, so restart from the reset vector instead of
+            // calling `SCB::sys_reset`
+            unsafe { cortex_m::asm::bootload(core::ptr::null()) }
+        }
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+
+        loop {
+            cortex_m::asm::nop();
+        }
+    }
+
+    #[task(priority = 1, local = [ticks])]
+    async fn tick(cx: tick::Context) {
+        *cx.local.ticks += 1;
+        // Without sealing, the change is dropped on the next reset
+        seal::ticks(cx.local.ticks);
+
+        hprintln!("tick - ticks {}", cx.local.ticks);
+    }
+}
@@ -0,0 +1,72 @@
+//! examples/read-lock.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [GPIOA, GPIOB, GPIOC])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    /// A configuration table, read by most tasks and updated at runtime
+    pub struct Config {
+        threshold: u32,
+    }
+
+    #[shared]
+    struct Shared {
+        config: Config,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        foo::spawn().unwrap();
+
+        (Shared { config: Config { threshold: 10 } }, Local {})
+    }
+
+    #[task(priority = 1, shared = [&config])]
+    async fn foo(mut cx: foo::Context) {
+        // `read` only raises the priority to that of the highest writer, `bar`
+        cx.shared.config.read(|config| {
+            hprintln!("foo - threshold = {}", config.threshold);
+
+            // `bar` writes `config`, it has to wait for the read to end
+            bar::spawn().unwrap();
+
+            // `baz` only reads `config`, it runs right away
+            baz::spawn().unwrap();
+
+            hprintln!("foo - done reading");
+        });
+
+        cx.shared
+            .config
+            .read(|config| hprintln!("foo - threshold = {}", config.threshold));
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+
+    #[task(priority = 2, shared = [config])]
+    async fn bar(mut cx: bar::Context) {
+        cx.shared.config.lock(|config| {
+            config.threshold += 1;
+
+            hprintln!("bar - threshold = {}", config.threshold);
+        });
+    }
+
+    #[task(priority = 3, shared = [&config])]
+    async fn baz(mut cx: baz::Context) {
+        cx.shared
+            .config
+            .read(|config| hprintln!("baz - threshold = {}", config.threshold));
+    }
+}
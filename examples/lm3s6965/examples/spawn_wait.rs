@@ -0,0 +1,47 @@
+//! examples/spawn_wait.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+        producer::spawn().unwrap();
+
+        (Shared {}, Local {})
+    }
+
+    #[task(priority = 1)]
+    async fn producer(_: producer::Context) {
+        for i in 0..3 {
+            hprintln!("producer - spawning {}", i);
+            // Parks `producer` until `consumer` has returned
+            consumer::spawn_wait(i).await;
+        }
+    }
+
+    // `spawn_wait` gives `consumer` a queue of the spawners waiting for it to return
+    #[task(priority = 1, spawn_wait)]
+    async fn consumer(_: consumer::Context, i: u32) {
+        hprintln!("consumer - {}", i);
+
+        if i == 2 {
+            debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+        }
+    }
+}
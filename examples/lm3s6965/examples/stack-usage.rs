@@ -0,0 +1,56 @@
+//! examples/stack-usage.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0], stack_usage = true)]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use lm3s6965::Interrupt;
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        rtic::pend(Interrupt::UART0);
+
+        (Shared {}, Local {})
+    }
+
+    #[idle]
+    fn idle(_: idle::Context) -> ! {
+        let used = rtic::stack::high_water_mark().unwrap();
+        hprintln!("stack used: {}", used > 0 && used < rtic::stack::size());
+
+        // `foo` preempted `uart0`, so priority 2 was entered on top of priority 1
+        let uart0 = rtic::stack::entry_depth(1);
+        let foo = rtic::stack::entry_depth(2);
+        hprintln!("priority 2 entered deeper than priority 1: {}", foo > uart0);
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+
+        loop {}
+    }
+
+    #[task(binds = UART0)]
+    fn uart0(_: uart0::Context) {
+        hprintln!("uart0");
+
+        // `foo` has a higher priority and runs before `spawn` returns
+        foo::spawn().unwrap();
+    }
+
+    #[task(priority = 2)]
+    async fn foo(_: foo::Context) {
+        hprintln!("foo");
+    }
+}
@@ -0,0 +1,82 @@
+//! examples/task-instances.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+/// A sensor driver
+pub trait Sensor {
+    /// Name of the sensor
+    const NAME: &'static str;
+
+    /// Takes a measurement
+    fn read(&mut self) -> i32;
+}
+
+/// An inertial measurement unit
+#[derive(Debug)]
+pub struct Imu;
+
+impl Sensor for Imu {
+    const NAME: &'static str = "imu";
+
+    fn read(&mut self) -> i32 {
+        42
+    }
+}
+
+/// A barometer
+#[derive(Debug)]
+pub struct Baro;
+
+impl Sensor for Baro {
+    const NAME: &'static str = "baro";
+
+    fn read(&mut self) -> i32 {
+        1013
+    }
+}
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0, QEI0])]
+mod app {
+    use super::{Baro, Imu, Sensor};
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {
+        samples: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        imu::spawn(Imu, 2).unwrap();
+        baro::spawn(Baro, 1).unwrap();
+
+        (Shared { samples: 0 }, Local {})
+    }
+
+    // One task per instance, each with its own spawn API and executor
+    #[task(priority = 1, shared = [samples], local = [count: u32 = 0], instances = [imu<Imu>, baro<Baro>])]
+    async fn poll<S: Sensor + Send>(mut cx: poll::Context, mut sensor: S, n: u32) {
+        for _ in 0..n {
+            *cx.local.count += 1;
+            let value = sensor.read();
+            let samples = cx.shared.samples.lock(|samples| {
+                *samples += 1;
+                *samples
+            });
+            hprintln!("{} #{}: {} (samples: {})", S::NAME, cx.local.count, value, samples);
+
+            if samples == 3 {
+                debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+            }
+        }
+    }
+}
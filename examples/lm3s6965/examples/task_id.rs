@@ -0,0 +1,50 @@
+//! examples/task_id.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    use core::{future::poll_fn, task::Poll, task::Waker};
+    use cortex_m_semihosting::{debug, hprintln};
+    use rtic::TaskId;
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+        foo::spawn().unwrap();
+
+        (Shared {}, Local {})
+    }
+
+    #[task(priority = 1)]
+    async fn foo(_: foo::Context) {
+        // The waker `foo` is polled with
+        let task = poll_fn(|cx| Poll::Ready(rtic::task_id_from_waker(cx.waker()))).await;
+        let task = task.unwrap();
+        hprintln!("foo - task {} is {}", task.index(), task.name());
+
+        let bar = rtic::task_id_from_waker(&bar::waker()).map(TaskId::name);
+        hprintln!("bar::waker() - {:?}", bar);
+
+        // Not a waker of RTIC
+        let noop = rtic::task_id_from_waker(Waker::noop());
+        hprintln!("Waker::noop() - {:?}", noop);
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+
+    #[task(priority = 1)]
+    async fn bar(_: bar::Context) {}
+}
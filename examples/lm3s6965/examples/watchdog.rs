@@ -0,0 +1,80 @@
+//! examples/watchdog.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(
+    device = lm3s6965,
+    dispatchers = [SSI0],
+    peripherals = true,
+    watchdog = Wdt,
+    timeout = 250.millis(),
+)]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+    use rtic::watchdog::Watchdog;
+    use rtic_monotonics::systick::prelude::*;
+
+    systick_monotonic!(Mono, 100);
+
+    /// Stands in for the watchdog of the device
+    pub struct Wdt;
+
+    impl Watchdog for Wdt {
+        type Duration = <Mono as Monotonic>::Duration;
+
+        fn start(_timeout: Self::Duration) {
+            hprintln!("watchdog - start");
+        }
+
+        fn feed() {
+            hprintln!("watchdog - fed");
+        }
+
+        async fn wait(timeout: Self::Duration) {
+            Mono::delay(timeout).await;
+        }
+    }
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(cx: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+
+        Mono::start(cx.core.SYST, 12_000_000);
+
+        worker::spawn().ok();
+
+        (Shared {}, Local {})
+    }
+
+    #[task(priority = 1, supervised)]
+    async fn worker(cx: worker::Context) {
+        for i in 0..3 {
+            cx.checkin();
+            hprintln!("worker - check-in {}", i);
+
+            Mono::delay(100.millis()).await;
+        }
+
+        // Stops checking in, the supervisor notices at the end of the window
+        hprintln!("worker - stuck");
+    }
+
+    #[on_starvation]
+    fn on_starvation(task: &'static str) {
+        hprintln!("on_starvation - {}", task);
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+}
@@ -0,0 +1,55 @@
+//! examples/yield_now.rs
+
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use panic_semihosting as _;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0], fair_dispatch = true)]
+mod app {
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+        a::spawn().unwrap();
+        b::spawn().unwrap();
+        c::spawn().unwrap();
+
+        (Shared {}, Local {})
+    }
+
+    // Each step is short, yielding in between lets the other tasks of the priority take turns
+    async fn work(name: &str) {
+        for step in 0..3 {
+            hprintln!("{} - step {}", name, step);
+            rtic::yield_now().await;
+        }
+    }
+
+    #[task(priority = 1)]
+    async fn a(_: a::Context) {
+        work("a").await;
+    }
+
+    #[task(priority = 1)]
+    async fn b(_: b::Context) {
+        work("b").await;
+    }
+
+    #[task(priority = 1)]
+    async fn c(_: c::Context) {
+        work("c").await;
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+    }
+}
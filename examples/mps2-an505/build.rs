@@ -0,0 +1,13 @@
+use std::{env, fs, path::PathBuf};
+
+fn main() {
+    // Put `memory.x` and `device.x` in the search path of the linker
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    for file in ["memory.x", "device.x"] {
+        fs::copy(file, out.join(file)).unwrap();
+        println!("cargo::rerun-if-changed={file}");
+    }
+    println!("cargo::rustc-link-search={}", out.display());
+
+    println!("cargo::rustc-link-arg=-Tlink.x");
+}
@@ -0,0 +1,77 @@
+//! secure app handing interrupts to the non-secure state and spawned by it
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(missing_docs)]
+#![feature(cmse_nonsecure_entry)]
+
+use panic_semihosting as _;
+use rtic_mps2_an505::an505;
+
+// The entry function of `log`, which the non-secure image calls through its veneer
+unsafe extern "C" {
+    fn log_spawn(value: u32) -> i32;
+}
+
+#[rtic::app(
+    device = an505,
+    peripherals = false,
+    dispatchers = [UART2RX, UART2TX],
+    backend = {
+        non_secure_interrupts = [UART1RX, UART1TX],
+        non_secure_callable = [log],
+    },
+)]
+mod app {
+    use super::{
+        an505::{self, Interrupt},
+        log_spawn,
+    };
+    use cortex_m_semihosting::{debug, hprintln};
+
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        hprintln!("init");
+        hprintln!(
+            "UART1RX non-secure: {}, UART2RX non-secure: {}",
+            an505::targets_non_secure(Interrupt::UART1RX),
+            an505::targets_non_secure(Interrupt::UART2RX)
+        );
+
+        (Shared {}, Local {})
+    }
+
+    #[idle]
+    fn idle(_: idle::Context) -> ! {
+        // `log` preempts `idle` before `log_spawn` returns
+        let first = unsafe { log_spawn(1) };
+        hprintln!("idle - log_spawn(1) = {}", first);
+
+        high::spawn().unwrap();
+
+        debug::exit(debug::EXIT_SUCCESS); // Exit QEMU simulator
+
+        loop {
+            cortex_m::asm::nop();
+        }
+    }
+
+    #[task(priority = 2)]
+    async fn high(_: high::Context) {
+        // `log` runs once `high` returns, so it is still spawned on the second call
+        let second = unsafe { log_spawn(2) };
+        let third = unsafe { log_spawn(3) };
+        hprintln!("high - log_spawn(2) = {}, log_spawn(3) = {}", second, third);
+    }
+
+    #[task(priority = 1)]
+    async fn log(_: log::Context, value: u32) {
+        hprintln!("log - value = {}", value);
+    }
+}
@@ -0,0 +1,16 @@
+/* Secure aliases of the SSRAM of the AN505, which QEMU boots from in the secure state */
+MEMORY
+{
+  FLASH : ORIGIN = 0x10000000, LENGTH = 4M
+  RAM : ORIGIN = 0x38000000, LENGTH = 2M
+}
+
+/* The secure gateway veneers of the `non_secure_callable` tasks */
+SECTIONS
+{
+  .gnu.sgstubs ALIGN(32) :
+  {
+    *(.gnu.sgstubs*)
+    . = ALIGN(32);
+  } > FLASH
+} INSERT AFTER .text;
@@ -0,0 +1,82 @@
+//! Minimal device crate of the QEMU `mps2-an505` machine, a Cortex-M33 with TrustZone
+#![no_std]
+#![deny(missing_docs)]
+
+use cortex_m_rt as _;
+
+/// The device, as passed to `#[rtic::app(device = ..)]`
+pub mod an505 {
+    use cortex_m::interrupt::InterruptNumber;
+
+    /// Number of priority bits implemented by the NVIC
+    pub const NVIC_PRIO_BITS: u8 = 3;
+
+    /// The interrupts of the device
+    #[allow(missing_docs)]
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    #[repr(u16)]
+    pub enum Interrupt {
+        TIMER0 = 3,
+        TIMER1 = 4,
+        DUALTIMER = 5,
+        UART0RX = 32,
+        UART0TX = 33,
+        UART1RX = 34,
+        UART1TX = 35,
+        UART2RX = 36,
+        UART2TX = 37,
+    }
+
+    pub use self::Interrupt as interrupt;
+
+    unsafe impl InterruptNumber for Interrupt {
+        fn number(self) -> u16 {
+            self as u16
+        }
+    }
+
+    /// Whether `interrupt` targets the non-secure state, read from NVIC_ITNS
+    pub fn targets_non_secure(interrupt: Interrupt) -> bool {
+        const NVIC_ITNS: *const u32 = 0xE000_E380 as *const u32;
+
+        let nr = usize::from(interrupt.number());
+        unsafe { NVIC_ITNS.add(nr / 32).read_volatile() & (1 << (nr % 32)) != 0 }
+    }
+
+    #[doc(hidden)]
+    pub union Vector {
+        handler: unsafe extern "C" fn(),
+        reserved: usize,
+    }
+
+    unsafe extern "C" {
+        fn TIMER0();
+        fn TIMER1();
+        fn DUALTIMER();
+        fn UART0RX();
+        fn UART0TX();
+        fn UART1RX();
+        fn UART1TX();
+        fn UART2RX();
+        fn UART2TX();
+    }
+
+    const RESERVED: Vector = Vector { reserved: 0 };
+
+    #[doc(hidden)]
+    #[link_section = ".vector_table.interrupts"]
+    #[no_mangle]
+    pub static __INTERRUPTS: [Vector; 38] = {
+        let mut vectors = [RESERVED; 38];
+        vectors[3] = Vector { handler: TIMER0 };
+        vectors[4] = Vector { handler: TIMER1 };
+        vectors[5] = Vector { handler: DUALTIMER };
+        vectors[32] = Vector { handler: UART0RX };
+        vectors[33] = Vector { handler: UART0TX };
+        vectors[34] = Vector { handler: UART1RX };
+        vectors[35] = Vector { handler: UART1TX };
+        vectors[36] = Vector { handler: UART2RX };
+        vectors[37] = Vector { handler: UART2TX };
+        vectors
+    };
+}
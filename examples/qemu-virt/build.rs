@@ -0,0 +1,12 @@
+use std::{env, fs, path::PathBuf};
+
+fn main() {
+    // Put `memory.x` in the search path of the linker
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::copy("memory.x", out.join("memory.x")).unwrap();
+    println!("cargo::rustc-link-search={}", out.display());
+    println!("cargo::rerun-if-changed=memory.x");
+
+    println!("cargo::rustc-link-arg=-Tmemory.x");
+    println!("cargo::rustc-link-arg=-Tlink.x");
+}
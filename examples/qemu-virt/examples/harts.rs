@@ -0,0 +1,58 @@
+//! tasks on two harts, spawning each other and sharing a resource
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use riscv_rt as _;
+use rtic_qemu_virt::virt;
+
+#[rtic::app(device = virt, peripherals = false, backend = { harts = [0, 1] })]
+mod app {
+    use super::virt;
+    use riscv::register::mhartid;
+    use semihosting::{println, process::exit};
+
+    #[shared]
+    struct Shared {
+        // used by tasks of both harts: protected by a spin lock on top of the priority ceiling
+        rounds: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        println!("init on hart {}", mhartid::read());
+
+        ping::spawn().unwrap();
+
+        (Shared { rounds: 0 }, Local {})
+    }
+
+    #[task(shared = [rounds], priority = 1)]
+    async fn ping(mut cx: ping::Context) {
+        let rounds = cx.shared.rounds.lock(|rounds| *rounds);
+        println!("ping {} on hart {}", rounds, mhartid::read());
+
+        // `pong` runs on hart 1: spawning it raises the software interrupt of hart 1
+        pong::spawn().unwrap();
+    }
+
+    #[task(core = 1, shared = [rounds], priority = 1)]
+    async fn pong(mut cx: pong::Context) {
+        let rounds = cx.shared.rounds.lock(|rounds| {
+            *rounds += 1;
+            *rounds
+        });
+        println!("pong {} on hart {}", rounds, mhartid::read());
+
+        if rounds == 3 {
+            exit(0); // Exit QEMU simulator
+        }
+
+        ping::spawn().unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+//! hardware task bound to a PLIC source, spawning software tasks
+#![no_main]
+#![no_std]
+#![deny(warnings)]
+#![deny(unsafe_code)]
+#![deny(missing_docs)]
+
+use riscv_rt as _;
+use rtic_qemu_virt::virt;
+
+#[rtic::app(device = virt, peripherals = false, backend = { base = 0x0c00_0000 })]
+mod app {
+    use super::virt;
+    use semihosting::{println, process::exit};
+
+    #[shared]
+    struct Shared {
+        count: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        println!("init");
+
+        // The UART interrupt is raised once `init` returns
+        virt::uart0_tx_interrupt(true);
+
+        (Shared { count: 0 }, Local {})
+    }
+
+    // PLIC source 10, with a PLIC priority of 2
+    #[task(binds = UART0, priority = 2, shared = [count])]
+    fn uart(mut cx: uart::Context) {
+        virt::uart0_tx_interrupt(false);
+        println!("uart - start");
+
+        cx.shared.count.lock(|count| *count += 1);
+
+        // `low` runs once `uart` returns, `high` preempts `uart`
+        low::spawn().unwrap();
+        high::spawn().unwrap();
+
+        println!("uart - end");
+    }
+
+    #[task(priority = 3)]
+    async fn high(_: high::Context) {
+        println!("high");
+    }
+
+    #[task(priority = 1, shared = [count])]
+    async fn low(mut cx: low::Context) {
+        let count = cx.shared.count.lock(|count| *count);
+        println!("low - count = {}", count);
+
+        exit(0); // Exit QEMU simulator
+    }
+}
@@ -0,0 +1,45 @@
+//! Minimal device crate of the QEMU `virt` machine, started with `-smp 2`
+#![no_std]
+#![deny(missing_docs)]
+
+/// The device, as passed to `#[rtic::app(device = ..)]`
+pub mod virt {
+    /// Interrupts and harts of the device
+    pub mod interrupt {
+        pub use riscv::interrupt::Interrupt as CoreInterrupt;
+
+        /// The harts of the device
+        #[riscv::pac_enum(unsafe HartIdNumber)]
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum Hart {
+            /// Hart 0, running `#[init]` and `#[idle]`
+            H0 = 0,
+            /// Hart 1
+            H1 = 1,
+        }
+    }
+
+    /// The PLIC sources of the device
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Interrupt {
+        /// The NS16550A UART
+        UART0 = 10,
+    }
+
+    /// Enables or disables the "transmitter holding register empty" interrupt of the UART, which
+    /// QEMU raises as soon as it is enabled
+    pub fn uart0_tx_interrupt(enable: bool) {
+        // Interrupt enable register of the NS16550A UART
+        const IER: *mut u8 = (0x1000_0000 + 1) as *mut u8;
+
+        unsafe { IER.write_volatile(if enable { 1 << 1 } else { 0 }) };
+    }
+
+    riscv_peripheral::clint_codegen!(
+        base 0x0200_0000,
+        msips [
+            msip0 = (crate::virt::interrupt::Hart::H0, "[0](crate::virt::interrupt::Hart::H0)"),
+            msip1 = (crate::virt::interrupt::Hart::H1, "[1](crate::virt::interrupt::Hart::H1)")
+        ],
+    );
+}
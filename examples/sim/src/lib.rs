@@ -0,0 +1,71 @@
+//! An RTIC app simulated in virtual time, with the `sim-backend` feature
+//!
+//! `slow` reads and writes `count` in two critical sections instead of one, so an update of
+//! `fast` in between is lost. `cargo test` runs the tests of `tests/`, which find that race.
+
+pub mod log {
+    use std::sync::Mutex;
+
+    static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Appends a line to the log
+    pub fn push(line: String) {
+        LOG.lock().unwrap().push(line);
+    }
+
+    /// Takes the lines logged so far
+    pub fn take() -> Vec<String> {
+        std::mem::take(&mut LOG.lock().unwrap())
+    }
+}
+
+#[rtic::app(dispatchers = [SWI0])]
+mod app {
+    use crate::log;
+
+    #[shared]
+    struct Shared {
+        count: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {
+        (Shared { count: 0 }, Local {})
+    }
+
+    #[task(binds = SLOW, shared = [count], local = [runs: u32 = 0])]
+    fn slow(mut cx: slow::Context) {
+        *cx.local.runs += 1;
+
+        // BUG: `fast` can preempt between the two critical sections
+        let count = cx.shared.count.lock(|count| *count);
+        cx.shared.count.lock(|c| *c = count + 1);
+
+        log::push(format!(
+            "slow: run {}, count = {}",
+            cx.local.runs,
+            count + 1
+        ));
+        report::spawn().unwrap();
+    }
+
+    #[task(binds = FAST, priority = 2, shared = [count])]
+    fn fast(mut cx: fast::Context) {
+        let count = cx.shared.count.lock(|count| {
+            *count += 1;
+            *count
+        });
+
+        log::push(format!("fast: count = {count}"));
+    }
+
+    #[task(priority = 1, shared = [count])]
+    async fn report(mut cx: report::Context) {
+        let count = cx.shared.count.lock(|count| *count);
+
+        log::push(format!("report: count = {count}"));
+    }
+}
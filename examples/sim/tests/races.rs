@@ -0,0 +1,65 @@
+use rtic::sim::Sim;
+use rtic_sim::{app, log};
+
+/// Number of updates logged by `slow` and `fast`, and the count after the last one
+fn updates(log: &[String]) -> (u32, u32) {
+    let mut updates = 0;
+    let mut count = 0;
+    for line in log.iter().filter(|line| !line.starts_with("report")) {
+        updates += 1;
+        count = line.rsplit(' ').next().unwrap().parse().unwrap();
+    }
+
+    (updates, count)
+}
+
+#[test]
+fn scripted_interrupts() {
+    let sim = Sim::new()
+        .at(10, app::Interrupt::SLOW)
+        .at(20, app::Interrupt::FAST);
+
+    // Every run starts over from `#[init]`, with fresh task local resources
+    for _ in 0..2 {
+        sim.run(app::main);
+
+        assert_eq!(
+            log::take(),
+            [
+                "slow: run 1, count = 1",
+                "report: count = 1",
+                "fast: count = 2"
+            ]
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "lost update")]
+fn explore_finds_the_lost_update() {
+    Sim::new()
+        .at(1, app::Interrupt::SLOW)
+        .explore(app::main, &[app::Interrupt::FAST], 6, || {
+            let (updates, count) = updates(&log::take());
+            assert_eq!(count, updates, "lost update");
+        });
+}
+
+#[test]
+fn replay_the_lost_update() {
+    // Schedule 4 pends `FAST` at point 2, when `slow` releases its first lock
+    let trace =
+        Sim::new()
+            .at(1, app::Interrupt::SLOW)
+            .replay(app::main, &[app::Interrupt::FAST], 6, 4);
+
+    assert!(trace.contains(&"t=4 pend FAST (schedule, point 2: unlock)".to_string()));
+    assert_eq!(
+        log::take(),
+        [
+            "fast: count = 1",
+            "slow: run 1, count = 1",
+            "report: count = 1"
+        ]
+    );
+}
@@ -0,0 +1,32 @@
+use rtic::stats::TaskStats;
+use rtic::sim::Sim;
+use rtic_sim::{app, log};
+
+#[test]
+fn executor_stats() {
+    let sim = Sim::new()
+        .at(10, app::Interrupt::SLOW)
+        .at(20, app::Interrupt::SLOW);
+
+    // The counters start over with every run
+    for _ in 0..2 {
+        sim.run(app::main);
+        log::take();
+
+        assert_eq!(
+            app::report::stats(),
+            TaskStats {
+                spawns: 2,
+                polls: 2,
+                wakeups: 2,
+                spurious_wakeups: 0,
+                completions: 2,
+                failed_allocations: 0,
+            }
+        );
+        assert_eq!(
+            app::task_stats().collect::<Vec<_>>(),
+            [("report", app::report::stats())]
+        );
+    }
+}
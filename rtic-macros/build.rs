@@ -1,8 +1,11 @@
+/// Features that can be selected on top of the backend
+const OPTIONAL_FEATURES: &[&str] = &["default", "executor-stats", "meta-section"];
+
 fn non_default_features() -> impl Iterator<Item = String> {
     std::env::vars().filter_map(|(k, _)| {
         k.strip_prefix("CARGO_FEATURE_")
             .map(|v| v.to_lowercase().replace("_", "-"))
-            .filter(|f| f != "default")
+            .filter(|f| !OPTIONAL_FEATURES.contains(&f.as_str()))
     })
 }
 
@@ -1,17 +1,20 @@
 use core::ops;
 use std::collections::{BTreeMap, BTreeSet};
 
+use crate::codegen::util;
 use crate::syntax::{
     analyze::{self, Priority},
     ast::{App, Dispatcher},
 };
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
 use syn::Ident;
 
 /// Extend the upstream `Analysis` struct with our field
 pub struct Analysis {
     parent: analyze::Analysis,
     pub interrupts: BTreeMap<Priority, (Ident, Dispatcher)>,
-    pub max_async_prio: Option<u8>,
+    pub max_async_prio: Option<TokenStream2>,
 }
 
 impl ops::Deref for Analysis {
@@ -57,12 +60,25 @@ pub fn app(analysis: analyze::Analysis, app: &App) -> Analysis {
         })
         .collect();
 
-    let max_async_prio = app
+    // One less than the smallest HW task
+    let max_async_prio = if app
         .hardware_tasks
-        .iter()
-        .map(|(_, task)| task.args.priority)
-        .min()
-        .map(|v| v - 1); // One less than the smallest HW task
+        .values()
+        .all(|task| task.args.priority_expr.is_none())
+    {
+        app.hardware_tasks
+            .values()
+            .map(|task| task.args.priority)
+            .min()
+            .map(|v| v - 1)
+            .map(|v| quote!(#v))
+    } else {
+        let priorities = app
+            .hardware_tasks
+            .values()
+            .map(|task| util::priority(app, task.args.priority));
+        Some(quote!(rtic::export::min_priority([#(#priorities),*]) - 1))
+    };
 
     Analysis {
         parent: analysis,
@@ -9,18 +9,26 @@ pub mod bindings;
 mod assertions;
 mod async_dispatchers;
 mod extra_mods;
+mod fault;
 mod hardware_tasks;
 mod idle;
 mod init;
 mod local_resources;
 mod local_resources_struct;
+mod meta;
 mod module;
+mod mpu;
+mod persistent;
 mod post_init;
 mod pre_init;
+mod priorities;
 mod shared_resources;
 mod shared_resources_struct;
 mod software_tasks;
-mod util;
+mod stack_guard;
+mod stats;
+pub(crate) mod util;
+mod watchdog;
 
 mod main;
 
@@ -39,6 +47,14 @@ pub fn app(app: &App, analysis: &Analysis) -> TokenStream2 {
     let hardware_tasks_codegen = hardware_tasks::codegen(app, analysis);
     let software_tasks_codegen = software_tasks::codegen(app, analysis);
     let async_dispatchers_codegen = async_dispatchers::codegen(app, analysis);
+    let stack_guard_codegen = stack_guard::codegen(app, analysis);
+    let fault_codegen = fault::codegen(app, analysis);
+    let watchdog_codegen = watchdog::codegen(app);
+    let mpu_codegen = mpu::codegen(app);
+    let persistent_codegen = persistent::codegen(app);
+    let priorities_codegen = priorities::codegen(app);
+    let stats_codegen = stats::codegen(app);
+    let meta_codegen = meta::codegen(app, analysis);
 
     let user_imports = &app.user_imports;
     let user_code = &app.user_code;
@@ -47,17 +63,24 @@ pub fn app(app: &App, analysis: &Analysis) -> TokenStream2 {
     let attribute_metas = &app.attribute_metas;
 
     let rt_err = util::rt_err_ident();
+    let device = device.as_ref().map(|device| {
+        quote!(
+            /// Always include the device crate which contains the vector table
+            use #device as #rt_err;
+        )
+    });
     let async_limit = bindings::async_prio_limit(app, analysis);
 
     quote!(
         /// The RTIC application module
         #(#[#attribute_metas])*
         pub mod #name {
-            /// Always include the device crate which contains the vector table
-            use #device as #rt_err;
+            #device
 
             #(#async_limit)*
 
+            #priorities_codegen
+
             #(#user_imports)*
 
             #(#user_code)*
@@ -75,8 +98,22 @@ pub fn app(app: &App, analysis: &Analysis) -> TokenStream2 {
 
             #local_resources_codegen
 
+            #persistent_codegen
+
+            #mpu_codegen
+
             #async_dispatchers_codegen
 
+            #stack_guard_codegen
+
+            #fault_codegen
+
+            #watchdog_codegen
+
+            #stats_codegen
+
+            #meta_codegen
+
             #main
         }
     )
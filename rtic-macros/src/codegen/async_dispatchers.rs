@@ -3,7 +3,7 @@ use crate::{
     analyze::Analysis,
     codegen::{
         bindings::{async_entry, handler_config, interrupt_entry, interrupt_exit, interrupt_mod},
-        util,
+        fault, mpu, util,
     },
 };
 use proc_macro2::TokenStream as TokenStream2;
@@ -14,10 +14,15 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
     let mut items = vec![];
 
     let interrupts = &analysis.interrupts;
+    let export = util::export_mod(app);
 
     // Generate executor definition and priority in global scope
     for (name, task) in app.software_tasks.iter() {
-        items.push(util::executor_decl(name, &task.cfgs));
+        items.push(util::executor_decl(
+            name,
+            &task.cfgs,
+            task.args.executor_section.as_ref(),
+        ));
     }
 
     for (&level, channel) in &analysis.channels {
@@ -29,36 +34,110 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
             util::zero_prio_dispatcher_ident()
         };
 
-        let pend_interrupt = if level > 0 {
-            let int_mod = interrupt_mod(app);
+        let fair = app.args.fair_dispatch;
+        let n_tasks = channel.tasks.len();
+        let mut arms = vec![];
+        let mut pend_checks = vec![];
 
-            quote!(rtic::export::pend(#int_mod::#dispatcher_name);)
-        } else {
-            // For 0 priority tasks we don't need to pend anything
-            quote!()
-        };
-
-        for name in channel.tasks.iter() {
+        for (index, name) in channel.tasks.iter().enumerate() {
             let task = &app.software_tasks[name];
             let cfgs = &task.cfgs;
             let exec = util::executor_expr(name);
+            let task_waker = util::internal_task_ident(name, "WAKER");
+            let waiters = util::waiters_expr(app, name);
+
+            let poll = mpu::in_domain(app, name, quote!(#exec.poll(&#task_waker, #waiters);));
+
+            let poll = fault::track(app, name, level, poll);
+
+            // In multi-hart apps the dispatcher of a priority level runs on every hart, each one
+            // polling its own tasks
+            let poll = match util::task_hart(app, &task.args.core) {
+                Some(hart) if level > 0 => quote!(if #export::on_hart(#hart) { #poll }),
+                _ => poll,
+            };
+
+            if fair {
+                arms.push(quote!(
+                    #index => {
+                        #(#cfgs)*
+                        if #exec.is_ready() {
+                            last = Some(#index);
+                            #poll
+                        }
+                    }
+                ));
+                pend_checks.push(quote!(
+                    #(#cfgs)*
+                    {
+                        more |= #exec.is_ready();
+                    }
+                ));
+            } else {
+                stmts.push(quote!(
+                    #(#cfgs)*
+                    #poll
+                ));
+            }
+        }
+
+        // A round visits every task once, polling the ready ones, starting after the task polled
+        // last in the previous round
+        if fair {
+            let cursor = util::dispatch_cursor_ident(level);
+            items.push(quote!(
+                #[allow(non_upper_case_globals)]
+                #[doc(hidden)]
+                static #cursor: rtic::RacyCell<usize> = rtic::RacyCell::new(0);
+            ));
+
+            // A task woken during the round has its waker pend the dispatcher too, pending it here
+            // keeps the rounds going without relying on that
+            let pend = (level > 0).then(|| {
+                let int_mod = interrupt_mod(app);
+                let interrupt = &interrupts.get(&level).expect("UNREACHABLE").0;
+                let pend = util::pend_dispatcher(app, quote!(#int_mod::#interrupt), &None);
+                quote!(
+                    let mut more = false;
+                    #(#pend_checks)*
+                    if more {
+                        #pend
+                    }
+                )
+            });
 
             stmts.push(quote!(
-                #(#cfgs)*
-                #exec.poll(|| {
-                    #exec.set_pending();
-                    #pend_interrupt
-                });
+                let start = unsafe { *#cursor.get() };
+                let mut last = None;
+                for i in 0..#n_tasks {
+                    match (start + i) % #n_tasks {
+                        #(#arms)*
+                        _ => {}
+                    }
+                }
+                if let Some(last) = last {
+                    unsafe { *#cursor.get_mut() = (last + 1) % #n_tasks };
+                }
+
+                #pend
             ));
         }
 
         if level > 0 {
-            let doc = format!("Interrupt handler to dispatch async tasks at priority {level}");
+            let doc = if let Some(expr) = util::priority_expr(app, level) {
+                let expr = quote!(#expr).to_string().replace(' ', "");
+                format!("Interrupt handler to dispatch async tasks at priority `{expr}`")
+            } else {
+                format!("Interrupt handler to dispatch async tasks at priority {level}")
+            };
+            let priority = util::priority(app, level);
             let attribute = &interrupts.get(&level).expect("UNREACHABLE").1.attrs;
             let entry_stmts = interrupt_entry(app, analysis);
             let exit_stmts = interrupt_exit(app, analysis);
             let async_entry_stmts = async_entry(app, analysis, dispatcher_name.clone());
             let config = handler_config(app, analysis, dispatcher_name.clone());
+            let priority_entry = util::priority_entry(app);
+            let priority_exit = util::priority_exit(app);
             items.push(quote!(
                 #[allow(non_snake_case)]
                 #[doc = #doc]
@@ -70,12 +149,16 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
                     #(#async_entry_stmts)*
 
                     /// The priority of this interrupt handler
-                    const PRIORITY: u8 = #level;
+                    const PRIORITY: u8 = #priority;
 
-                    rtic::export::run(PRIORITY, || {
+                    #(#priority_entry)*
+
+                    #export::run(PRIORITY, || {
                         #(#stmts)*
                     });
 
+                    #(#priority_exit)*
+
                     #(#exit_stmts)*
                 }
             ));
@@ -27,3 +27,21 @@ pub use riscv_slic::*;
 
 #[cfg(riscv_slic)]
 mod riscv_slic;
+
+#[cfg(feature = "riscv-plic")]
+pub use riscv_plic::*;
+
+#[cfg(feature = "riscv-plic")]
+mod riscv_plic;
+
+#[cfg(feature = "custom")]
+pub use custom::*;
+
+#[cfg(feature = "custom")]
+mod custom;
+
+#[cfg(any(feature = "host", feature = "sim"))]
+pub use host::*;
+
+#[cfg(any(feature = "host", feature = "sim"))]
+mod host;
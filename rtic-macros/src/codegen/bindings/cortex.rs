@@ -36,11 +36,25 @@ pub fn interrupt_ident() -> Ident {
 }
 
 pub fn interrupt_mod(app: &App) -> TokenStream2 {
+    if let Some(interrupts) = &app.args.interrupts {
+        return quote!(#interrupts);
+    }
+
     let device = &app.args.device;
     let interrupt = interrupt_ident();
     quote!(#device::#interrupt)
 }
 
+/// The number of priority bits implemented by the NVIC
+fn nvic_prio_bits(app: &App) -> TokenStream2 {
+    if let Some(bits) = &app.args.prio_bits {
+        quote!(#bits)
+    } else {
+        let device = &app.args.device;
+        quote!(#device::NVIC_PRIO_BITS)
+    }
+}
+
 #[cfg(feature = "cortex-m-source-masking")]
 mod source_masking {
     use super::*;
@@ -55,7 +69,7 @@ mod source_masking {
         resources_prefix: bool,
         name: &Ident,
         ty: &TokenStream2,
-        ceiling: u8,
+        ceiling: TokenStream2,
         ptr: &TokenStream2,
     ) -> TokenStream2 {
         let path = if resources_prefix {
@@ -68,21 +82,21 @@ mod source_masking {
         let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
 
         let mut prio_to_masks = HashMap::new();
-        let device = &app.args.device;
+        let int_mod = interrupt_mod(app);
         // let mut uses_exceptions_with_resources = false;
 
         let mut mask_ids = Vec::new();
 
         for (&priority, name) in interrupt_ids.chain(app.hardware_tasks.values().flat_map(|task| {
-            if !is_exception(&task.args.binds) {
-                Some((&task.args.priority, &task.args.binds))
-            } else {
-                None
-            }
+            task.args
+                .binds
+                .iter()
+                .filter(|binds| !is_exception(binds))
+                .map(|binds| (&task.args.priority, binds))
         })) {
             let v: &mut Vec<_> = prio_to_masks.entry(priority - 1).or_default();
-            v.push(quote!(#device::Interrupt::#name as u32));
-            mask_ids.push(quote!(#device::Interrupt::#name as u32));
+            v.push(quote!(#int_mod::#name as u32));
+            mask_ids.push(quote!(#int_mod::#name as u32));
         }
 
         // Call rtic::export::create_mask([Mask; N]), where the array is the list of shifts
@@ -144,7 +158,7 @@ mod basepri {
         resources_prefix: bool,
         name: &Ident,
         ty: &TokenStream2,
-        ceiling: u8,
+        ceiling: TokenStream2,
         ptr: &TokenStream2,
     ) -> TokenStream2 {
         let path = if resources_prefix {
@@ -153,7 +167,7 @@ mod basepri {
             quote!(#name)
         };
 
-        let device = &app.args.device;
+        let nvic_prio_bits = nvic_prio_bits(app);
         quote!(
             #(#cfgs)*
             impl<'a> rtic::Mutex for #path<'a> {
@@ -168,7 +182,7 @@ mod basepri {
                         rtic::export::lock(
                             #ptr,
                             CEILING,
-                            #device::NVIC_PRIO_BITS,
+                            #nvic_prio_bits,
                             f,
                         )
                     }
@@ -191,11 +205,10 @@ pub fn pre_init_checks(app: &App, _: &SyntaxAnalysis) -> Vec<TokenStream2> {
 
     // check that all dispatchers exists in the `Interrupt` enumeration regardless of whether
     // they are used or not
-    let interrupt = interrupt_ident();
-    let rt_err = util::rt_err_ident();
+    let int_mod = interrupt_mod(app);
 
     for name in app.args.dispatchers.keys() {
-        stmts.push(quote!(let _ = #rt_err::#interrupt::#name;));
+        stmts.push(quote!(let _ = #int_mod::#name;));
     }
 
     stmts
@@ -204,21 +217,35 @@ pub fn pre_init_checks(app: &App, _: &SyntaxAnalysis) -> Vec<TokenStream2> {
 pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
     let mut stmts = vec![];
 
-    let interrupt = interrupt_ident();
-    let rt_err = util::rt_err_ident();
-    let device = &app.args.device;
-    let nvic_prio_bits = quote!(#device::NVIC_PRIO_BITS);
+    let int_mod = interrupt_mod(app);
+    let nvic_prio_bits = nvic_prio_bits(app);
     let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
 
+    // Hand interrupts over to the non-secure state, whose priorities go below the ones of the app
+    if let Some(backend) = &app.args.backend {
+        stmts.push(quote!(
+            const _: () = ::core::assert!(
+                rtic::export::trustzone::SUPPORTED,
+                "TrustZone is only supported by the `thumbv8main-backend`"
+            );
+        ));
+        stmts.push(quote!(rtic::export::trustzone::prioritize_secure();));
+
+        for name in &backend.non_secure_interrupts {
+            stmts.push(quote!(rtic::export::trustzone::target_non_secure(#int_mod::#name);));
+        }
+    }
+
     // Unmask interrupts and set their priorities
-    for (&priority, name) in interrupt_ids.chain(app.hardware_tasks.values().filter_map(|task| {
-        if is_exception(&task.args.binds) {
+    for (&priority, name) in interrupt_ids.chain(app.hardware_tasks.values().flat_map(|task| {
+        task.args
+            .binds
+            .iter()
             // We do exceptions in another pass
-            None
-        } else {
-            Some((&task.args.priority, &task.args.binds))
-        }
+            .filter(|binds| !is_exception(binds))
+            .map(|binds| (&task.args.priority, binds))
     })) {
+        let priority = util::priority(app, priority);
         let es = format!(
             "Maximum priority used by interrupt vector '{name}' is more than supported by hardware"
         );
@@ -229,24 +256,25 @@ pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<
 
         stmts.push(quote!(
             core.NVIC.set_priority(
-                #rt_err::#interrupt::#name,
+                #int_mod::#name,
                 rtic::export::cortex_logical2hw(#priority, #nvic_prio_bits),
             );
         ));
 
         // NOTE unmask the interrupt *after* setting its priority: changing the priority of a pended
         // interrupt is implementation defined
-        stmts.push(quote!(rtic::export::NVIC::unmask(#rt_err::#interrupt::#name);));
+        stmts.push(quote!(rtic::export::NVIC::unmask(#int_mod::#name);));
     }
 
     // Set exception priorities
-    for (name, priority) in app.hardware_tasks.values().filter_map(|task| {
-        if is_exception(&task.args.binds) {
-            Some((&task.args.binds, task.args.priority))
-        } else {
-            None
-        }
+    for (name, priority) in app.hardware_tasks.values().flat_map(|task| {
+        task.args
+            .binds
+            .iter()
+            .filter(|binds| is_exception(binds))
+            .map(|binds| (binds, task.args.priority))
     }) {
+        let priority = util::priority(app, priority);
         let es = format!(
             "Maximum priority used by interrupt vector '{name}' is more than supported by hardware"
         );
@@ -265,6 +293,29 @@ pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<
 }
 
 pub fn architecture_specific_analysis(app: &App, _: &SyntaxAnalysis) -> parse::Result<()> {
+    // Without a device crate the interrupt enumeration and NVIC priority bits must be given
+    if app.args.device.is_none() {
+        if app.args.prio_bits.is_none() {
+            return Err(parse::Error::new(
+                app.name.span(),
+                "`prio_bits = ..` is required when there is no `device = ..`",
+            ));
+        }
+
+        let interrupt = app.args.dispatchers.keys().next().or_else(|| {
+            app.hardware_tasks
+                .values()
+                .flat_map(|task| &task.args.binds)
+                .find(|binds| !is_exception(binds))
+        });
+        if let (Some(interrupt), None) = (interrupt, &app.args.interrupts) {
+            return Err(parse::Error::new(
+                interrupt.span(),
+                "`interrupts = ..` is required to use interrupts when there is no `device = ..`",
+            ));
+        }
+    }
+
     // Check that external (device-specific) interrupts are not named after known (Cortex-M)
     // exceptions
     for name in app.args.dispatchers.keys() {
@@ -313,8 +364,12 @@ pub fn architecture_specific_analysis(app: &App, _: &SyntaxAnalysis) -> parse::R
 
     // Check that all exceptions are valid; only exceptions with configurable priorities are
     // accepted
-    for (name, task) in &app.hardware_tasks {
-        let name_s = task.args.binds.to_string();
+    for (name, binds) in app
+        .hardware_tasks
+        .iter()
+        .flat_map(|(name, task)| task.args.binds.iter().map(move |binds| (name, binds)))
+    {
+        let name_s = binds.to_string();
         match &*name_s {
             "NonMaskableInt" | "HardFault" => {
                 return Err(parse::Error::new(
@@ -323,10 +378,219 @@ pub fn architecture_specific_analysis(app: &App, _: &SyntaxAnalysis) -> parse::R
                 ));
             }
 
+            "UsageFault" if app.args.stack_guard.is_some() => {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "`UsageFault` is used to detect stack overflows when `stack_guard` is set",
+                ));
+            }
+
             _ => {}
         }
     }
 
+    // MSPLIM only exists on ARMv8-M mainline
+    #[cfg(feature = "cortex-m-source-masking")]
+    if let Some(bytes) = &app.args.stack_guard {
+        return Err(parse::Error::new(
+            bytes.span(),
+            "`stack_guard` is only supported by the `thumbv8main-backend`",
+        ));
+    }
+
+    // The masks of every priority level are computed here, which needs the value of each priority
+    #[cfg(feature = "cortex-m-source-masking")]
+    if let Some(expr) = util::const_priority(app) {
+        return Err(parse::Error::new_spanned(
+            expr,
+            "priorities given as constant expressions are not supported by the source masking backends",
+        ));
+    }
+
+    // The MPU of ARMv6-M and ARMv8-M baseline parts is optional and too small to be useful
+    #[cfg(feature = "cortex-m-source-masking")]
+    if app.args.mpu {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "`mpu` is only supported by the `thumbv7-backend` and the `thumbv8main-backend`",
+        ));
+    }
+
+    // ARMv8-M baseline parts have TrustZone too, but the secure app can't mask the sources of the
+    // non-secure state
+    #[cfg(feature = "cortex-m-source-masking")]
+    if app.args.backend.is_some() {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "TrustZone is only supported by the `thumbv8main-backend`",
+        ));
+    }
+
+    #[cfg(feature = "cortex-m-basepri")]
+    if app.args.mpu {
+        mpu_analysis(app)?;
+    }
+
+    #[cfg(feature = "cortex-m-basepri")]
+    trustzone_analysis(app)?;
+
+    Ok(())
+}
+
+/// Checks the interrupts handed over to the non-secure state and the tasks it can spawn
+#[cfg(feature = "cortex-m-basepri")]
+fn trustzone_analysis(app: &App) -> parse::Result<()> {
+    use syn::{spanned::Spanned, Type};
+
+    let Some(backend) = &app.args.backend else {
+        return Ok(());
+    };
+
+    let mut seen = HashSet::new();
+    for name in &backend.non_secure_interrupts {
+        let error = if !seen.insert(name) {
+            "this interrupt is listed more than once"
+        } else if is_exception(name) || name == "NonMaskableInt" || name == "HardFault" {
+            "only device interrupts can target the non-secure state"
+        } else if app.args.dispatchers.contains_key(name) {
+            "this interrupt is used as a dispatcher and can't target the non-secure state"
+        } else if app
+            .hardware_tasks
+            .values()
+            .any(|task| task.args.binds.contains(name))
+        {
+            "this interrupt is bound to a hardware task and can't target the non-secure state"
+        } else {
+            continue;
+        };
+
+        return Err(parse::Error::new(name.span(), error));
+    }
+
+    let mut seen = HashSet::new();
+    for name in &backend.non_secure_callable {
+        if !seen.insert(name) {
+            return Err(parse::Error::new(
+                name.span(),
+                "this task is listed more than once",
+            ));
+        }
+
+        let Some(task) = app.software_tasks.get(name) else {
+            return Err(parse::Error::new(
+                name.span(),
+                "only software tasks can be spawned by the non-secure state",
+            ));
+        };
+
+        if task.args.local_task {
+            return Err(parse::Error::new(
+                name.span(),
+                "local tasks can't be spawned by the non-secure state",
+            ));
+        }
+
+        // The non-secure state could pass addresses of secure memory
+        for input in &task.inputs {
+            if matches!(
+                *input.ty,
+                Type::Reference(_) | Type::Ptr(_) | Type::BareFn(_)
+            ) {
+                return Err(parse::Error::new(
+                    input.ty.span(),
+                    "tasks spawned by the non-secure state can't take references, pointers or functions",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the resources of every task fit in the MPU regions RTIC uses
+#[cfg(feature = "cortex-m-basepri")]
+fn mpu_analysis(app: &App) -> parse::Result<()> {
+    use crate::syntax::ast::TaskLocal;
+
+    // One region for the local resources of the task, one per shared resource, and at least one
+    // protecting everything else
+    const MAX_GRANTS: usize = 7;
+
+    let idle = app.idle.iter().map(|idle| {
+        (
+            &idle.name,
+            &idle.args.local_resources,
+            &idle.args.shared_resources,
+        )
+    });
+    let hardware_tasks = app.hardware_tasks.iter().map(|(name, task)| {
+        (
+            name,
+            &task.args.local_resources,
+            &task.args.shared_resources,
+        )
+    });
+    let software_tasks = app.software_tasks.iter().map(|(name, task)| {
+        (
+            name,
+            &task.args.local_resources,
+            &task.args.shared_resources,
+        )
+    });
+
+    for (name, local_resources, shared_resources) in
+        idle.chain(hardware_tasks).chain(software_tasks)
+    {
+        let grants = usize::from(!local_resources.is_empty()) + shared_resources.len();
+
+        if grants > MAX_GRANTS {
+            return Err(parse::Error::new(
+                name.span(),
+                format!(
+                    "with `mpu` a task can access at most {MAX_GRANTS} shared resources, \
+                     or {} along with its local resources",
+                    MAX_GRANTS - 1
+                ),
+            ));
+        }
+
+        for (resource, task_local) in local_resources {
+            if let TaskLocal::Declared(local) = task_local {
+                if !local.attrs.is_empty() {
+                    return Err(parse::Error::new(
+                        resource.span(),
+                        "with `mpu` task local resources can't have attributes",
+                    ));
+                }
+            }
+        }
+    }
+
+    let shared = app
+        .shared_resources
+        .iter()
+        .map(|(name, res)| (name, &res.attrs, res.properties.persistent));
+    let local = app
+        .local_resources
+        .iter()
+        .map(|(name, res)| (name, &res.attrs, res.persistent));
+    for (name, attrs, persistent) in shared.chain(local) {
+        if !attrs.is_empty() {
+            return Err(parse::Error::new(
+                name.span(),
+                "with `mpu` resources can't have attributes, such as `#[link_section]`",
+            ));
+        }
+
+        // The MPU container is not in a `.uninit` section
+        if persistent {
+            return Err(parse::Error::new(
+                name.span(),
+                "with `mpu` resources can't be `#[persistent]`",
+            ));
+        }
+    }
+
     Ok(())
 }
 
@@ -347,12 +611,12 @@ pub fn async_entry(
 }
 
 pub fn async_prio_limit(app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
-    let max = if let Some(max) = analysis.max_async_prio {
+    let max = if let Some(max) = &analysis.max_async_prio {
         quote!(#max)
     } else {
         // No limit
-        let device = &app.args.device;
-        quote!(1 << #device::NVIC_PRIO_BITS)
+        let nvic_prio_bits = nvic_prio_bits(app);
+        quote!(1 << #nvic_prio_bits)
     };
 
     vec![quote!(
@@ -369,6 +633,68 @@ pub fn handler_config(
     vec![]
 }
 
-pub fn extra_modules(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
-    vec![]
+/// The `cmse-nonsecure-entry` functions of the tasks the non-secure state can spawn, named
+/// `<task>_spawn` and returning `0` once spawned or `-1` when the task is already running
+pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let Some(backend) = &app.args.backend else {
+        return vec![];
+    };
+
+    backend
+        .non_secure_callable
+        .iter()
+        .map(|name| {
+            let task = &app.software_tasks[name];
+            let cfgs = &task.cfgs;
+            let (input_args, _, input_untupled, _) = util::regroup_inputs(&task.inputs);
+            let export_name = format!("{name}_spawn");
+            let entry = util::internal_task_ident(name, "non_secure_spawn");
+
+            quote!(
+                #(#cfgs)*
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                #[export_name = #export_name]
+                pub extern "cmse-nonsecure-entry" fn #entry(#(#input_args),*) -> i32 {
+                    match #name::spawn(#(#input_untupled),*) {
+                        Ok(()) => 0,
+                        Err(_) => -1,
+                    }
+                }
+            )
+        })
+        .collect()
+}
+
+/// The `HardFault` handler, which must not touch the stack before RTIC has found the stacked
+/// registers
+pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![quote!(
+        #[doc(hidden)]
+        #[no_mangle]
+        #[unsafe(naked)]
+        unsafe extern "C" fn HardFault() -> ! {
+            // `b` can't reach far enough on ARMv6-M
+            ::core::arch::naked_asm!(
+                "ldr r1, ={}",
+                "bx r1",
+                sym rtic::export::fault::hard_fault,
+            )
+        }
+    )]
+}
+
+/// The exception handler raised when the stack grows past MSPLIM.
+///
+/// The stack pointer sits at the limit when the fault is taken, so the handler must not push
+/// anything before RTIC has lifted the limit.
+pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![quote!(
+        #[doc(hidden)]
+        #[no_mangle]
+        #[unsafe(naked)]
+        unsafe extern "C" fn UsageFault() -> ! {
+            ::core::arch::naked_asm!("b {}", sym rtic::export::stack_guard::usage_fault)
+        }
+    )]
 }
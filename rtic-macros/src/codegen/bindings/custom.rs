@@ -0,0 +1,308 @@
+//! Backend implemented out of tree, through the `rtic::Backend` trait
+//!
+//! The type given in `#[app(backend = ..)]` is wrapped in the `__rtic_internal_backend` module of
+//! the app, which provides the functions the rest of the generated code takes from `rtic::export`
+//! on the built-in backends.
+
+use crate::{
+    analyze::Analysis as CodegenAnalysis,
+    codegen::util,
+    syntax::{analyze::Analysis as SyntaxAnalysis, ast::App},
+};
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse, Attribute, Ident};
+
+pub fn interrupt_ident() -> Ident {
+    let span = Span::call_site();
+    Ident::new("interrupt", span)
+}
+
+pub fn interrupt_mod(app: &App) -> TokenStream2 {
+    if let Some(interrupts) = &app.args.interrupts {
+        return quote!(#interrupts);
+    }
+
+    let device = &app.args.device;
+    let interrupt = interrupt_ident();
+    quote!(#device::#interrupt)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn impl_mutex(
+    app: &App,
+    _analysis: &CodegenAnalysis,
+    cfgs: &[Attribute],
+    resources_prefix: bool,
+    name: &Ident,
+    ty: &TokenStream2,
+    ceiling: TokenStream2,
+    ptr: &TokenStream2,
+) -> TokenStream2 {
+    let path = if resources_prefix {
+        quote!(shared_resources::#name)
+    } else {
+        quote!(#name)
+    };
+    let export = util::export_mod(app);
+
+    quote!(
+        #(#cfgs)*
+        impl<'a> rtic::Mutex for #path<'a> {
+            type T = #ty;
+
+            #[inline(always)]
+            fn lock<RTIC_INTERNAL_R>(&mut self, f: impl FnOnce(&mut #ty) -> RTIC_INTERNAL_R) -> RTIC_INTERNAL_R {
+                /// Priority ceiling
+                const CEILING: u8 = #ceiling;
+
+                unsafe { #export::lock(#ptr, CEILING, f) }
+            }
+        }
+    )
+}
+
+pub fn extra_assertions(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn pre_init_preprocessing(app: &mut App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    app.args.core = false; // The core peripherals are specific to Cortex-M
+    Ok(())
+}
+
+pub fn pre_init_checks(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    // check that all dispatchers exists in the interrupt enumeration
+    let int_mod = interrupt_mod(app);
+    for name in app.args.dispatchers.keys() {
+        stmts.push(quote!(let _ = #int_mod::#name;));
+    }
+
+    stmts
+}
+
+pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    let int_mod = interrupt_mod(app);
+    let export = util::export_mod(app);
+    let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
+
+    for (&priority, name) in interrupt_ids.chain(app.hardware_tasks.values().flat_map(|task| {
+        task.args
+            .binds
+            .iter()
+            .map(move |binds| (&task.args.priority, binds))
+    })) {
+        let priority = util::priority(app, priority);
+        let es = format!(
+            "Maximum priority used by interrupt vector '{name}' is more than supported by the backend"
+        );
+        // Compile time assert that this priority is supported by the backend
+        stmts.push(quote!(
+            const _: () = if #export::MAX_PRIORITY < #priority { ::core::panic!(#es); };
+        ));
+
+        stmts.push(quote!(
+            #export::enable_interrupt(#int_mod::#name, #priority);
+        ));
+    }
+
+    stmts
+}
+
+pub fn architecture_specific_analysis(app: &App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    if app.args.backend.is_none() {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "the custom backend requires `backend = ..`, the type implementing `rtic::Backend`",
+        ));
+    }
+
+    if let Some(bytes) = &app.args.stack_guard {
+        return Err(parse::Error::new(
+            bytes.span(),
+            "`stack_guard` is not supported by the custom backend",
+        ));
+    }
+
+    if app.args.mpu {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "`mpu` is not supported by the custom backend",
+        ));
+    }
+
+    if app.args.stack_usage {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "`stack_usage` is not supported by the custom backend",
+        ));
+    }
+
+    if let Some(bits) = &app.args.prio_bits {
+        return Err(parse::Error::new(
+            bits.span(),
+            "`prio_bits` is not supported by the custom backend, see `rtic::Backend::MAX_PRIORITY`",
+        ));
+    }
+
+    let interrupt = app
+        .args
+        .dispatchers
+        .keys()
+        .chain(
+            app.hardware_tasks
+                .values()
+                .flat_map(|task| &task.args.binds),
+        )
+        .next();
+    if let (Some(interrupt), None, None) = (interrupt, &app.args.interrupts, &app.args.device) {
+        return Err(parse::Error::new(
+            interrupt.span(),
+            "`interrupts = ..` is required to use interrupts when there is no `device = ..`",
+        ));
+    }
+
+    // Check that there are enough external interrupts to dispatch the software tasks
+    let mut first = None;
+    let priorities = app
+        .software_tasks
+        .iter()
+        .map(|(name, task)| {
+            first = Some(name);
+            task.args.priority
+        })
+        .filter(|prio| *prio > 0)
+        .collect::<HashSet<_>>();
+
+    let need = priorities.len();
+    let given = app.args.dispatchers.len();
+    if need > given {
+        let s = format!(
+            "not enough interrupts to dispatch all software tasks (need: {need}; given: {given})"
+        );
+
+        return Err(parse::Error::new(first.unwrap().span(), s));
+    }
+
+    Ok(())
+}
+
+pub fn interrupt_entry(app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let export = util::export_mod(app);
+    vec![quote!(#export::interrupt_entry();)]
+}
+
+pub fn interrupt_exit(app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let export = util::export_mod(app);
+    vec![quote!(#export::interrupt_exit();)]
+}
+
+pub fn async_entry(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    _dispatcher_name: Ident,
+) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn async_prio_limit(app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let max = if let Some(max) = &analysis.max_async_prio {
+        quote!(#max)
+    } else {
+        let export = util::export_mod(app);
+        quote!(#export::MAX_PRIORITY)
+    };
+
+    vec![quote!(
+        /// Holds the maximum priority level for use by async HAL drivers.
+        #[no_mangle]
+        static RTIC_ASYNC_MAX_LOGICAL_PRIO: u8 = #max;
+    )]
+}
+
+pub fn handler_config(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    _dispatcher_name: Ident,
+) -> Vec<TokenStream2> {
+    vec![]
+}
+
+/// The functions the generated code calls, forwarded to the `rtic::Backend` implementation
+pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let backend = &app.args.backend.as_ref().expect("UNREACHABLE").path;
+    let export = util::export_mod(app);
+
+    vec![quote!(
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        mod #export {
+            use rtic::Backend as _;
+
+            type Backend = #backend;
+
+            pub const MAX_PRIORITY: u8 = <Backend as rtic::Backend>::MAX_PRIORITY;
+
+            #[inline(always)]
+            pub unsafe fn enable_interrupt(
+                interrupt: <Backend as rtic::Backend>::Interrupt,
+                priority: u8,
+            ) {
+                unsafe { Backend::enable_interrupt(interrupt, priority) }
+            }
+
+            #[inline(always)]
+            pub fn pend(interrupt: <Backend as rtic::Backend>::Interrupt) {
+                Backend::pend(interrupt)
+            }
+
+            #[inline(always)]
+            pub fn run(priority: u8, f: impl FnOnce()) {
+                Backend::run(priority, f)
+            }
+
+            #[inline(always)]
+            pub unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+                unsafe { Backend::lock(ptr, ceiling, f) }
+            }
+
+            #[inline(always)]
+            pub fn interrupt_entry() {
+                Backend::interrupt_entry()
+            }
+
+            #[inline(always)]
+            pub fn interrupt_exit() {
+                Backend::interrupt_exit()
+            }
+
+            pub mod interrupt {
+                use rtic::Backend as _;
+
+                #[inline(always)]
+                pub fn disable() {
+                    super::Backend::interrupt_disable()
+                }
+
+                #[inline(always)]
+                pub unsafe fn enable() {
+                    unsafe { super::Backend::interrupt_enable() }
+                }
+            }
+        }
+    )]
+}
+
+pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
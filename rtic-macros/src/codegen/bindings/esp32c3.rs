@@ -22,7 +22,7 @@ mod esp32c3 {
         resources_prefix: bool,
         name: &Ident,
         ty: &TokenStream2,
-        ceiling: u8,
+        ceiling: TokenStream2,
         ptr: &TokenStream2,
     ) -> TokenStream2 {
         let path = if resources_prefix {
@@ -90,13 +90,14 @@ mod esp32c3 {
         let min_prio: usize = 1;
         let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
         // Unmask interrupts and set their priorities
-        for (curr_cpu_id, (&priority, name)) in (curr_cpu_id..).zip(
-            interrupt_ids.chain(
-                app.hardware_tasks
-                    .values()
-                    .map(|task| (&task.args.priority, &task.args.binds)),
-            ),
-        ) {
+        for (curr_cpu_id, (&priority, name)) in (curr_cpu_id..).zip(interrupt_ids.chain(
+            app.hardware_tasks.values().flat_map(|task| {
+                task.args
+                    .binds
+                    .iter()
+                    .map(move |binds| (&task.args.priority, binds))
+            }),
+        )) {
             let es = format!(
                 "Maximum priority used by interrupt vector '{name}' is more than supported by hardware"
             );
@@ -121,6 +122,48 @@ mod esp32c3 {
         app: &App,
         _analysis: &SyntaxAnalysis,
     ) -> parse::Result<()> {
+        if app.args.device.is_none() {
+            return Err(parse::Error::new(
+                app.name.span(),
+                "the ESP32 backends require `device = ..`",
+            ));
+        }
+
+        if let Some(path) = &app.args.interrupts {
+            return Err(parse::Error::new_spanned(
+                path,
+                "`interrupts` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if let Some(bits) = &app.args.prio_bits {
+            return Err(parse::Error::new(
+                bits.span(),
+                "`prio_bits` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if let Some(bytes) = &app.args.stack_guard {
+            return Err(parse::Error::new(
+                bytes.span(),
+                "`stack_guard` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if app.args.mpu {
+            return Err(parse::Error::new(
+                app.name.span(),
+                "`mpu` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if let Some(expr) = util::const_priority(app) {
+            return Err(parse::Error::new_spanned(
+                expr,
+                "priorities given as constant expressions are not supported by the ESP32 backends",
+            ));
+        }
+
         //check if the dispatchers are supported
         for name in app.args.dispatchers.keys() {
             let name_s = name.to_string();
@@ -187,7 +230,7 @@ mod esp32c3 {
     }
 
     pub fn async_prio_limit(_app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
-        let max = if let Some(max) = analysis.max_async_prio {
+        let max = if let Some(max) = &analysis.max_async_prio {
             quote!(#max)
         } else {
             quote!(u8::MAX) // No limit
@@ -208,13 +251,14 @@ mod esp32c3 {
         let mut stmts = vec![];
         let curr_cpu_id = 16; // cpu interrupt ids 0-15 are reserved
         let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
-        for (curr_cpu_id, (_, name)) in (curr_cpu_id..).zip(
-            interrupt_ids.chain(
-                app.hardware_tasks
-                    .values()
-                    .map(|task| (&task.args.priority, &task.args.binds)),
-            ),
-        ) {
+        for (curr_cpu_id, (_, name)) in (curr_cpu_id..).zip(interrupt_ids.chain(
+            app.hardware_tasks.values().flat_map(|task| {
+                task.args
+                    .binds
+                    .iter()
+                    .map(move |binds| (&task.args.priority, binds))
+            }),
+        )) {
             if *name == dispatcher_name {
                 let ret = &("interrupt".to_owned() + &curr_cpu_id.to_string());
                 stmts.push(quote!(#[export_name = #ret]));
@@ -227,4 +271,12 @@ mod esp32c3 {
     pub fn extra_modules(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
         vec![]
     }
+
+    pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+        vec![]
+    }
+
+    pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+        vec![]
+    }
 }
@@ -27,7 +27,7 @@ mod esp32c6 {
         resources_prefix: bool,
         name: &Ident,
         ty: &TokenStream2,
-        ceiling: u8,
+        ceiling: TokenStream2,
         ptr: &TokenStream2,
     ) -> TokenStream2 {
         let path = if resources_prefix {
@@ -95,11 +95,12 @@ mod esp32c6 {
         let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
         // Unmask interrupts and set their priorities
         for ((&priority, name), curr_cpu_id) in interrupt_ids
-            .chain(
-                app.hardware_tasks
-                    .values()
-                    .map(|task| (&task.args.priority, &task.args.binds)),
-            )
+            .chain(app.hardware_tasks.values().flat_map(|task| {
+                task.args
+                    .binds
+                    .iter()
+                    .map(move |binds| (&task.args.priority, binds))
+            }))
             .zip(EXTERNAL_INTERRUPTS)
         {
             let es = format!(
@@ -126,6 +127,48 @@ mod esp32c6 {
         app: &App,
         _analysis: &SyntaxAnalysis,
     ) -> parse::Result<()> {
+        if app.args.device.is_none() {
+            return Err(parse::Error::new(
+                app.name.span(),
+                "the ESP32 backends require `device = ..`",
+            ));
+        }
+
+        if let Some(path) = &app.args.interrupts {
+            return Err(parse::Error::new_spanned(
+                path,
+                "`interrupts` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if let Some(bits) = &app.args.prio_bits {
+            return Err(parse::Error::new(
+                bits.span(),
+                "`prio_bits` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if let Some(bytes) = &app.args.stack_guard {
+            return Err(parse::Error::new(
+                bytes.span(),
+                "`stack_guard` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if app.args.mpu {
+            return Err(parse::Error::new(
+                app.name.span(),
+                "`mpu` is not supported by the ESP32 backends",
+            ));
+        }
+
+        if let Some(expr) = util::const_priority(app) {
+            return Err(parse::Error::new_spanned(
+                expr,
+                "priorities given as constant expressions are not supported by the ESP32 backends",
+            ));
+        }
+
         //check if the dispatchers are supported
         for name in app.args.dispatchers.keys() {
             let name_s = name.to_string();
@@ -192,7 +235,7 @@ mod esp32c6 {
     }
 
     pub fn async_prio_limit(_app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
-        let max = if let Some(max) = analysis.max_async_prio {
+        let max = if let Some(max) = &analysis.max_async_prio {
             quote!(#max)
         } else {
             quote!(u8::MAX) // No limit
@@ -213,11 +256,12 @@ mod esp32c6 {
         let mut stmts = vec![];
         let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
         for ((_, name), curr_cpu_id) in interrupt_ids
-            .chain(
-                app.hardware_tasks
-                    .values()
-                    .map(|task| (&task.args.priority, &task.args.binds)),
-            )
+            .chain(app.hardware_tasks.values().flat_map(|task| {
+                task.args
+                    .binds
+                    .iter()
+                    .map(move |binds| (&task.args.priority, binds))
+            }))
             .zip(EXTERNAL_INTERRUPTS)
         {
             if *name == dispatcher_name {
@@ -232,4 +276,12 @@ mod esp32c6 {
     pub fn extra_modules(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
         vec![]
     }
+
+    pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+        vec![]
+    }
+
+    pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+        vec![]
+    }
 }
@@ -0,0 +1,314 @@
+//! Backends simulating the interrupt controller, to run apps on the host
+//!
+//! See `rtic::host` and, for the deterministic simulation of the sim backend, `rtic::sim`. Without
+//! `interrupts = ..`, the app module gets an `Interrupt` enumeration of the interrupts bound by
+//! hardware tasks and used as dispatchers.
+
+use crate::{
+    analyze::Analysis as CodegenAnalysis,
+    codegen::util,
+    syntax::{analyze::Analysis as SyntaxAnalysis, ast::App},
+};
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse, Attribute, Ident};
+
+/// Name of the backend in error messages
+const BACKEND: &str = if cfg!(feature = "sim") { "sim" } else { "host" };
+
+pub fn interrupt_ident() -> Ident {
+    let span = Span::call_site();
+    Ident::new("Interrupt", span)
+}
+
+pub fn interrupt_mod(app: &App) -> TokenStream2 {
+    if let Some(interrupts) = &app.args.interrupts {
+        return quote!(#interrupts);
+    }
+
+    let interrupt = interrupt_ident();
+    quote!(#interrupt)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn impl_mutex(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    cfgs: &[Attribute],
+    resources_prefix: bool,
+    name: &Ident,
+    ty: &TokenStream2,
+    ceiling: TokenStream2,
+    ptr: &TokenStream2,
+) -> TokenStream2 {
+    let path = if resources_prefix {
+        quote!(shared_resources::#name)
+    } else {
+        quote!(#name)
+    };
+
+    quote!(
+        #(#cfgs)*
+        impl<'a> rtic::Mutex for #path<'a> {
+            type T = #ty;
+
+            #[inline(always)]
+            fn lock<RTIC_INTERNAL_R>(&mut self, f: impl FnOnce(&mut #ty) -> RTIC_INTERNAL_R) -> RTIC_INTERNAL_R {
+                /// Priority ceiling
+                const CEILING: u8 = #ceiling;
+
+                unsafe { rtic::export::lock(#ptr, CEILING, f) }
+            }
+        }
+    )
+}
+
+pub fn extra_assertions(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn pre_init_preprocessing(app: &mut App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    app.args.core = false; // There are no core peripherals on the host
+    Ok(())
+}
+
+pub fn pre_init_checks(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    // check that all dispatchers exists in the interrupt enumeration
+    let int_mod = interrupt_mod(app);
+    for name in app.args.dispatchers.keys() {
+        stmts.push(quote!(let _ = #int_mod::#name;));
+    }
+
+    // Every simulation runs the app from the start: forget the tasks of the previous run and give
+    // the task local resources their initial values again
+    if cfg!(feature = "sim") {
+        for (name, task) in &app.software_tasks {
+            let cfgs = &task.cfgs;
+            let exec = util::executor_expr(name);
+            let waiters = util::waiters_expr(app, name);
+            stmts.push(quote!(
+                #(#cfgs)*
+                #exec.reset(#waiters);
+            ));
+        }
+
+        if app.args.fair_dispatch {
+            let levels = app
+                .software_tasks
+                .values()
+                .map(|task| task.args.priority)
+                .collect::<std::collections::BTreeSet<_>>();
+            for level in levels {
+                let cursor = util::dispatch_cursor_ident(level);
+                stmts.push(quote!(unsafe { *#cursor.get_mut() = 0 };));
+            }
+        }
+
+        for (task_name, name, local) in app.declared_local_resources() {
+            let cfgs = &local.cfgs;
+            let expr = &local.expr;
+            let mangled_name = util::declared_static_local_resource_ident(name, task_name);
+            stmts.push(quote!(
+                #(#cfgs)*
+                unsafe { #mangled_name.get_mut().write(#expr) };
+            ));
+        }
+    }
+
+    stmts
+}
+
+pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    let int_mod = interrupt_mod(app);
+    let interrupt_ids = analysis
+        .interrupts
+        .iter()
+        .map(|(p, (id, _))| (p, id, &[][..]));
+
+    // Register the handler of each interrupt with its priority
+    for (&priority, name, cfgs) in
+        interrupt_ids.chain(app.hardware_tasks.values().flat_map(|task| {
+            task.args
+                .binds
+                .iter()
+                .map(move |binds| (&task.args.priority, binds, &task.cfgs[..]))
+        }))
+    {
+        let priority = util::priority(app, priority);
+        stmts.push(quote!(
+            #(#cfgs)*
+            rtic::export::enable_interrupt(#int_mod::#name, #priority, #name);
+        ));
+    }
+
+    stmts
+}
+
+pub fn architecture_specific_analysis(app: &App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    if let Some(device) = &app.args.device {
+        return Err(parse::Error::new_spanned(
+            device,
+            format!("the {BACKEND} backend doesn't use a device crate; remove `device = ..`"),
+        ));
+    }
+
+    if let Some(bits) = &app.args.prio_bits {
+        return Err(parse::Error::new(
+            bits.span(),
+            format!("`prio_bits` is not supported by the {BACKEND} backend"),
+        ));
+    }
+
+    if let Some(bytes) = &app.args.stack_guard {
+        return Err(parse::Error::new(
+            bytes.span(),
+            format!("`stack_guard` is not supported by the {BACKEND} backend"),
+        ));
+    }
+
+    if app.args.mpu {
+        return Err(parse::Error::new(
+            app.name.span(),
+            format!("`mpu` is not supported by the {BACKEND} backend"),
+        ));
+    }
+
+    if app.args.stack_usage {
+        return Err(parse::Error::new(
+            app.name.span(),
+            format!("`stack_usage` is not supported by the {BACKEND} backend"),
+        ));
+    }
+
+    // The simulation ends when `#[idle]` waits for interrupts, which the dispatcher of priority 0
+    // never does
+    if cfg!(feature = "sim") {
+        if let Some((name, _)) = app
+            .software_tasks
+            .iter()
+            .find(|(_, task)| task.args.priority == 0)
+        {
+            return Err(parse::Error::new(
+                name.span(),
+                "software tasks of priority 0 are not supported by the sim backend",
+            ));
+        }
+    }
+
+    // Check that there are enough external interrupts to dispatch the software tasks
+    let mut first = None;
+    let priorities = app
+        .software_tasks
+        .iter()
+        .map(|(name, task)| {
+            first = Some(name);
+            task.args.priority
+        })
+        .filter(|prio| *prio > 0)
+        .collect::<HashSet<_>>();
+
+    let need = priorities.len();
+    let given = app.args.dispatchers.len();
+    if need > given {
+        let s = format!(
+            "not enough interrupts to dispatch all software tasks (need: {need}; given: {given})"
+        );
+
+        return Err(parse::Error::new(first.unwrap().span(), s));
+    }
+
+    Ok(())
+}
+
+pub fn interrupt_entry(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn interrupt_exit(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn async_entry(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    _dispatcher_name: Ident,
+) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn async_prio_limit(_app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let max = if let Some(max) = &analysis.max_async_prio {
+        quote!(#max)
+    } else {
+        // No limit
+        quote!(u8::MAX)
+    };
+
+    vec![quote!(
+        /// Holds the maximum priority level for use by async HAL drivers.
+        #[no_mangle]
+        static RTIC_ASYNC_MAX_LOGICAL_PRIO: u8 = #max;
+    )]
+}
+
+pub fn handler_config(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    _dispatcher_name: Ident,
+) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    if app.args.interrupts.is_some() {
+        return vec![];
+    }
+
+    let mut names: Vec<&Ident> = vec![];
+    for name in app.args.dispatchers.keys().chain(
+        app.hardware_tasks
+            .values()
+            .flat_map(|task| &task.args.binds),
+    ) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        return vec![];
+    }
+    let interrupt = interrupt_ident();
+
+    vec![quote!(
+        /// The interrupts of the app, simulated on the host
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(u16)]
+        pub enum #interrupt {
+            #(
+                #[allow(missing_docs)]
+                #names,
+            )*
+        }
+
+        impl rtic::export::InterruptNumber for #interrupt {
+            fn number(self) -> u16 {
+                self as u16
+            }
+        }
+    )]
+}
+
+pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
@@ -0,0 +1,370 @@
+//! Backend for RISC-V devices with a standard PLIC
+//!
+//! Hardware tasks bind to the PLIC sources of the `Interrupt` enumeration of the device, or of
+//! `interrupts = ..`. The dispatchers of software tasks are software interrupts of the backend,
+//! generated like on the SLIC backends, as the PLIC can't pend its sources from software.
+
+use crate::{
+    analyze::Analysis as CodegenAnalysis,
+    codegen::util,
+    syntax::{
+        analyze::Analysis as SyntaxAnalysis,
+        ast::{App, Dispatcher},
+    },
+};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use std::collections::HashSet;
+use syn::{parse, Attribute, Ident};
+
+pub fn interrupt_ident() -> Ident {
+    let span = Span::call_site();
+    Ident::new("SoftwareInterrupt", span)
+}
+
+/// The software interrupts of the dispatchers
+pub fn interrupt_mod(_app: &App) -> TokenStream2 {
+    let interrupt = interrupt_ident();
+    quote!(plic::#interrupt)
+}
+
+/// The enumeration of the PLIC sources hardware tasks bind to
+fn source_mod(app: &App) -> TokenStream2 {
+    if let Some(interrupts) = &app.args.interrupts {
+        return quote!(#interrupts);
+    }
+
+    let device = util::rt_err_ident();
+    quote!(#device::Interrupt)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn impl_mutex(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    cfgs: &[Attribute],
+    resources_prefix: bool,
+    name: &Ident,
+    ty: &TokenStream2,
+    ceiling: TokenStream2,
+    ptr: &TokenStream2,
+) -> TokenStream2 {
+    let path = if resources_prefix {
+        quote!(shared_resources::#name)
+    } else {
+        quote!(#name)
+    };
+
+    quote!(
+        #(#cfgs)*
+        impl<'a> rtic::Mutex for #path<'a> {
+            type T = #ty;
+
+            #[inline(always)]
+            fn lock<RTIC_INTERNAL_R>(&mut self, f: impl FnOnce(&mut #ty) -> RTIC_INTERNAL_R) -> RTIC_INTERNAL_R {
+                /// Priority ceiling
+                const CEILING: u8 = #ceiling;
+
+                unsafe { __rtic_internal_backend::lock(#ptr, CEILING, f) }
+            }
+        }
+    )
+}
+
+pub fn extra_assertions(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn pre_init_preprocessing(app: &mut App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    app.args.core = false; // There are no Cortex-M core peripherals
+    if !app.args.dispatchers.is_empty() {
+        return Err(parse::Error::new(
+            Span::call_site(),
+            "the PLIC backend does not support explicit interrupt dispatchers; remove the `dispatchers` argument from `#[app]`",
+        ));
+    }
+
+    // One software interrupt per priority of the software tasks
+    let soft_priorities = app
+        .software_tasks
+        .values()
+        .map(|task| task.args.priority)
+        .filter(|prio| *prio > 0)
+        .collect::<HashSet<_>>();
+
+    for i in 0..soft_priorities.len() {
+        let dispatcher_ident = Ident::new(&format!("__RTICDispatcher{i}"), Span::call_site());
+        app.args
+            .dispatchers
+            .insert(dispatcher_ident, Dispatcher { attrs: vec![] });
+    }
+
+    Ok(())
+}
+
+pub fn pre_init_checks(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    // check that all dispatchers exists in the `plic::SoftwareInterrupt` enumeration
+    let int_mod = interrupt_mod(app);
+    for name in app.args.dispatchers.keys() {
+        stmts.push(quote!(let _ = #int_mod::#name;));
+    }
+
+    stmts
+}
+
+pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    let int_mod = interrupt_mod(app);
+    for (&priority, (name, _)) in &analysis.interrupts {
+        stmts.push(quote!(
+            __rtic_internal_backend::PLIC.set_priority(#int_mod::#name as u16, #priority);
+        ));
+    }
+
+    // The PLIC priority of a source is the priority of its task
+    let source_mod = source_mod(app);
+    for task in app.hardware_tasks.values() {
+        let cfgs = &task.cfgs;
+        let priority = task.args.priority;
+        for binds in &task.args.binds {
+            stmts.push(quote!(
+                #(#cfgs)*
+                unsafe {
+                    __rtic_internal_backend::PLIC.enable_source(#source_mod::#binds as u16, #priority)
+                };
+            ));
+        }
+    }
+
+    stmts
+}
+
+pub fn architecture_specific_analysis(app: &App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    let Some(backend) = &app.args.backend else {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "the PLIC backend requires `backend = { base = .. }`, the base address of the PLIC",
+        ));
+    };
+
+    if app.args.device.is_none() && app.args.interrupts.is_none() {
+        if let Some(task) = app.hardware_tasks.values().next() {
+            return Err(parse::Error::new(
+                task.args.binds[0].span(),
+                "hardware tasks require `device = ..` or `interrupts = ..`, the enumeration of the PLIC sources",
+            ));
+        }
+    }
+
+    if let Some(bits) = &app.args.prio_bits {
+        return Err(parse::Error::new(
+            bits.span(),
+            "`prio_bits` is not supported by the PLIC backend; use `backend = { max_priority = .. }`",
+        ));
+    }
+
+    if app.args.mpu {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "`mpu` is not supported by the PLIC backend",
+        ));
+    }
+
+    if let Some(expr) = util::const_priority(app) {
+        return Err(parse::Error::new_spanned(
+            expr,
+            "priorities given as constant expressions are not supported by the PLIC backend",
+        ));
+    }
+
+    // The threshold can only be raised up to the highest priority of the PLIC
+    let max_priority = backend.max_priority()?;
+    let priorities = app
+        .hardware_tasks
+        .iter()
+        .map(|(name, task)| (name, task.args.priority))
+        .chain(
+            app.software_tasks
+                .iter()
+                .map(|(name, task)| (name, task.args.priority)),
+        );
+    for (name, priority) in priorities {
+        if priority > max_priority {
+            return Err(parse::Error::new(
+                name.span(),
+                format!("the priority of this task is above the highest priority of the PLIC ({max_priority})"),
+            ));
+        }
+    }
+
+    // Check that there are enough software interrupts to dispatch the software tasks
+    let mut first = None;
+    let priorities = app
+        .software_tasks
+        .iter()
+        .map(|(name, task)| {
+            first = Some(name);
+            task.args.priority
+        })
+        .filter(|prio| *prio > 0)
+        .collect::<HashSet<_>>();
+
+    let need = priorities.len();
+    let given = app.args.dispatchers.len();
+    if need > given {
+        let s = format!(
+            "not enough interrupts to dispatch all software tasks (need: {need}; given: {given})"
+        );
+
+        return Err(parse::Error::new(first.unwrap().span(), s));
+    }
+
+    Ok(())
+}
+
+pub fn interrupt_entry(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn interrupt_exit(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn async_entry(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    _dispatcher_name: Ident,
+) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn async_prio_limit(_app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    let max = if let Some(max) = &analysis.max_async_prio {
+        quote!(#max)
+    } else {
+        // No limit
+        quote!(u8::MAX)
+    };
+
+    vec![quote!(
+        /// Holds the maximum priority level for use by async HAL drivers.
+        #[no_mangle]
+        static RTIC_ASYNC_MAX_LOGICAL_PRIO: u8 = #max;
+    )]
+}
+
+pub fn handler_config(
+    _app: &App,
+    _analysis: &CodegenAnalysis,
+    _dispatcher_name: Ident,
+) -> Vec<TokenStream2> {
+    vec![]
+}
+
+/// The software interrupts of the dispatchers, the `Plic` of the app and the machine external
+/// interrupt handler, running the handlers of the PLIC sources
+pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let backend = app.args.backend.as_ref().expect("UNREACHABLE");
+    let base = &backend.base;
+    let context = match &backend.context {
+        Some(context) => quote!(#context),
+        None => quote!(0),
+    };
+
+    let interrupt = interrupt_ident();
+    let dispatchers: Vec<_> = app.args.dispatchers.keys().collect();
+    let n = dispatchers.len();
+    let numbers = (0..n).map(Literal::usize_unsuffixed);
+
+    let pend = if dispatchers.is_empty() {
+        quote!()
+    } else {
+        quote!(
+            #[inline(always)]
+            pub fn pend(interrupt: super::plic::#interrupt) {
+                PLIC.pend(interrupt as u16);
+            }
+        )
+    };
+
+    let source_mod = source_mod(app);
+    let source_mod = &source_mod;
+    let sources = app.hardware_tasks.values().flat_map(|task| {
+        let cfgs = &task.cfgs;
+        task.args.binds.iter().map(move |binds| {
+            quote!(
+                #(#cfgs)*
+                if source == #source_mod::#binds as u16 {
+                    return unsafe { #binds() };
+                }
+            )
+        })
+    });
+
+    vec![quote!(
+        /// The software interrupts dispatching the software tasks
+        #[doc(hidden)]
+        pub mod plic {
+            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+            pub enum #interrupt {
+                #(#dispatchers = #numbers,)*
+            }
+        }
+
+        #[doc(hidden)]
+        mod __rtic_internal_backend {
+            pub use rtic::export::run;
+
+            pub static PLIC: rtic::export::Plic<#n> =
+                rtic::export::Plic::new(#base, #context, [#(super::#dispatchers,)*]);
+
+            #pend
+
+            #[inline(always)]
+            pub unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+                unsafe { PLIC.lock(ptr, ceiling, f) }
+            }
+
+            pub mod interrupt {
+                #[inline(always)]
+                pub fn disable() {
+                    super::PLIC.disable();
+                }
+
+                #[inline(always)]
+                pub unsafe fn enable() {
+                    unsafe { super::PLIC.enable() };
+                }
+            }
+        }
+
+        #[doc(hidden)]
+        #[export_name = "MachineExternal"]
+        unsafe extern "C" fn __rtic_internal_machine_external() {
+            unsafe {
+                __rtic_internal_backend::PLIC.external(|source| {
+                    #(#sources)*
+                    let _ = source;
+                })
+            };
+        }
+    )]
+}
+
+pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+/// The exception handler raised when the stack grows into the PMP guard region.
+pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![quote!(
+        #[doc(hidden)]
+        #[no_mangle]
+        unsafe extern "C" fn StoreFault(trap_frame: *const ()) {
+            rtic::export::stack_guard::store_fault(trap_frame);
+        }
+    )]
+}
@@ -1,14 +1,18 @@
 use crate::{
     analyze::Analysis as CodegenAnalysis,
+    codegen::util,
     syntax::{
         analyze::Analysis as SyntaxAnalysis,
         ast::{App, Dispatcher},
     },
 };
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use quote::quote;
-use std::{collections::HashSet, vec};
-use syn::{parse, Attribute, Ident};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    vec,
+};
+use syn::{parse, Attribute, Ident, LitInt};
 
 /// Utility function to get the SLIC interrupt module.
 pub fn interrupt_ident() -> Ident {
@@ -21,16 +25,61 @@ pub fn interrupt_mod(_app: &App) -> TokenStream2 {
     quote!(slic::#interrupt)
 }
 
+/// The hart a task pinned with `core` runs on, in multi-hart apps
+fn task_hart(app: &App, core: &Option<LitInt>) -> Option<u32> {
+    match core {
+        Some(core) => core.base10_parse().ok(),
+        None => app.args.harts().first().copied(),
+    }
+}
+
+/// The harts of the tasks accessing each shared resource, in multi-hart apps
+fn shared_resource_harts(app: &App) -> BTreeMap<&Ident, BTreeSet<u32>> {
+    let mut harts: BTreeMap<&Ident, BTreeSet<u32>> = BTreeMap::new();
+    let Some(&boot) = app.args.harts().first() else {
+        return harts;
+    };
+
+    let idle = app.idle.iter().flat_map(|idle| {
+        idle.args
+            .shared_resources
+            .keys()
+            .map(move |name| (name, boot))
+    });
+    let hardware = app.hardware_tasks.values().flat_map(|task| {
+        let hart = task_hart(app, &task.args.core).unwrap_or(boot);
+        task.args
+            .shared_resources
+            .keys()
+            .map(move |name| (name, hart))
+    });
+    let software = app.software_tasks.values().flat_map(|task| {
+        let hart = task_hart(app, &task.args.core).unwrap_or(boot);
+        task.args
+            .shared_resources
+            .keys()
+            .map(move |name| (name, hart))
+    });
+
+    for (name, hart) in idle.chain(hardware).chain(software) {
+        harts.entry(name).or_default().insert(hart);
+    }
+
+    harts
+}
+
 /// This macro implements the [`rtic::Mutex`] trait for shared resources using the SLIC.
+///
+/// In multi-hart apps, resources shared across harts are also protected by a spin lock.
 #[allow(clippy::too_many_arguments)]
 pub fn impl_mutex(
-    _app: &App,
+    app: &App,
     _analysis: &CodegenAnalysis,
     cfgs: &[Attribute],
     resources_prefix: bool,
     name: &Ident,
     ty: &TokenStream2,
-    ceiling: u8,
+    ceiling: TokenStream2,
     ptr: &TokenStream2,
 ) -> TokenStream2 {
     let path = if resources_prefix {
@@ -39,6 +88,31 @@ pub fn impl_mutex(
         quote!(#name)
     };
 
+    // `name` is the proxy of the resource, `util::need_to_lock_ident`. Nested spin locks are taken
+    // in declaration order, checked at runtime against the position of the resource. Read ceilings
+    // never need one, `&name` access to resources written on other harts is rejected.
+    let cross_hart = shared_resource_harts(app)
+        .into_iter()
+        .find(|(resource, harts)| util::need_to_lock_ident(resource) == *name && harts.len() > 1)
+        .and_then(|(resource, _)| app.shared_resources.get_index_of(resource));
+    let lock = if let Some(index) = cross_hart {
+        let order = index + 1;
+        quote!(
+            static SPIN: rtic::export::harts::Spin = rtic::export::harts::Spin::new(#order);
+
+            unsafe {
+                __rtic_internal_backend::lock_spin(&SPIN, #ptr, CEILING, f)
+            }
+        )
+    } else {
+        let export = util::export_mod(app);
+        quote!(
+            unsafe {
+                #export::lock(#ptr, CEILING, f)
+            }
+        )
+    };
+
     quote!(
         #(#cfgs)*
         impl<'a> rtic::Mutex for #path<'a> {
@@ -49,18 +123,34 @@ pub fn impl_mutex(
 
                 const CEILING: u8 = #ceiling;
 
-                unsafe {
-                    rtic::export::lock(#ptr, CEILING, f)
-                }
+                #lock
             }
         }
     )
 }
 
 /// This macro is used to define additional compile-time assertions in case the platform needs it.
-/// The Cortex-M implementations do not use it. Thus, we think we do not need it either.
-pub fn extra_assertions(_app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
-    vec![]
+///
+/// In multi-hart apps, the resources shared across harts must be `Sync`.
+pub fn extra_assertions(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    for (name, harts) in shared_resource_harts(app) {
+        if harts.len() < 2 {
+            continue;
+        }
+
+        if let Some(resource) = app.shared_resources.get(name) {
+            let cfgs = &resource.cfgs;
+            let ty = &resource.ty;
+            stmts.push(quote!(
+                #(#cfgs)*
+                rtic::export::assert_sync::<#ty>();
+            ));
+        }
+    }
+
+    stmts
 }
 
 pub fn pre_init_preprocessing(app: &mut App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
@@ -95,6 +185,13 @@ pub fn pre_init_checks(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream
     let mut stmts: Vec<TokenStream2> = vec![];
     let int_mod = interrupt_mod(app);
 
+    // The other harts of a multi-hart app also run `main`, until they wait for `#[init]` to return
+    if !app.args.harts().is_empty() {
+        stmts.push(quote!(if !__rtic_internal_backend::HARTS.is_boot_hart() {
+            unsafe { __rtic_internal_backend::HARTS.run_secondary() }
+        }));
+    }
+
     // check that all dispatchers exists in the `slic::Interrupt` enumeration
     for name in app.args.dispatchers.keys() {
         stmts.push(quote!(let _ = #int_mod::#name;));
@@ -109,14 +206,21 @@ pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<
     let mut stmts = vec![];
 
     let interrupt_ids = analysis.interrupts.iter().map(|(p, (id, _))| (p, id));
-    for (&p, name) in interrupt_ids.chain(
-        app.hardware_tasks
-            .values()
-            .map(|task| (&task.args.priority, &task.args.binds)),
-    ) {
-        stmts.push(quote!(
-            rtic::export::set_priority(slic::SoftwareInterrupt::#name, #p);
-        ));
+    for (&p, name) in interrupt_ids.chain(app.hardware_tasks.values().flat_map(|task| {
+        task.args
+            .binds
+            .iter()
+            .map(move |binds| (&task.args.priority, binds))
+    })) {
+        if app.args.harts().is_empty() {
+            stmts.push(quote!(
+                rtic::export::set_priority(slic::SoftwareInterrupt::#name, #p);
+            ));
+        } else {
+            stmts.push(quote!(
+                __rtic_internal_backend::HARTS.set_priority(slic::SoftwareInterrupt::#name as u16, #p);
+            ));
+        }
     }
 
     stmts
@@ -124,6 +228,41 @@ pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<
 
 /// Any additional checks that depend on the system architecture.
 pub fn architecture_specific_analysis(app: &App, _analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    if app.args.device.is_none() {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "the RISC-V SLIC backends require `device = ..`",
+        ));
+    }
+
+    if let Some(path) = &app.args.interrupts {
+        return Err(parse::Error::new_spanned(
+            path,
+            "`interrupts` is not supported by the RISC-V SLIC backends",
+        ));
+    }
+
+    if let Some(bits) = &app.args.prio_bits {
+        return Err(parse::Error::new(
+            bits.span(),
+            "`prio_bits` is not supported by the RISC-V SLIC backends",
+        ));
+    }
+
+    if app.args.mpu {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "`mpu` is not supported by the RISC-V SLIC backends",
+        ));
+    }
+
+    if let Some(expr) = util::const_priority(app) {
+        return Err(parse::Error::new_spanned(
+            expr,
+            "priorities given as constant expressions are not supported by the RISC-V SLIC backends",
+        ));
+    }
+
     // Check that there are enough external interrupts to dispatch the software tasks and the timer queue handler
     let mut first = None;
     let priorities = app
@@ -156,6 +295,96 @@ pub fn architecture_specific_analysis(app: &App, _analysis: &SyntaxAnalysis) ->
         ));
     }
 
+    #[cfg(feature = "riscv-clint")]
+    multi_hart_analysis(app)?;
+
+    Ok(())
+}
+
+/// Checks specific to multi-hart apps
+#[cfg(feature = "riscv-clint")]
+fn multi_hart_analysis(app: &App) -> parse::Result<()> {
+    let harts = &app.args.backend.as_ref().unwrap().harts;
+    let Some(boot) = harts.first() else {
+        return Ok(());
+    };
+
+    let mut ids = HashSet::new();
+    for hart in harts {
+        if !ids.insert(hart.id) {
+            return Err(parse::Error::new(
+                hart.lit.span(),
+                "this hart appears more than once",
+            ));
+        }
+    }
+
+    if let Some(bytes) = &app.args.stack_guard {
+        return Err(parse::Error::new(
+            bytes.span(),
+            "`stack_guard` is not supported by multi-hart apps",
+        ));
+    }
+
+    if app.args.stack_usage {
+        return Err(parse::Error::new(
+            app.name.span(),
+            "`stack_usage` is not supported by multi-hart apps",
+        ));
+    }
+
+    let interrupts = app.hardware_tasks.len() + app.args.dispatchers.len();
+    if interrupts > 32 {
+        return Err(parse::Error::new(
+            app.name.span(),
+            format!(
+                "multi-hart apps support up to 32 software interrupts, this app uses {interrupts}"
+            ),
+        ));
+    }
+
+    let cores = app
+        .hardware_tasks
+        .values()
+        .map(|task| (&task.args.core, task.args.priority))
+        .chain(
+            app.software_tasks
+                .values()
+                .map(|task| (&task.args.core, task.args.priority)),
+        );
+    for (core, priority) in cores {
+        let Some(core) = core else { continue };
+        let id: u32 = core.base10_parse()?;
+
+        if !ids.contains(&id) {
+            return Err(parse::Error::new(
+                core.span(),
+                "this hart is not one of the `harts` of the app",
+            ));
+        }
+
+        // The priority 0 dispatcher is the `#[idle]` of the first hart
+        if priority == 0 && id != boot.id {
+            return Err(parse::Error::new(
+                core.span(),
+                "software tasks of priority 0 run on the first of the `harts` of the app",
+            ));
+        }
+    }
+
+    for (name, harts) in shared_resource_harts(app) {
+        let lock_free = app
+            .shared_resources
+            .get(name)
+            .is_some_and(|resource| resource.properties.lock_free);
+        if lock_free && harts.len() > 1 {
+            return Err(parse::Error::new(
+                name.span(),
+                "`#[lock_free]` resources can't be shared across harts",
+            ));
+        }
+    }
+
     Ok(())
 }
 
@@ -179,7 +408,7 @@ pub fn async_entry(
 
 /// Macro to define a maximum priority level for async tasks.
 pub fn async_prio_limit(_app: &App, analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
-    let max = if let Some(max) = analysis.max_async_prio {
+    let max = if let Some(max) = &analysis.max_async_prio {
         quote!(#max)
     } else {
         quote!(u8::MAX) // No limit
@@ -208,7 +437,7 @@ pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2>
     let hw_slice: Vec<_> = app
         .hardware_tasks
         .values()
-        .map(|task| &task.args.binds)
+        .flat_map(|task| &task.args.binds)
         .collect();
     let sw_slice: Vec<_> = app.args.dispatchers.keys().collect();
 
@@ -216,6 +445,11 @@ pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2>
 
     let device = &app.args.device;
 
+    if !app.args.harts().is_empty() {
+        stmts.push(multi_hart_modules(app, &swi_slice));
+        return stmts;
+    }
+
     stmts.push(quote!(
         use rtic::export::riscv_slic;
     ));
@@ -235,3 +469,150 @@ pub fn extra_modules(app: &App, _analysis: &SyntaxAnalysis) -> Vec<TokenStream2>
 
     stmts
 }
+
+/// The `slic` module and software interrupt controller of a multi-hart app, in place of the ones
+/// generated by `riscv-slic`, and the `__rtic_internal_backend` module the generated code takes
+/// `pend`, `run` and `lock` from.
+fn multi_hart_modules(app: &App, swi: &[&&Ident]) -> TokenStream2 {
+    // The device is imported in the app module under this name
+    let device = util::rt_err_ident();
+    let boot = app.args.harts()[0];
+
+    #[cfg(feature = "riscv-clint")]
+    let (ids, variants): (Vec<_>, Vec<_>) = app
+        .args
+        .backend
+        .as_ref()
+        .unwrap()
+        .harts
+        .iter()
+        .map(|hart| (Literal::usize_unsuffixed(hart.id as usize), &hart.ident))
+        .unzip();
+    #[cfg(not(feature = "riscv-clint"))]
+    let (ids, variants): (Vec<Literal>, Vec<Ident>) = (vec![], vec![]);
+
+    let n_harts = ids.len();
+    let n_interrupts = swi.len();
+    let numbers = (0..n_interrupts).map(Literal::usize_unsuffixed);
+
+    // Dispatchers are pended on the hart of the spawned task, hardware tasks on their own hart
+    let owners = app
+        .hardware_tasks
+        .values()
+        .map(|task| task_hart(app, &task.args.core).unwrap_or(boot))
+        .chain(app.args.dispatchers.keys().map(|_| boot))
+        .map(|hart| Literal::usize_unsuffixed(hart as usize));
+
+    quote!(
+        /// The software interrupts of the app
+        pub mod slic {
+            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+            #[doc(hidden)]
+            #[repr(u16)]
+            pub enum SoftwareInterrupt {
+                #(#swi = #numbers,)*
+            }
+
+            /// Pends the software interrupt `interrupt` on the hart of its task.
+            pub fn pend(interrupt: SoftwareInterrupt) {
+                super::__rtic_internal_backend::HARTS.pend(interrupt as u16);
+            }
+        }
+
+        #[doc(hidden)]
+        mod __rtic_internal_backend {
+            use super::#device;
+
+            pub use rtic::export::harts::run;
+
+            /// Pends or clears the MSIP of the hart `hart`
+            unsafe fn msip(hart: usize, pend: bool) {
+                let hart = match hart {
+                    #(#ids => #device::interrupt::Hart::#variants,)*
+                    _ => unreachable!(),
+                };
+                let msip = #device::CLINT::mswi().msip(hart);
+                if pend {
+                    msip.pend();
+                } else {
+                    msip.unpend();
+                }
+            }
+
+            pub static HARTS: rtic::export::harts::Harts<#n_harts, #n_interrupts> =
+                rtic::export::harts::Harts::new(
+                    [#(#ids,)*],
+                    [#(#owners,)*],
+                    [#(super::#swi,)*],
+                    msip,
+                );
+
+            #[export_name = "_mp_hook"]
+            extern "Rust" fn mp_hook(hart: usize) -> bool {
+                unsafe { rtic::export::harts::mp_hook(hart, &[#(#ids,)*], msip) }
+            }
+
+            #[riscv_rt::core_interrupt(#device::interrupt::CoreInterrupt::MachineSoft)]
+            unsafe fn machine_soft() {
+                unsafe { HARTS.dispatch() };
+            }
+
+            #[inline(always)]
+            pub fn pend(interrupt: super::slic::SoftwareInterrupt) {
+                HARTS.pend(interrupt as u16);
+            }
+
+            #[inline(always)]
+            pub fn pend_on(hart: usize, interrupt: super::slic::SoftwareInterrupt) {
+                HARTS.pend_on(hart, interrupt as u16);
+            }
+
+            #[inline(always)]
+            pub fn on_hart(hart: usize) -> bool {
+                HARTS.on_hart(hart)
+            }
+
+            #[inline(always)]
+            pub unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+                unsafe { HARTS.lock(ptr, ceiling, f) }
+            }
+
+            #[inline(always)]
+            pub unsafe fn lock_spin<T, R>(
+                spin: &rtic::export::harts::Spin,
+                ptr: *mut T,
+                ceiling: u8,
+                f: impl FnOnce(&mut T) -> R,
+            ) -> R {
+                unsafe { HARTS.lock_spin(spin, ptr, ceiling, f) }
+            }
+
+            pub mod interrupt {
+                #[inline(always)]
+                pub fn disable() {
+                    super::HARTS.disable();
+                }
+
+                #[inline(always)]
+                pub unsafe fn enable() {
+                    unsafe { super::HARTS.enable() };
+                }
+            }
+        }
+    )
+}
+
+pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+/// The exception handler raised when the stack grows into the PMP guard region.
+pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![quote!(
+        #[doc(hidden)]
+        #[no_mangle]
+        unsafe extern "C" fn StoreFault(trap_frame: *const ()) {
+            rtic::export::stack_guard::store_fault(trap_frame);
+        }
+    )]
+}
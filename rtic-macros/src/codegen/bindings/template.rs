@@ -12,6 +12,10 @@ pub fn interrupt_ident() -> Ident {
 }
 
 pub fn interrupt_mod(app: &App) -> TokenStream2 {
+    if let Some(interrupts) = &app.args.interrupts {
+        return quote!(#interrupts);
+    }
+
     let device = &app.args.device;
     let interrupt = interrupt_ident();
     quote!(#device::#interrupt)
@@ -24,7 +28,7 @@ pub fn impl_mutex(
     resources_prefix: bool,
     name: &Ident,
     ty: &TokenStream2,
-    ceiling: u8,
+    ceiling: TokenStream2,
     ptr: &TokenStream2,
 ) -> TokenStream2 {
     quote!()
@@ -47,6 +51,13 @@ pub fn pre_init_enable_interrupts(app: &App, analysis: &CodegenAnalysis) -> Vec<
 }
 
 pub fn architecture_specific_analysis(app: &App, analysis: &SyntaxAnalysis) -> parse::Result<()> {
+    if let Some(bits) = &app.args.prio_bits {
+        return Err(parse::Error::new(
+            bits.span(),
+            "`prio_bits` is not supported by the template backend",
+        ));
+    }
+
     Ok(())
 }
 
@@ -81,3 +92,11 @@ pub fn handler_config(
 pub fn extra_modules(app: &App, analysis: &SyntaxAnalysis) -> Vec<TokenStream2> {
     vec![]
 }
+
+pub fn fault_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
+
+pub fn stack_overflow_handler(_app: &App, _analysis: &CodegenAnalysis) -> Vec<TokenStream2> {
+    vec![]
+}
@@ -0,0 +1,126 @@
+use crate::{
+    analyze::Analysis,
+    codegen::{bindings::fault_handler, persistent, util},
+    syntax::ast::App,
+};
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+use std::collections::BTreeSet;
+use syn::Ident;
+
+fn running_tasks_ident() -> Ident {
+    util::mark_internal_name("RUNNING_TASKS")
+}
+
+/// The priority levels of the app, `0` being the one of `#[init]` and `#[idle]`
+fn levels(app: &App) -> Vec<u8> {
+    let levels = app
+        .hardware_tasks
+        .values()
+        .map(|task| task.args.priority)
+        .chain(app.software_tasks.values().map(|task| task.args.priority))
+        .chain([0])
+        .collect::<BTreeSet<_>>();
+
+    levels.into_iter().collect()
+}
+
+/// Marks `task` as running at `level`
+pub fn enter(app: &App, task: &Ident, level: u8) -> TokenStream2 {
+    if app.on_fault.is_none() {
+        return quote!();
+    }
+
+    let running_tasks = running_tasks_ident();
+    let level = levels(app).iter().position(|l| *l == level);
+    let (level, id) = (
+        Literal::usize_unsuffixed(level.expect("UNREACHABLE")),
+        Literal::u16_unsuffixed(util::task_id(app, task)),
+    );
+
+    quote!(#running_tasks.enter(#level, #id);)
+}
+
+/// Marks the task running at `level` as done
+pub fn exit(app: &App, level: u8) -> TokenStream2 {
+    if app.on_fault.is_none() {
+        return quote!();
+    }
+
+    let running_tasks = running_tasks_ident();
+    let level = levels(app).iter().position(|l| *l == level);
+    let level = Literal::usize_unsuffixed(level.expect("UNREACHABLE"));
+
+    quote!(#running_tasks.exit(#level);)
+}
+
+/// Records the task running `run` at `level` for the `#[on_fault]` function
+pub fn track(app: &App, task: &Ident, level: u8, run: TokenStream2) -> TokenStream2 {
+    if app.on_fault.is_none() {
+        return run;
+    }
+
+    let enter = enter(app, task, level);
+    let exit = exit(app, level);
+
+    quote!({
+        #enter
+        #run
+        #exit
+    })
+}
+
+/// Generates the `#[on_fault]` function, the record of the running tasks and the fault handler
+pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
+    let Some(hook) = &app.on_fault else {
+        return quote!();
+    };
+
+    let attrs = &hook.attrs;
+    let name = &hook.name;
+    let frame = &hook.frame;
+    let tasks = &hook.tasks;
+    let persisted = hook.persisted.iter();
+    let stmts = &hook.stmts;
+    let handler = fault_handler(app, analysis);
+    // Nothing else runs once the fault is taken, so the resources can be handed out as `&mut`
+    let persisted_arg = hook.persisted.as_ref().and_then(|_| {
+        let persisted = persistent::fault_persisted_ident(app, name)?;
+        Some(quote!(unsafe { #persisted::new() }))
+    });
+    let persisted_arg = persisted_arg.iter();
+
+    let running_tasks = running_tasks_ident();
+    let levels = levels(app);
+    let n = levels.len();
+    let priorities = levels.iter().map(|level| util::priority(app, *level));
+    let names = util::task_names(app)
+        .into_iter()
+        .map(|name| name.to_string());
+
+    quote!(
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        fn #name(#frame, #tasks #(, #persisted)*) -> ! {
+            #(#stmts)*
+        }
+
+        const _: () = ::core::assert!(
+            rtic::export::fault::SUPPORTED,
+            "`#[on_fault]` is not supported by the selected backend"
+        );
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        static #running_tasks: rtic::export::fault::RunningTasks<#n> =
+            rtic::export::fault::RunningTasks::new([#(#priorities,)*], &[#(#names,)*]);
+
+        #[doc(hidden)]
+        #[no_mangle]
+        fn __rtic_on_fault(frame: &rtic::fault::FaultFrame) -> ! {
+            #name(frame, &#running_tasks.active() #(, #persisted_arg)*)
+        }
+
+        #(#handler)*
+    )
+}
@@ -3,7 +3,7 @@ use crate::{
     analyze::Analysis,
     codegen::{
         bindings::{handler_config, interrupt_entry, interrupt_exit},
-        local_resources_struct, module, shared_resources_struct,
+        fault, local_resources_struct, module, mpu, shared_resources_struct, util,
     },
 };
 use proc_macro2::TokenStream as TokenStream2;
@@ -15,39 +15,52 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
     let mut mod_app = vec![];
     let mut root = vec![];
     let mut user_tasks = vec![];
+    let export = util::export_mod(app);
 
     for (name, task) in &app.hardware_tasks {
-        let symbol = task.args.binds.clone();
-        let priority = task.args.priority;
+        let priority = util::priority(app, task.args.priority);
         let cfgs = &task.cfgs;
         let attrs = &task.attrs;
-        let entry_stmts = interrupt_entry(app, analysis);
-        let exit_stmts = interrupt_exit(app, analysis);
-        let config = handler_config(app, analysis, symbol.clone());
+        let priority_entry = util::priority_entry(app);
+        let priority_exit = util::priority_exit(app);
+        let run = mpu::in_domain(app, name, quote!(#export::run(PRIORITY, exec);));
+        let run = fault::track(app, name, task.args.priority, run);
 
         let lifetime = Lifetime::new("'non_static", name.span());
 
-        mod_app.push(quote!(
-            #[allow(non_snake_case)]
-            #[no_mangle]
-            #(#attrs)*
-            #(#cfgs)*
-            #(#config)*
-            unsafe fn #symbol() {
-                #(#entry_stmts)*
+        // One handler per vector, all running the same task
+        for symbol in &task.args.binds {
+            let entry_stmts = interrupt_entry(app, analysis);
+            let exit_stmts = interrupt_exit(app, analysis);
+            let config = handler_config(app, analysis, symbol.clone());
+            let source = (task.args.binds.len() > 1).then(|| quote!(#name::Source::#symbol));
 
-                const PRIORITY: u8 = #priority;
+            mod_app.push(quote!(
+                #[allow(non_snake_case)]
+                #[no_mangle]
+                #(#attrs)*
+                #(#cfgs)*
+                #(#config)*
+                unsafe fn #symbol() {
+                    #(#entry_stmts)*
 
-                fn exec<#lifetime>() {
-                    let ctx = unsafe { #name::Context::<#lifetime>::new() };
-                    #name(ctx)
-                }
+                    const PRIORITY: u8 = #priority;
+
+                    #(#priority_entry)*
 
-                rtic::export::run(PRIORITY, exec);
+                    fn exec<#lifetime>() {
+                        let ctx = unsafe { #name::Context::<#lifetime>::new(#source) };
+                        #name(ctx)
+                    }
 
-                #(#exit_stmts)*
-            }
-        ));
+                    #run
+
+                    #(#priority_exit)*
+
+                    #(#exit_stmts)*
+                }
+            ));
+        }
 
         // `${task}Locals`
         if !task.args.local_resources.is_empty() {
@@ -62,7 +75,7 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
         // `${task}Resources`
         if !task.args.shared_resources.is_empty() {
             let (item, constructor) =
-                shared_resources_struct::codegen(Context::HardwareTask(name), app);
+                shared_resources_struct::codegen(Context::HardwareTask(name), app, analysis);
 
             root.push(item);
 
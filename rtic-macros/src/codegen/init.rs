@@ -3,7 +3,7 @@ use quote::quote;
 
 use crate::{
     analyze::Analysis,
-    codegen::{local_resources_struct, module},
+    codegen::{local_resources_struct, module, persistent, util},
     syntax::{ast::App, Context},
 };
 
@@ -27,6 +27,11 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
         .iter()
         .map(|(k, v)| {
             let ty = &v.ty;
+            let ty = if v.properties.init_in_place {
+                quote!(rtic::slot::Initialized<#ty>)
+            } else {
+                quote!(#ty)
+            };
             let cfgs = &v.cfgs;
             let docs = &v.docs;
             let vis = &v.vis;
@@ -42,6 +47,11 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
         .iter()
         .map(|(k, v)| {
             let ty = &v.ty;
+            let ty = if v.init_in_place {
+                quote!(rtic::slot::Initialized<#ty>)
+            } else {
+                quote!(#ty)
+            };
             let cfgs = &v.cfgs;
             let docs = &v.docs;
             let vis = &v.vis;
@@ -65,6 +75,87 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
         }
     });
 
+    // `${init}Slots`
+    let in_place = util::init_in_place_resources(app);
+    if !in_place.is_empty() {
+        let slots = util::internal_task_ident(name, "Slots");
+        let mut fields = vec![];
+        let mut values = vec![];
+
+        for (resource, cfgs, ty, mangled_name) in in_place {
+            fields.push(quote!(
+                #(#cfgs)*
+                #[allow(missing_docs)]
+                pub #resource: rtic::slot::Slot<#ty>,
+            ));
+            values.push(quote!(
+                #(#cfgs)*
+                #resource: rtic::slot::Slot::new(#mangled_name.get_mut()),
+            ));
+        }
+
+        root_init.push(quote!(
+            /// Storage of the `#[init_in_place]` resources
+            #[allow(non_camel_case_types)]
+            pub struct #slots {
+                #(#fields)*
+            }
+
+            impl #slots {
+                #[inline(always)]
+                #[doc(hidden)]
+                pub unsafe fn new() -> Self {
+                    unsafe { #slots { #(#values)* } }
+                }
+            }
+        ));
+    }
+
+    // `${init}Persisted`
+    let persistent = util::persistent_resources(app);
+    if !persistent.is_empty() {
+        let persisted = util::internal_task_ident(name, "Persisted");
+        let mut fields = vec![];
+        let mut values = vec![];
+
+        for (resource, cfgs, ty, mangled_name) in persistent {
+            let restore = persistent::restore(resource, ty, &mangled_name);
+
+            fields.push(quote!(
+                #(#cfgs)*
+                #[allow(missing_docs)]
+                pub #resource: Option<&'a mut #ty>,
+            ));
+            values.push(quote!(
+                #(#cfgs)*
+                #resource: #restore,
+            ));
+        }
+
+        root_init.push(quote!(
+            /// The `#[persistent]` resources that survived the reset
+            #[allow(non_camel_case_types)]
+            pub struct #persisted<'a> {
+                #(#fields)*
+                #[doc(hidden)]
+                pub __rtic_internal_marker: core::marker::PhantomData<&'a ()>,
+            }
+
+            impl<'a> #persisted<'a> {
+                #[inline(always)]
+                #[doc(hidden)]
+                pub unsafe fn new() -> Self {
+                    unsafe {
+                        #persisted {
+                            #(#values)*
+                            __rtic_internal_marker: core::marker::PhantomData,
+                        }
+                    }
+                }
+            }
+        ));
+    }
+
     // let locals_pat = locals_pat.iter();
 
     let user_init_return = quote! {#shared, #local};
@@ -9,8 +9,8 @@ use quote::quote;
 pub fn codegen(app: &App, _analysis: &Analysis) -> TokenStream2 {
     let mut mod_app = vec![];
 
-    // All local resources declared in the `#[local]' struct
-    for (name, res) in &app.local_resources {
+    // All local resources declared in the `#[local]' struct, unless they live in the MPU container
+    for (name, res) in app.local_resources.iter().filter(|_| !app.args.mpu) {
         let cfgs = &res.cfgs;
         let ty = &res.ty;
         let mangled_name = util::static_local_resource_ident(name);
@@ -53,6 +53,11 @@ pub fn codegen(app: &App, _analysis: &Analysis) -> TokenStream2 {
 
     // All declared `local = [NAME: TY = EXPR]` local resources
     for (task_name, resource_name, task_local) in app.declared_local_resources() {
+        // Only the ones of `#[init]` are outside of the MPU container
+        if app.args.mpu && *task_name != app.init.name {
+            continue;
+        }
+
         let cfgs = &task_local.cfgs;
         let ty = &task_local.ty;
         let expr = &task_local.expr;
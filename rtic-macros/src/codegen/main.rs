@@ -2,7 +2,7 @@ use crate::{analyze::Analysis, codegen::util, syntax::ast::App};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
-use super::{assertions, extra_mods, post_init, pre_init};
+use super::{assertions, extra_mods, fault, post_init, pre_init};
 
 /// Generates code for `fn main`
 pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
@@ -16,16 +16,26 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
 
     let call_idle = if let Some(idle) = &app.idle {
         let name = &idle.name;
-        quote!(#name(#name::Context::new()))
+        let enter_idle = fault::enter(app, name, 0);
+        quote!(
+            #enter_idle
+            #name(#name::Context::new())
+        )
     } else if analysis.channels.contains_key(&0) {
         let dispatcher = util::zero_prio_dispatcher_ident();
         quote!(#dispatcher();)
+    } else if cfg!(any(feature = "host", feature = "sim")) {
+        quote!(loop {
+            rtic::export::wfi()
+        })
     } else {
         quote!(loop {})
     };
 
     let main = util::suffixed("main");
     let init_name = &app.init.name;
+    let enter_init = fault::enter(app, init_name, 0);
+    let exit_init = fault::exit(app, 0);
 
     let init_args = if app.args.core {
         quote!(core.into())
@@ -33,12 +43,25 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
         quote!()
     };
 
+    // On the host the app is started by `rtic::host::start` or `rtic::sim::Sim`, next to the
+    // `main` of the crate
+    let signature = if cfg!(any(feature = "host", feature = "sim")) {
+        quote!(
+            /// Runs the app, pass it to `rtic::host::start` or `rtic::sim::Sim`
+            pub unsafe fn #main() -> !
+        )
+    } else {
+        quote!(
+            #[doc(hidden)]
+            #[no_mangle]
+            unsafe extern "C" fn #main() -> !
+        )
+    };
+
     quote!(
         #(#extra_mods_stmts)*
 
-        #[doc(hidden)]
-        #[no_mangle]
-        unsafe extern "C" fn #main() -> ! {
+        #signature {
             #(#assertion_stmts)*
 
             #(#pre_init_stmts)*
@@ -50,7 +73,9 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
 
             // Wrap late_init_stmts in a function to ensure that stack space is reclaimed.
             __rtic_init_resources(||{
+                #enter_init
                 let (shared_resources, local_resources) = #init_name(#init_name::Context::new(#init_args));
+                #exit_init
 
                 #(#post_init_stmts)*
             });
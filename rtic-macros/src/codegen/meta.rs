@@ -0,0 +1,176 @@
+use crate::syntax::{analyze::Ownership, ast::App};
+use crate::{analyze::Analysis, codegen::util};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::LitByteStr;
+
+/// The string table of the section
+#[derive(Default)]
+struct Strings(Vec<u8>);
+
+impl Strings {
+    fn push(&mut self, s: &str) -> TokenStream2 {
+        let offset = self.0.len() as u32;
+        let len = s.len() as u32;
+        self.0.extend_from_slice(s.as_bytes());
+
+        quote!(rtic::meta::Str { offset: #offset, len: #len })
+    }
+}
+
+fn task_entry(
+    strings: &mut Strings,
+    name: &str,
+    binds: &str,
+    priority: TokenStream2,
+    kind: TokenStream2,
+    flags: Option<TokenStream2>,
+) -> TokenStream2 {
+    let name = strings.push(name);
+    let binds = strings.push(binds);
+    let (flags, running, pending) = match flags {
+        Some(flags) => (
+            quote!(let [running, pending] = #flags;),
+            quote!(running),
+            quote!(pending),
+        ),
+        None => (
+            quote!(),
+            quote!(rtic::meta::Address::NULL),
+            quote!(rtic::meta::Address::NULL),
+        ),
+    };
+
+    quote!({
+        #flags
+        rtic::meta::Task {
+            running: #running,
+            pending: #pending,
+            name: #name,
+            binds: #binds,
+            priority: #priority,
+            kind: #kind,
+        }
+    })
+}
+
+/// Generates the `.rtic_meta` section describing the tasks and shared resources of the app
+pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
+    if !cfg!(feature = "meta-section") {
+        return quote!();
+    }
+
+    let mut strings = Strings::default();
+    let mut tasks = vec![];
+
+    let init = &app.init;
+    let entry = task_entry(
+        &mut strings,
+        &init.name.to_string(),
+        "",
+        quote!(0),
+        quote!(rtic::meta::KIND_INIT),
+        None,
+    );
+    tasks.push(entry);
+
+    if let Some(idle) = &app.idle {
+        let entry = task_entry(
+            &mut strings,
+            &idle.name.to_string(),
+            "",
+            quote!(0),
+            quote!(rtic::meta::KIND_IDLE),
+            None,
+        );
+        tasks.push(entry);
+    }
+
+    for (name, task) in &app.hardware_tasks {
+        let cfgs = &task.cfgs;
+        let binds = task
+            .args
+            .binds
+            .iter()
+            .map(|binds| binds.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let entry = task_entry(
+            &mut strings,
+            &name.to_string(),
+            &binds,
+            util::priority(app, task.args.priority),
+            quote!(rtic::meta::KIND_HARDWARE),
+            None,
+        );
+        tasks.push(quote!(#(#cfgs)* #entry));
+    }
+
+    for (name, task) in &app.software_tasks {
+        let cfgs = &task.cfgs;
+        let exec_name = util::internal_task_ident(name, "EXEC");
+        let entry = task_entry(
+            &mut strings,
+            &name.to_string(),
+            "",
+            util::priority(app, task.args.priority),
+            quote!(rtic::meta::KIND_SOFTWARE),
+            Some(quote!(rtic::export::executor::exec_flags(#name, &raw const #exec_name))),
+        );
+        tasks.push(quote!(#(#cfgs)* #entry));
+    }
+
+    let mut resources = vec![];
+    for (name, res) in &app.shared_resources {
+        let cfgs = &res.cfgs;
+        let ty = &res.ty;
+        let mangled_name = util::static_shared_resource_ident(name);
+        // With `mpu` the `static` is a reference into the MPU container
+        let address = if app.args.mpu {
+            quote!(rtic::meta::Address::of(#mangled_name))
+        } else {
+            quote!(rtic::meta::Address::of(&raw const #mangled_name))
+        };
+        let ceiling = match analysis.ownerships.get(name) {
+            Some(Ownership::Owned { priority } | Ownership::CoOwned { priority }) => *priority,
+            Some(Ownership::Contended { ceiling }) => *ceiling,
+            None => 0,
+        };
+        let ceiling = util::ceiling(app, name, ceiling);
+        let name = strings.push(&name.to_string());
+
+        resources.push(quote!(
+            #(#cfgs)*
+            rtic::meta::Resource {
+                address: #address,
+                size: core::mem::size_of::<#ty>() as u32,
+                name: #name,
+                ceiling: #ceiling,
+            }
+        ));
+    }
+
+    // Tasks and resources removed by a `#[cfg]` are not counted
+    let idle = app.idle.as_ref().map(|_| quote!((),));
+    let task_cfgs = app
+        .hardware_tasks
+        .values()
+        .map(|task| &task.cfgs)
+        .chain(app.software_tasks.values().map(|task| &task.cfgs));
+    let resource_cfgs = app.shared_resources.values().map(|res| &res.cfgs);
+    let n_strings = strings.0.len();
+    let strings = LitByteStr::new(&strings.0, Span::call_site());
+    let meta = util::mark_internal_name("META");
+
+    quote!(
+        #[doc(hidden)]
+        #[used]
+        #[link_section = ".rtic_meta"]
+        #[allow(non_upper_case_globals)]
+        static #meta: rtic::meta::Meta<
+            { <[()]>::len(&[(), #idle #(#(#task_cfgs)* (),)*]) },
+            { <[()]>::len(&[#(#(#resource_cfgs)* (),)*]) },
+            #n_strings,
+        > = rtic::meta::Meta::new([#(#tasks,)*], [#(#resources,)*], *#strings);
+    )
+}
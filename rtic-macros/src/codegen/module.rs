@@ -1,5 +1,8 @@
 use crate::syntax::{ast::App, Context};
-use crate::{analyze::Analysis, codegen::bindings::interrupt_mod, codegen::util};
+use crate::{
+    analyze::Analysis,
+    codegen::{bindings::interrupt_mod, util, watchdog},
+};
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
@@ -44,9 +47,76 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
             ));
 
             values.push(quote!(cs: rtic::export::CriticalSection::new()));
+
+            if !util::init_in_place_resources(app).is_empty() {
+                let ident = util::internal_task_ident(&app.init.name, "Slots");
+
+                module_items.push(quote!(
+                    #[doc(inline)]
+                    pub use super::#ident as Slots;
+                ));
+
+                fields.push(quote!(
+                    /// Storage of the `#[init_in_place]` resources
+                    pub slots: #name::Slots
+                ));
+
+                values.push(quote!(slots: #name::Slots::new()));
+            }
+
+            if !util::persistent_resources(app).is_empty() {
+                let ident = util::internal_task_ident(&app.init.name, "Persisted");
+
+                module_items.push(quote!(
+                    #[doc(inline)]
+                    pub use super::#ident as Persisted;
+                ));
+
+                fields.push(quote!(
+                    /// The `#[persistent]` resources that survived the reset, `None` after a cold
+                    /// reset or if their memory was corrupted
+                    pub persisted: #name::Persisted<'a>
+                ));
+
+                values.push(quote!(persisted: #name::Persisted::new()));
+            }
         }
 
-        Context::Idle | Context::HardwareTask(_) | Context::SoftwareTask(_) => {}
+        Context::HardwareTask(t) => {
+            let binds = &app.hardware_tasks[t].args.binds;
+
+            if binds.len() > 1 {
+                let ident = util::internal_task_ident(t, "Source");
+                let cfgs = &app.hardware_tasks[t].cfgs;
+
+                items.push(quote!(
+                    #(#cfgs)*
+                    /// The vectors the task is bound to
+                    #[allow(non_camel_case_types)]
+                    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+                    pub enum #ident {
+                        #(
+                            #[allow(missing_docs)]
+                            #binds,
+                        )*
+                    }
+                ));
+
+                module_items.push(quote!(
+                    #[doc(inline)]
+                    pub use super::#ident as Source;
+                ));
+
+                fields.push(quote!(
+                    /// The vector that started this run of the task
+                    pub source: #name::Source
+                ));
+
+                values.push(quote!(source));
+            }
+        }
+
+        Context::Idle | Context::SoftwareTask(_) => {}
     }
 
     if ctxt.has_local_resources(app) {
@@ -95,10 +165,12 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
         _ => &v,
     };
 
-    let core = if ctxt.is_init() && app.args.core {
-        Some(quote!(core: rtic::export::Peripherals))
-    } else {
-        None
+    let param = match ctxt {
+        Context::Init if app.args.core => Some(quote!(core: rtic::export::Peripherals)),
+        Context::HardwareTask(t) if app.hardware_tasks[t].args.binds.len() > 1 => {
+            Some(quote!(source: #name::Source))
+        }
+        _ => None,
     };
 
     let internal_context_name = util::internal_task_ident(name, "Context");
@@ -113,12 +185,14 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
         let pend_interrupt = if priority > 0 {
             let int_mod = interrupt_mod(app);
             let interrupt = &analysis.interrupts.get(&priority).expect("UREACHABLE").0;
-            quote!(rtic::export::pend(#int_mod::#interrupt);)
+            util::pend_dispatcher(app, quote!(#int_mod::#interrupt), &spawnee.args.core)
         } else {
             quote!()
         };
 
         let internal_spawn_ident = util::internal_task_ident(name, "spawn");
+        let internal_spawn_allocated_ident = util::internal_task_ident(name, "spawn_allocated");
+        let internal_spawn_wait_ident = util::internal_task_ident(name, "spawn_wait");
         let internal_waker_ident = util::internal_task_ident(name, "waker");
         let (input_args, input_tupled, input_untupled, input_ty) =
             util::regroup_inputs(&spawnee.inputs);
@@ -162,6 +236,23 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
             }
         };
 
+        // Spawns the task into the executor allocated by the callers below, so that the checks
+        // on the signature of the task are only made once
+        items.push(quote!(
+            #(#cfgs)*
+            #[allow(non_snake_case)]
+            #[doc(hidden)]
+            #[allow(clippy::extra_unused_lifetimes)]
+            #[inline(always)]
+            unsafe fn #internal_spawn_allocated_ident<#lifetime>(#(#input_args,)*) {
+                unsafe {
+                    let exec = #exec;
+                    #spawn
+                    #pend_interrupt
+                }
+            }
+        ));
+
         // Spawn caller
         items.push(quote!(
             #(#cfgs)*
@@ -172,10 +263,8 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
             pub #unsafety fn #internal_spawn_ident<#lifetime>(#(#input_args,)*) -> ::core::result::Result<(), #input_ty> {
                 // SAFETY: If `try_allocate` succeeds one must call `spawn`, which we do.
                 unsafe {
-                    let exec = #exec;
-                    if exec.try_allocate() {
-                        #spawn
-                        #pend_interrupt
+                    if #exec.try_allocate() {
+                        #internal_spawn_allocated_ident(#(#input_untupled,)*);
 
                         Ok(())
                     } else {
@@ -185,20 +274,55 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
             }
         ));
 
+        // Spawn caller that waits for the task to return first
+        let spawn_wait = app.software_tasks[t].args.spawn_wait;
+        if spawn_wait {
+            let waiters = util::waiters_ident(name);
+
+            items.push(quote!(
+                #(#cfgs)*
+                #[doc(hidden)]
+                #[allow(non_upper_case_globals)]
+                static #waiters: rtic::export::executor::WaitQueue =
+                    rtic::export::executor::WaitQueue::new();
+
+                #(#cfgs)*
+                /// Spawns the task once it is no longer running
+                #[allow(non_snake_case)]
+                #[doc(hidden)]
+                #[allow(clippy::extra_unused_lifetimes)]
+                pub async fn #internal_spawn_wait_ident<#lifetime>(#(#input_args,)*) {
+                    // SAFETY: Once `allocate` returns one must call `spawn`, which we do.
+                    unsafe {
+                        #exec.allocate(&#waiters).await;
+                        #internal_spawn_allocated_ident(#(#input_untupled,)*);
+                    }
+                }
+            ));
+        }
+
         // Waker
+        let task_waker = util::internal_task_ident(name, "WAKER");
+        let task_id = util::task_id(app, name);
+        let task_names = util::task_names_ident();
         items.push(quote!(
+            #(#cfgs)*
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            static #task_waker: rtic::export::executor::TaskWaker =
+                rtic::export::executor::TaskWaker::new(#task_id, &#task_names, || {
+                    // SAFETY: the executor's storage was declared for this task.
+                    unsafe { #exec.set_pending() };
+                    #pend_interrupt
+                });
+
             #(#cfgs)*
             /// Gives waker to the task
             #[allow(non_snake_case)]
             #[doc(hidden)]
             pub fn #internal_waker_ident() -> ::core::task::Waker {
                 // SAFETY: the executor's storage was declared for this task.
-                unsafe {
-                    #exec.waker(|| {
-                        #exec.set_pending();
-                        #pend_interrupt
-                    })
-                }
+                unsafe { #exec.waker(&#task_waker) }
             }
         ));
 
@@ -210,6 +334,14 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
             ));
         }
 
+        if spawn_wait {
+            module_items.push(quote!(
+                #(#cfgs)*
+                #[doc(inline)]
+                pub use super::#internal_spawn_wait_ident as spawn_wait;
+            ));
+        }
+
         let local_tasks_on_same_executor: Vec<_> = app
             .software_tasks
             .iter()
@@ -272,6 +404,27 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
             #[doc(inline)]
             pub use super::#internal_waker_ident as waker;
         ));
+
+        #[cfg(feature = "executor-stats")]
+        {
+            let internal_stats_ident = util::internal_task_ident(name, "stats");
+            items.push(quote!(
+                #(#cfgs)*
+                /// Counters of the task's executor
+                #[allow(non_snake_case)]
+                #[doc(hidden)]
+                pub fn #internal_stats_ident() -> rtic::stats::TaskStats {
+                    // SAFETY: the executor's storage was declared for this task.
+                    unsafe { #exec.stats() }
+                }
+            ));
+
+            module_items.push(quote!(
+                #(#cfgs)*
+                #[doc(inline)]
+                pub use super::#internal_stats_ident as stats;
+            ));
+        }
     }
 
     items.push(quote!(
@@ -289,7 +442,7 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
         impl<'a> #internal_context_name<'a> {
             #[inline(always)]
             #[allow(missing_docs)]
-            pub unsafe fn new(#core) -> Self {
+            pub unsafe fn new(#param) -> Self {
                 #internal_context_name {
                     __rtic_internal_p: ::core::marker::PhantomData,
                     #(#values,)*
@@ -298,6 +451,14 @@ pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> TokenStream2 {
         }
     ));
 
+    if let Some(checkin) = watchdog::checkin(app, name) {
+        items.push(quote!(
+            impl<'a> #internal_context_name<'a> {
+                #checkin
+            }
+        ));
+    }
+
     module_items.push(quote!(
         #(#cfgs)*
         #[doc(inline)]
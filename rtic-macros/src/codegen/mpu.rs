@@ -0,0 +1,276 @@
+use crate::codegen::util;
+use crate::syntax::ast::{App, LocalResources, SharedResources, TaskLocal};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Attribute, Ident};
+
+/// A task, or `#[idle]`, running with its own MPU domain
+struct Domain<'a> {
+    name: &'a Ident,
+    cfgs: &'a [Attribute],
+    local_resources: &'a LocalResources,
+    shared_resources: &'a SharedResources,
+}
+
+fn domains(app: &App) -> Vec<Domain<'_>> {
+    let idle = app.idle.iter().map(|idle| Domain {
+        name: &idle.name,
+        cfgs: &[],
+        local_resources: &idle.args.local_resources,
+        shared_resources: &idle.args.shared_resources,
+    });
+    let hardware_tasks = app.hardware_tasks.iter().map(|(name, task)| Domain {
+        name,
+        cfgs: &task.cfgs,
+        local_resources: &task.args.local_resources,
+        shared_resources: &task.args.shared_resources,
+    });
+    let software_tasks = app.software_tasks.iter().map(|(name, task)| Domain {
+        name,
+        cfgs: &task.cfgs,
+        local_resources: &task.args.local_resources,
+        shared_resources: &task.args.shared_resources,
+    });
+
+    idle.chain(hardware_tasks).chain(software_tasks).collect()
+}
+
+fn container_ident() -> Ident {
+    util::mark_internal_name("MPU_CONTAINER")
+}
+
+fn locals_field(task: &Ident) -> Ident {
+    format_ident!("local_{}", task)
+}
+
+fn shared_field(resource: &Ident) -> Ident {
+    format_ident!("shared_{}", resource)
+}
+
+/// The MPU domain a task runs in
+pub fn domain_ident(task: &Ident) -> Ident {
+    util::internal_task_ident(task, "MPU_DOMAIN")
+}
+
+/// The MPU domain `main` runs in when there is no `#[idle]`, it grants access to nothing
+fn main_domain_ident() -> Ident {
+    util::mark_internal_name("main_MPU_DOMAIN")
+}
+
+/// Generates the container holding all resources, and a reference to each resource in it
+///
+/// The references have the names the resource `static`s have without `mpu`, so the rest of the
+/// generated code doesn't need to tell the difference.
+pub fn codegen(app: &App) -> TokenStream2 {
+    if !app.args.mpu {
+        return quote!();
+    }
+
+    let container = container_ident();
+    let container_ty = util::mark_internal_name("MpuContainer");
+
+    let mut items = vec![];
+    let mut fields = vec![];
+    let mut inits = vec![];
+
+    for domain in domains(app) {
+        let task = domain.name;
+        let task_cfgs = domain.cfgs;
+        let block = locals_field(task);
+        let block_ty = util::internal_task_ident(task, "MpuLocals");
+
+        let mut local_fields = vec![];
+        let mut local_inits = vec![];
+
+        for (name, task_local) in domain.local_resources {
+            let (cfgs, ty, init, alias) = match task_local {
+                TaskLocal::External => {
+                    let res = &app.local_resources[name];
+                    let ty = &res.ty;
+                    (
+                        &res.cfgs,
+                        quote!(rtic::RacyCell<core::mem::MaybeUninit<#ty>>),
+                        quote!(rtic::RacyCell::new(core::mem::MaybeUninit::uninit())),
+                        util::static_local_resource_ident(name),
+                    )
+                }
+                TaskLocal::Declared(local) => {
+                    let ty = &local.ty;
+                    let expr = &local.expr;
+                    (
+                        &local.cfgs,
+                        quote!(rtic::RacyCell<#ty>),
+                        quote!(rtic::RacyCell::new(#expr)),
+                        util::declared_static_local_resource_ident(name, task),
+                    )
+                }
+            };
+
+            local_fields.push(quote!(#(#cfgs)* #name: #ty,));
+            local_inits.push(quote!(#(#cfgs)* #name: #init,));
+            items.push(quote!(
+                #[allow(non_upper_case_globals)]
+                #[doc(hidden)]
+                #(#task_cfgs)*
+                #(#cfgs)*
+                static #alias: &#ty = &#container.get().#block.get().#name;
+            ));
+        }
+
+        if !local_fields.is_empty() {
+            items.push(quote!(
+                #[allow(non_camel_case_types)]
+                #[doc(hidden)]
+                #(#task_cfgs)*
+                #[repr(C)]
+                struct #block_ty {
+                    #(#local_fields)*
+                }
+            ));
+            fields.push(quote!(
+                #(#task_cfgs)*
+                #block: rtic::export::mpu::Block<
+                    #block_ty,
+                    { rtic::export::mpu::block_align::<#block_ty>() },
+                >,
+            ));
+            inits.push(quote!(
+                #(#task_cfgs)*
+                #block: rtic::export::mpu::Block::new(#block_ty { #(#local_inits)* }),
+            ));
+        }
+    }
+
+    for (name, res) in &app.shared_resources {
+        let cfgs = &res.cfgs;
+        let ty = &res.ty;
+        let block = shared_field(name);
+        let alias = util::static_shared_resource_ident(name);
+        let cell_ty = quote!(rtic::RacyCell<core::mem::MaybeUninit<#ty>>);
+
+        fields.push(quote!(
+            #(#cfgs)*
+            #block: rtic::export::mpu::Block<
+                #cell_ty,
+                { rtic::export::mpu::block_align::<#cell_ty>() },
+            >,
+        ));
+        inits.push(quote!(
+            #(#cfgs)*
+            #block: rtic::export::mpu::Block::new(
+                rtic::RacyCell::new(core::mem::MaybeUninit::uninit())
+            ),
+        ));
+        items.push(quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            #(#cfgs)*
+            static #alias: &#cell_ty = #container.get().#block.get();
+        ));
+    }
+
+    let mut domains = vec![];
+    for domain in self::domains(app) {
+        let name = domain_ident(domain.name);
+        let cfgs = domain.cfgs;
+
+        domains.push(quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            #(#cfgs)*
+            static #name: rtic::export::mpu::Domain = rtic::export::mpu::Domain::new();
+        ));
+    }
+
+    if app.idle.is_none() {
+        let name = main_domain_ident();
+
+        domains.push(quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            static #name: rtic::export::mpu::Domain = rtic::export::mpu::Domain::new();
+        ));
+    }
+
+    quote!(
+        #[allow(non_camel_case_types)]
+        #[doc(hidden)]
+        #[repr(C)]
+        struct #container_ty {
+            #(#fields)*
+        }
+
+        #[allow(non_upper_case_globals)]
+        #[doc(hidden)]
+        static #container: rtic::export::mpu::Block<
+            #container_ty,
+            { rtic::export::mpu::block_align::<#container_ty>() },
+        > = rtic::export::mpu::Block::new(#container_ty { #(#inits)* });
+
+        #(#items)*
+
+        #(#domains)*
+    )
+}
+
+/// Computes every MPU domain and enables the MPU, run after all resources have been initialized
+pub fn post_init(app: &App) -> Vec<TokenStream2> {
+    if !app.args.mpu {
+        return vec![];
+    }
+
+    let container = container_ident();
+    let mut stmts = vec![];
+
+    for domain in domains(app) {
+        let name = domain_ident(domain.name);
+        let cfgs = domain.cfgs;
+        let mut grants = vec![];
+
+        // In the order of the fields in the container, so sorted by address
+        if !domain.local_resources.is_empty() {
+            let block = locals_field(domain.name);
+            grants.push(quote!(#container.get().#block.range()));
+        }
+
+        for (resource, res) in &app.shared_resources {
+            if domain.shared_resources.contains_key(resource) {
+                let res_cfgs = &res.cfgs;
+                let block = shared_field(resource);
+                grants.push(quote!(#(#res_cfgs)* #container.get().#block.range()));
+            }
+        }
+
+        stmts.push(quote!(
+            #(#cfgs)*
+            #name.configure(&#container, &[#(#grants),*]);
+        ));
+    }
+
+    let initial = if let Some(idle) = &app.idle {
+        domain_ident(&idle.name)
+    } else {
+        let name = main_domain_ident();
+        stmts.push(quote!(#name.configure(&#container, &[]);));
+        name
+    };
+
+    stmts.push(quote!(rtic::export::mpu::enable(&#initial);));
+
+    stmts
+}
+
+/// Wraps `run` so that it executes in the MPU domain of `task`
+pub fn in_domain(app: &App, task: &Ident, run: TokenStream2) -> TokenStream2 {
+    if !app.args.mpu {
+        return run;
+    }
+
+    let domain = domain_ident(task);
+
+    quote!({
+        let __rtic_previous_domain = rtic::export::mpu::switch(&#domain);
+        #run
+        rtic::export::mpu::restore(__rtic_previous_domain);
+    })
+}
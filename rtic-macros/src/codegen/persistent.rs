@@ -0,0 +1,158 @@
+use crate::{codegen::util, syntax::ast::App};
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{spanned::Spanned, Ident, Type};
+
+fn seal_ident(name: &Ident) -> Ident {
+    util::mark_internal_name(&format!("{name}_SEAL"))
+}
+
+/// FNV-1a hash of the name and type of the resource, so that a resource left behind by a
+/// firmware that declared it differently is not taken for a valid one
+fn magic(name: &Ident, ty: &Type) -> Literal {
+    let key = format!("{name}: {}", quote!(#ty));
+    let hash = key.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+
+    Literal::u32_suffixed(hash)
+}
+
+/// The resource in `mangled_name` if its seal survived the reset, as an `Option<&mut T>`
+pub fn restore(name: &Ident, ty: &Type, mangled_name: &Ident) -> TokenStream2 {
+    let seal = seal_ident(name);
+    let magic = magic(name, ty);
+
+    quote!(#seal.restore(#magic, #mangled_name.get_mut()))
+}
+
+/// Seals the current value of the resource, `value` is a `*const T` to it
+pub fn seal(name: &Ident, ty: &Type, value: TokenStream2) -> TokenStream2 {
+    let seal = seal_ident(name);
+    let magic = magic(name, ty);
+
+    quote!(#seal.seal(#magic, #value);)
+}
+
+fn seal_fn_ident(name: &Ident) -> Ident {
+    util::mark_internal_name(&format!("seal_{name}"))
+}
+
+/// The `#[on_fault]` function's access to the `#[persistent]` resources, `None` without any
+pub fn fault_persisted_ident(app: &App, hook: &Ident) -> Option<Ident> {
+    if util::persistent_resources(app).is_empty() {
+        None
+    } else {
+        Some(util::internal_task_ident(hook, "Persisted"))
+    }
+}
+
+/// Generates the seals of the `#[persistent]` resources, the `seal` module and the
+/// `#[on_fault]` function's `Persisted` struct
+pub fn codegen(app: &App) -> TokenStream2 {
+    let mut items = vec![];
+    let mut seal_fns = vec![];
+    let mut fields = vec![];
+    let mut values = vec![];
+
+    for (name, cfgs, ty, mangled_name) in util::persistent_resources(app) {
+        let seal = seal_ident(name);
+        let seal_fn = seal_fn_ident(name);
+        let section = util::link_section_uninit();
+        let storage = quote!((*#mangled_name.get()).as_ptr());
+        let seal_value = self::seal(name, ty, quote!(value));
+        let assert_persistent = quote_spanned!(ty.span()=>
+            let _ = assert_persistent::<#ty>;
+        );
+
+        items.push(quote!(
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            #(#cfgs)*
+            #section
+            static #seal: rtic::export::persistent::Seal = rtic::export::persistent::Seal::new();
+
+            #(#cfgs)*
+            const _: () = {
+                fn assert_persistent<T: rtic::persistent::Persistent>() {}
+                #assert_persistent
+            };
+
+            #(#cfgs)*
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            #[inline(always)]
+            pub fn #seal_fn(value: &mut #ty) {
+                ::core::assert!(
+                    ::core::ptr::eq(value, unsafe { #storage }),
+                    "`seal` was given a value other than the resource"
+                );
+                unsafe { #seal_value }
+            }
+        ));
+
+        seal_fns.push(quote!(
+            #(#cfgs)*
+            #[doc(inline)]
+            pub use super::#seal_fn as #name;
+        ));
+
+        fields.push(quote!(
+            #(#cfgs)*
+            #[allow(missing_docs)]
+            pub #name: &'static mut #ty,
+        ));
+        values.push(quote!(
+            #(#cfgs)*
+            #name: &mut *#mangled_name.get_mut().cast::<#ty>(),
+        ));
+    }
+
+    if seal_fns.is_empty() {
+        return quote!();
+    }
+
+    items.push(quote!(
+        /// Seals the `#[persistent]` resources, so that their current value survives a reset
+        ///
+        /// Each function takes the resource itself, from the context of a task or from the
+        /// `#[on_fault]` function, and panics if given any other value.
+        pub mod seal {
+            #(#seal_fns)*
+        }
+    ));
+
+    if let Some(hook) = &app.on_fault {
+        let name = &hook.name;
+        let persisted = fault_persisted_ident(app, name).expect("UNREACHABLE");
+
+        items.push(quote!(
+            /// The `#[persistent]` resources, as left by the tasks the fault interrupted
+            #[allow(non_camel_case_types)]
+            pub struct #persisted {
+                #(#fields)*
+            }
+
+            impl #persisted {
+                #[inline(always)]
+                #[doc(hidden)]
+                pub unsafe fn new() -> Self {
+                    unsafe {
+                        #persisted {
+                            #(#values)*
+                        }
+                    }
+                }
+            }
+
+            #[allow(non_snake_case)]
+            #[doc = "Items of the `#[on_fault]` function"]
+            pub mod #name {
+                #[doc(inline)]
+                pub use super::#persisted as Persisted;
+            }
+        ));
+    }
+
+    quote!(#(#items)*)
+}
@@ -1,17 +1,28 @@
-use crate::{analyze::Analysis, codegen::util, syntax::ast::App};
+use crate::{
+    analyze::Analysis,
+    codegen::{mpu, persistent, util, watchdog},
+    syntax::ast::App,
+};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
 /// Generates code that runs after `#[init]` returns
 pub fn codegen(app: &App, analysis: &Analysis) -> Vec<TokenStream2> {
     let mut stmts = vec![];
+    let export = util::export_mod(app);
 
     // Initialize shared resources
     for (name, res) in &app.shared_resources {
         let mangled_name = util::static_shared_resource_ident(name);
         // If it's live
         let cfgs = res.cfgs.clone();
-        if analysis.shared_resources.get(name).is_some() {
+        if res.properties.init_in_place {
+            stmts.push(quote!(
+                #(#cfgs)*
+                // `#[init]` wrote the resource through its slot, check it was the right one
+                shared_resources.#name.verify(#mangled_name.get_mut());
+            ));
+        } else if analysis.shared_resources.get(name).is_some() {
             stmts.push(quote!(
                 // We include the cfgs
                 #(#cfgs)*
@@ -20,6 +31,14 @@ pub fn codegen(app: &App, analysis: &Analysis) -> Vec<TokenStream2> {
                 // - `write` the defined value for the late resource T
                 #mangled_name.get_mut().write(core::mem::MaybeUninit::new(shared_resources.#name));
             ));
+
+            if res.properties.persistent {
+                let seal = persistent::seal(name, &res.ty, quote!((*#mangled_name.get()).as_ptr()));
+                stmts.push(quote!(
+                    #(#cfgs)*
+                    #seal
+                ));
+            }
         }
     }
 
@@ -28,7 +47,13 @@ pub fn codegen(app: &App, analysis: &Analysis) -> Vec<TokenStream2> {
         let mangled_name = util::static_local_resource_ident(name);
         // If it's live
         let cfgs = res.cfgs.clone();
-        if analysis.local_resources.get(name).is_some() {
+        if res.init_in_place {
+            stmts.push(quote!(
+                #(#cfgs)*
+                // `#[init]` wrote the resource through its slot, check it was the right one
+                local_resources.#name.verify(#mangled_name.get_mut());
+            ));
+        } else if analysis.local_resources.get(name).is_some() {
             stmts.push(quote!(
                 // We include the cfgs
                 #(#cfgs)*
@@ -37,11 +62,25 @@ pub fn codegen(app: &App, analysis: &Analysis) -> Vec<TokenStream2> {
                 // - `write` the defined value for the late resource T
                 #mangled_name.get_mut().write(core::mem::MaybeUninit::new(local_resources.#name));
             ));
+
+            if res.persistent {
+                let seal = persistent::seal(name, &res.ty, quote!((*#mangled_name.get()).as_ptr()));
+                stmts.push(quote!(
+                    #(#cfgs)*
+                    #seal
+                ));
+            }
         }
     }
 
+    // Protect the resources of each task from the others
+    stmts.extend(mpu::post_init(app));
+
+    // Start feeding the watchdog
+    stmts.extend(watchdog::post_init(app));
+
     // Enable the interrupts -- this completes the `init`-ialization phase
-    stmts.push(quote!(rtic::export::interrupt::enable();));
+    stmts.push(quote!(#export::interrupt::enable();));
 
     stmts
 }
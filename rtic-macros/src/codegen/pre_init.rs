@@ -1,4 +1,5 @@
 use super::bindings::{pre_init_checks, pre_init_enable_interrupts};
+use super::util;
 use crate::analyze::Analysis;
 use crate::syntax::ast::App;
 use proc_macro2::TokenStream as TokenStream2;
@@ -7,9 +8,10 @@ use quote::quote;
 /// Generates code that runs before `#[init]`
 pub fn codegen(app: &App, analysis: &Analysis) -> Vec<TokenStream2> {
     let mut stmts = vec![];
+    let export = util::export_mod(app);
 
     // Disable interrupts -- `init` must run with interrupts disabled
-    stmts.push(quote!(rtic::export::interrupt::disable();));
+    stmts.push(quote!(#export::interrupt::disable();));
 
     if app.args.core {
         stmts.push(quote!(
@@ -18,6 +20,16 @@ pub fn codegen(app: &App, analysis: &Analysis) -> Vec<TokenStream2> {
         ));
     }
 
+    if app.args.stack_usage {
+        // Paint the unused part of the stack so its high-water mark can be measured later
+        stmts.push(quote!(rtic::export::paint_stack();));
+    }
+
+    if let Some(bytes) = &app.args.stack_guard {
+        // Fault as soon as the stack grows into the bytes reserved for `#[on_stack_overflow]`
+        stmts.push(quote!(rtic::export::stack_guard::enable(#bytes);));
+    }
+
     stmts.append(&mut pre_init_checks(app, analysis));
 
     stmts.append(&mut pre_init_enable_interrupts(app, analysis));
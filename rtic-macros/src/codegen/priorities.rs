@@ -0,0 +1,39 @@
+use crate::codegen::util;
+use crate::syntax::ast::App;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::BTreeSet;
+
+/// Generates a constant for each priority given as a constant expression
+pub fn codegen(app: &App) -> TokenStream2 {
+    let levels = app
+        .hardware_tasks
+        .values()
+        .map(|task| task.args.priority)
+        .chain(app.software_tasks.values().map(|task| task.args.priority))
+        .collect::<BTreeSet<_>>();
+
+    let mut items = vec![];
+
+    for level in levels {
+        let Some(expr) = util::priority_expr(app, level) else {
+            continue;
+        };
+        let name = util::priority_const_ident(level);
+        let es = format!(
+            "the priority `{}` must be at least 1, priority 0 can only be given as the literal `0`",
+            quote!(#expr).to_string().replace(' ', "")
+        );
+
+        items.push(quote!(
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            const #name: u8 = #expr;
+
+            // Priority 0 is reserved for `#[idle]` and tasks dispatched from `main`
+            const _: () = if #name == 0 { ::core::panic!(#es); };
+        ));
+    }
+
+    quote!(#(#items)*)
+}
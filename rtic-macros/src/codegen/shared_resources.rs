@@ -39,16 +39,19 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
 
         // For future use
         // let doc = format!(" RTIC internal: {}:{}", file!(), line!());
-        mod_app.push(quote!(
-            #[allow(non_camel_case_types)]
-            #[allow(non_upper_case_globals)]
-            // #[doc = #doc]
-            #[doc(hidden)]
-            #(#attrs)*
-            #(#cfgs)*
-            #section
-            static #mangled_name: rtic::RacyCell<core::mem::MaybeUninit<#ty>> = rtic::RacyCell::new(core::mem::MaybeUninit::uninit());
-        ));
+        // With `mpu` the resource lives in the MPU container instead
+        if !app.args.mpu {
+            mod_app.push(quote!(
+                #[allow(non_camel_case_types)]
+                #[allow(non_upper_case_globals)]
+                // #[doc = #doc]
+                #[doc(hidden)]
+                #(#attrs)*
+                #(#cfgs)*
+                #section
+                static #mangled_name: rtic::RacyCell<core::mem::MaybeUninit<#ty>> = rtic::RacyCell::new(core::mem::MaybeUninit::uninit());
+            ));
+        }
 
         // For future use
         // let doc = format!(" RTIC internal: {}:{}", file!(), line!());
@@ -90,6 +93,7 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
                 Some(Ownership::Contended { ceiling }) => *ceiling,
                 None => 0,
             };
+            let ceiling = util::ceiling(app, name, ceiling);
 
             // For future use
             // let doc = format!(" RTIC internal ({} resource): {}:{}", doc, file!(), line!());
@@ -104,6 +108,79 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
                 ceiling,
                 &ptr,
             ));
+
+            // Tasks reading a resource that others write lock it up to the writers' ceiling only
+            if let Some(read_ceiling) = analysis.read_ceilings.get(name) {
+                let read_name = util::need_to_read_ident(name);
+                let ceiling_name = util::read_ceiling_ident(name);
+
+                mod_resources.push(quote!(
+                    #[doc(hidden)]
+                    #[allow(non_camel_case_types)]
+                    #(#cfgs)*
+                    pub struct #read_name<'a> {
+                        __rtic_internal_p: ::core::marker::PhantomData<(&'a (), *const u8)>,
+                    }
+
+                    #(#cfgs)*
+                    unsafe impl<'a> Sync for #read_name<'a> {}
+
+                    #(#cfgs)*
+                    impl<'a> #read_name<'a> {
+                        #[inline(always)]
+                        pub unsafe fn new() -> Self {
+                            #read_name { __rtic_internal_p: ::core::marker::PhantomData }
+                        }
+                    }
+
+                    #[doc(hidden)]
+                    #[allow(non_camel_case_types)]
+                    #(#cfgs)*
+                    pub struct #ceiling_name<'a> {
+                        __rtic_internal_p: ::core::marker::PhantomData<(&'a (), *const u8)>,
+                    }
+
+                    #(#cfgs)*
+                    impl<'a> #ceiling_name<'a> {
+                        #[inline(always)]
+                        pub unsafe fn new() -> Self {
+                            #ceiling_name { __rtic_internal_p: ::core::marker::PhantomData }
+                        }
+                    }
+                ));
+
+                mod_app.push(quote!(
+                    #(#cfgs)*
+                    impl<'a> shared_resources::#read_name<'a> {
+                        /// Runs `f` with a shared reference to the resource, only blocking the
+                        /// tasks that write it
+                        #[inline(always)]
+                        pub fn read<RTIC_INTERNAL_R>(
+                            &mut self,
+                            f: impl FnOnce(&#ty) -> RTIC_INTERNAL_R,
+                        ) -> RTIC_INTERNAL_R {
+                            let mut ceiling = unsafe { shared_resources::#ceiling_name::new() };
+                            rtic::Mutex::lock(&mut ceiling, |_| {
+                                f(unsafe { &*(&*#mangled_name.get()).as_ptr() })
+                            })
+                        }
+                    }
+                ));
+
+                // The lock only raises the priority, a `()` stands in for the resource so that
+                // readers never hold a `&mut` to it
+                let read_ceiling = util::read_ceiling(app, name, *read_ceiling);
+                mod_app.push(impl_mutex(
+                    app,
+                    analysis,
+                    cfgs,
+                    true,
+                    &ceiling_name,
+                    &quote!(()),
+                    read_ceiling,
+                    &quote!(::core::ptr::NonNull::<()>::dangling().as_ptr()),
+                ));
+            }
         }
     }
 
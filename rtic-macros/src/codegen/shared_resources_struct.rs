@@ -1,11 +1,11 @@
-use crate::syntax::{ast::App, Context};
+use crate::syntax::{analyze::Analysis, ast::App, Context};
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
 use crate::codegen::util;
 
 /// Generate shared resources structs
-pub fn codegen(ctxt: Context, app: &App) -> (TokenStream2, TokenStream2) {
+pub fn codegen(ctxt: Context, app: &App, analysis: &Analysis) -> (TokenStream2, TokenStream2) {
     let resources = match ctxt {
         Context::Init => unreachable!("Tried to generate shared resources struct for init"),
         Context::Idle => {
@@ -50,6 +50,21 @@ pub fn codegen(ctxt: Context, app: &App) -> (TokenStream2, TokenStream2) {
                 #[allow(missing_docs)]
                 pub #name: &#lt #mut_ #ty
             ));
+        } else if access.is_shared() && analysis.read_ceilings.contains_key(name) {
+            let read_name = util::need_to_read_ident(name);
+
+            fields.push(quote!(
+                #(#cfgs)*
+                #[allow(missing_docs)]
+                pub #name: shared_resources::#read_name<'a>
+            ));
+
+            values.push(quote!(
+                #(#cfgs)*
+                #name: shared_resources::#read_name::new()
+            ));
+
+            continue;
         } else if access.is_shared() {
             fields.push(quote!(
                 #(#cfgs)*
@@ -1,7 +1,7 @@
 use crate::syntax::{ast::App, Context};
 use crate::{
     analyze::Analysis,
-    codegen::{local_resources_struct, module, shared_resources_struct},
+    codegen::{local_resources_struct, module, shared_resources_struct, util},
 };
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
@@ -24,7 +24,7 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
 
         if !task.args.shared_resources.is_empty() {
             let (item, constructor) =
-                shared_resources_struct::codegen(Context::SoftwareTask(name), app);
+                shared_resources_struct::codegen(Context::SoftwareTask(name), app, analysis);
 
             root.push(item);
 
@@ -70,6 +70,21 @@ pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
         root.push(module::codegen(Context::SoftwareTask(name), app, analysis));
     }
 
+    // The names the wakers of the tasks point into
+    if !app.software_tasks.is_empty() {
+        let task_names = util::task_names_ident();
+        let names = util::task_names(app)
+            .into_iter()
+            .map(|name| name.to_string());
+        let n = names.len();
+
+        mod_app.push(quote!(
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            static #task_names: [&str; #n] = [#(#names,)*];
+        ));
+    }
+
     quote!(
         #(#mod_app)*
 
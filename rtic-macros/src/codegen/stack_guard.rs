@@ -0,0 +1,42 @@
+use crate::{analyze::Analysis, codegen::bindings::stack_overflow_handler, syntax::ast::App};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+/// Generates the `#[on_stack_overflow]` function and the fault handler calling it
+pub fn codegen(app: &App, analysis: &Analysis) -> TokenStream2 {
+    let (Some(hook), Some(reserve)) = (&app.on_stack_overflow, &app.args.stack_guard) else {
+        return quote!();
+    };
+
+    let attrs = &hook.attrs;
+    let name = &hook.name;
+    let priority = &hook.priority;
+    let stmts = &hook.stmts;
+    let handler = stack_overflow_handler(app, analysis);
+
+    quote!(
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        fn #name(#priority: u8) -> ! {
+            #(#stmts)*
+        }
+
+        const _: () = ::core::assert!(
+            rtic::export::stack_guard::SUPPORTED,
+            "`stack_guard` is not supported by the selected backend"
+        );
+
+        const _: () = ::core::assert!(
+            #reserve >= rtic::export::stack_guard::MIN_RESERVE,
+            "`stack_guard` is too small to hold the frames the trap entry stores next to the guard region"
+        );
+
+        #[doc(hidden)]
+        #[no_mangle]
+        fn __rtic_on_stack_overflow(priority: u8) -> ! {
+            #name(priority)
+        }
+
+        #(#handler)*
+    )
+}
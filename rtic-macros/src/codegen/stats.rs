@@ -0,0 +1,27 @@
+use crate::{codegen::util, syntax::ast::App};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+/// Generates `task_stats()`, iterating over the executor counters of all software tasks
+pub fn codegen(app: &App) -> TokenStream2 {
+    if !cfg!(feature = "executor-stats") {
+        return quote!();
+    }
+
+    let tasks = app.software_tasks.iter().map(|(name, task)| {
+        let cfgs = &task.cfgs;
+        let internal_stats_ident = util::internal_task_ident(name, "stats");
+        let name = name.to_string();
+
+        quote!(#(#cfgs)* (#name, #internal_stats_ident))
+    });
+
+    quote!(
+        /// The name and executor counters of every software task
+        pub fn task_stats() -> impl Iterator<Item = (&'static str, rtic::stats::TaskStats)> {
+            let tasks: &[(&'static str, fn() -> rtic::stats::TaskStats)] = &[#(#tasks,)*];
+
+            tasks.iter().map(|(name, stats)| (*name, stats()))
+        }
+    )
+}
@@ -1,8 +1,8 @@
 use crate::syntax::{ast::App, Context};
 use core::sync::atomic::{AtomicUsize, Ordering};
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use quote::quote;
-use syn::{Attribute, Ident, PatType};
+use syn::{Attribute, Ident, LitInt, LitStr, PatType, Type};
 
 const RTIC_INTERNAL: &str = "__rtic_internal";
 
@@ -16,6 +16,32 @@ pub fn internal_task_ident(task: &Ident, ident_name: &str) -> Ident {
     mark_internal_name(&format!("{task}_{ident_name}"))
 }
 
+/// The names of the tasks, indexed by their id
+pub fn task_names(app: &App) -> Vec<&Ident> {
+    let idle = app.idle.iter().map(|idle| &idle.name);
+    let hardware_tasks = app.hardware_tasks.keys();
+    let software_tasks = app.software_tasks.keys();
+
+    [&app.init.name]
+        .into_iter()
+        .chain(idle)
+        .chain(hardware_tasks)
+        .chain(software_tasks)
+        .collect()
+}
+
+/// The id of `task`, its index in [`task_names`]
+pub fn task_id(app: &App, task: &Ident) -> u16 {
+    let id = task_names(app).iter().position(|name| *name == task);
+
+    id.expect("UNREACHABLE") as u16
+}
+
+/// The table of task names the wakers of the software tasks point into
+pub fn task_names_ident() -> Ident {
+    mark_internal_name("TASK_NAMES")
+}
+
 fn link_section_index() -> usize {
     static INDEX: AtomicUsize = AtomicUsize::new(0);
 
@@ -148,14 +174,179 @@ pub fn declared_static_local_resource_ident(name: &Ident, task_name: &Ident) ->
     mark_internal_name(&format!("local_{task_name}_{name}"))
 }
 
+/// Where the next round of the dispatcher of `level` starts, with `fair_dispatch`
+pub fn dispatch_cursor_ident(level: u8) -> Ident {
+    mark_internal_name(&format!("DISPATCH_CURSOR_{level}"))
+}
+
+/// The constant holding the priority of the tasks at `level`, when given as an expression
+pub fn priority_const_ident(level: u8) -> Ident {
+    mark_internal_name(&format!("PRIORITY_{level}"))
+}
+
+/// The constant expression tasks at `level` were given as their priority, if any
+pub fn priority_expr(app: &App, level: u8) -> Option<&syn::Expr> {
+    app.hardware_tasks
+        .values()
+        .map(|task| (task.args.priority, &task.args.priority_expr))
+        .chain(
+            app.software_tasks
+                .values()
+                .map(|task| (task.args.priority, &task.args.priority_expr)),
+        )
+        .find_map(|(priority, expr)| match expr {
+            Some(expr) if priority == level => Some(&**expr),
+            _ => None,
+        })
+}
+
+/// The first priority given as a constant expression, for backends that don't support them
+#[allow(dead_code)]
+pub fn const_priority(app: &App) -> Option<&syn::Expr> {
+    app.hardware_tasks
+        .values()
+        .map(|task| &task.args.priority_expr)
+        .chain(
+            app.software_tasks
+                .values()
+                .map(|task| &task.args.priority_expr),
+        )
+        .find_map(|expr| expr.as_deref())
+}
+
+/// The priority of the tasks at `level`, as a literal or as the constant holding the expression
+pub fn priority(app: &App, level: u8) -> TokenStream2 {
+    if priority_expr(app, level).is_some() {
+        let name = priority_const_ident(level);
+        quote!(#name)
+    } else {
+        quote!(#level)
+    }
+}
+
+/// The ceiling of the shared resource `name`, or `ceiling` if all of its users have literal
+/// priorities
+pub fn ceiling(app: &App, name: &Ident, ceiling: u8) -> TokenStream2 {
+    ceiling_of(app, name, ceiling, false)
+}
+
+/// The ceiling of the `read` lock of the shared resource `name`, or `ceiling` if all of its writers
+/// have literal priorities
+pub fn read_ceiling(app: &App, name: &Ident, ceiling: u8) -> TokenStream2 {
+    ceiling_of(app, name, ceiling, true)
+}
+
+fn ceiling_of(app: &App, name: &Ident, ceiling: u8, writers_only: bool) -> TokenStream2 {
+    let levels = app
+        .shared_resource_accesses()
+        .filter(|(_, _, access)| !writers_only || access.is_exclusive())
+        .filter_map(|(priority, resource, _)| priority.filter(|_| resource == name))
+        .collect::<std::collections::BTreeSet<_>>();
+
+    if levels
+        .iter()
+        .all(|level| priority_expr(app, *level).is_none())
+    {
+        return quote!(#ceiling);
+    }
+
+    let priorities = levels.into_iter().map(|level| priority(app, level));
+    quote!(rtic::export::max_priority([#(#priorities),*]))
+}
+
+/// Shared and local resources marked `#[init_in_place]`, with the `static` holding each of them
+pub fn init_in_place_resources(app: &App) -> Vec<(&Ident, &[Attribute], &Type, Ident)> {
+    let shared = app
+        .shared_resources
+        .iter()
+        .filter(|(_, res)| res.properties.init_in_place)
+        .map(|(name, res)| {
+            let mangled_name = static_shared_resource_ident(name);
+            (name, &res.cfgs[..], &*res.ty, mangled_name)
+        });
+    let local = app
+        .local_resources
+        .iter()
+        .filter(|(_, res)| res.init_in_place)
+        .map(|(name, res)| {
+            let mangled_name = static_local_resource_ident(name);
+            (name, &res.cfgs[..], &*res.ty, mangled_name)
+        });
+
+    shared.chain(local).collect()
+}
+
+/// Shared and local resources marked `#[persistent]`, with the `static` holding each of them
+pub fn persistent_resources(app: &App) -> Vec<(&Ident, &[Attribute], &Type, Ident)> {
+    let shared = app
+        .shared_resources
+        .iter()
+        .filter(|(_, res)| res.properties.persistent)
+        .map(|(name, res)| {
+            let mangled_name = static_shared_resource_ident(name);
+            (name, &res.cfgs[..], &*res.ty, mangled_name)
+        });
+    let local = app
+        .local_resources
+        .iter()
+        .filter(|(_, res)| res.persistent)
+        .map(|(name, res)| {
+            let mangled_name = static_local_resource_ident(name);
+            (name, &res.cfgs[..], &*res.ty, mangled_name)
+        });
+
+    shared.chain(local).collect()
+}
+
 pub fn need_to_lock_ident(name: &Ident) -> Ident {
     Ident::new(&format!("{name}_that_needs_to_be_locked"), name.span())
 }
 
+pub fn need_to_read_ident(name: &Ident) -> Ident {
+    Ident::new(&format!("{name}_that_needs_to_be_read"), name.span())
+}
+
+/// The type implementing the `read` lock of a resource as a `Mutex` over `()`
+pub fn read_ceiling_ident(name: &Ident) -> Ident {
+    Ident::new(&format!("{name}_read_ceiling"), name.span())
+}
+
 pub fn zero_prio_dispatcher_ident() -> Ident {
     Ident::new("__rtic_internal_async_0_prio_dispatcher", Span::call_site())
 }
 
+/// The module providing `run`, `pend` and the `interrupt` functions to the generated code
+///
+/// Custom backends, the PLIC backend and multi-hart apps get an `__rtic_internal_backend` module
+/// of their own.
+pub fn export_mod(app: &App) -> TokenStream2 {
+    if cfg!(any(feature = "custom", feature = "riscv-plic")) || !app.args.harts().is_empty() {
+        quote!(__rtic_internal_backend)
+    } else {
+        quote!(rtic::export)
+    }
+}
+
+/// The hart a task pinned with `core` runs on, in multi-hart apps
+pub fn task_hart(app: &App, core: &Option<LitInt>) -> Option<Literal> {
+    let harts = app.args.harts();
+    let hart = match core {
+        Some(core) => core.base10_parse().ok()?,
+        None => *harts.first()?,
+    };
+
+    Some(Literal::u32_unsuffixed(hart))
+}
+
+/// Pends `interrupt` to dispatch a software task, on the hart of the task in multi-hart apps
+pub fn pend_dispatcher(app: &App, interrupt: TokenStream2, core: &Option<LitInt>) -> TokenStream2 {
+    let export = export_mod(app);
+    match task_hart(app, core) {
+        Some(hart) => quote!(#export::pend_on(#hart, #interrupt);),
+        None => quote!(#export::pend(#interrupt);),
+    }
+}
+
 /// The name to get better RT flag errors
 pub fn rt_err_ident() -> Ident {
     Ident::new(
@@ -164,15 +355,45 @@ pub fn rt_err_ident() -> Ident {
     )
 }
 
+/// Statements run by hardware tasks and async dispatchers before running at `PRIORITY`
+pub fn priority_entry(app: &App) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    if app.args.stack_usage {
+        stmts.push(quote!(rtic::export::stack_enter::<PRIORITY>();));
+    }
+
+    if app.args.stack_guard.is_some() {
+        stmts.push(quote!(
+            let __rtic_preempted_priority = rtic::export::stack_guard::enter(PRIORITY);
+        ));
+    }
+
+    stmts
+}
+
+/// Statements run by hardware tasks and async dispatchers after running at `PRIORITY`
+pub fn priority_exit(app: &App) -> Vec<TokenStream2> {
+    let mut stmts = vec![];
+
+    if app.args.stack_guard.is_some() {
+        stmts.push(quote!(rtic::export::stack_guard::exit(__rtic_preempted_priority);));
+    }
+
+    stmts
+}
+
 /// Declares the `static` holding a task's executor.
 ///
 /// The future's type cannot be written down, so the storage is declared as bytes of the right size
 /// and alignment. [`executor_expr`] is the matching accessor and has to be used with this.
-pub fn executor_decl(name: &Ident, cfgs: &[Attribute]) -> TokenStream2 {
+pub fn executor_decl(name: &Ident, cfgs: &[Attribute], section: Option<&LitStr>) -> TokenStream2 {
     let exec_name = internal_task_ident(name, "EXEC");
+    let section = section.map(|section| quote!(#[link_section = #section]));
 
     quote!(
         #(#cfgs)*
+        #section
         #[allow(non_upper_case_globals)]
         static #exec_name: rtic::export::executor::ExecutorHolder<
             { rtic::export::executor::exec_size::<_, _, _>(#name) },
@@ -183,6 +404,22 @@ pub fn executor_decl(name: &Ident, cfgs: &[Attribute]) -> TokenStream2 {
     )
 }
 
+/// The queue of the callers of `spawn_wait` of a task
+pub fn waiters_ident(name: &Ident) -> Ident {
+    internal_task_ident(name, "WAITERS")
+}
+
+/// An expression for the `Option<&WaitQueue>` of the callers of `spawn_wait` of a task, `None`
+/// unless the task has `spawn_wait`
+pub fn waiters_expr(app: &App, name: &Ident) -> TokenStream2 {
+    if app.software_tasks[name].args.spawn_wait {
+        let waiters = waiters_ident(name);
+        quote!(Some(&#waiters))
+    } else {
+        quote!(None)
+    }
+}
+
 /// An expression for `&'static AsyncTaskExecutor<_>`, to pair with [`executor_decl`].
 ///
 /// Only sound where [`executor_decl`] emitted the matching declaration for the same task.
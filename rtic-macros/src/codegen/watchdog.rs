@@ -0,0 +1,87 @@
+use crate::{
+    codegen::util,
+    syntax::{ast::App, checkins_ident, supervisor_ident},
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Ident;
+
+/// The tasks marked `supervised`, indexed by their id
+fn supervised(app: &App) -> Vec<&Ident> {
+    let hardware_tasks = app
+        .hardware_tasks
+        .iter()
+        .filter(|(_, task)| task.args.supervised)
+        .map(|(name, _)| name);
+    let software_tasks = app
+        .software_tasks
+        .iter()
+        .filter(|(_, task)| task.args.supervised)
+        .map(|(name, _)| name);
+
+    hardware_tasks.chain(software_tasks).collect()
+}
+
+/// The `checkin` method of the context of a `supervised` task
+pub fn checkin(app: &App, task: &Ident) -> Option<TokenStream2> {
+    let id = supervised(app).iter().position(|name| *name == task)?;
+    let checkins = checkins_ident();
+
+    Some(quote!(
+        /// Reports to the watchdog supervisor that the task is making progress
+        #[inline(always)]
+        pub fn checkin(&self) {
+            #checkins.check_in(#id);
+        }
+    ))
+}
+
+/// Starts the watchdog and its supervisor once `#[init]` returns
+pub fn post_init(app: &App) -> Vec<TokenStream2> {
+    let (Some(watchdog), Some(timeout)) = (&app.args.watchdog, &app.args.timeout) else {
+        return vec![];
+    };
+    let supervisor = supervisor_ident();
+    let internal_spawn_ident = util::internal_task_ident(&supervisor, "spawn");
+
+    vec![quote!(
+        <#watchdog as rtic::watchdog::Watchdog>::start(#timeout);
+        // Only spawned here, so it can't be running already
+        let _ = #internal_spawn_ident();
+    )]
+}
+
+/// Generates the `#[on_starvation]` function and the record of the check-ins
+pub fn codegen(app: &App) -> TokenStream2 {
+    if app.args.watchdog.is_none() {
+        return quote!();
+    }
+
+    let checkins = checkins_ident();
+    let names = supervised(app).into_iter().map(|name| name.to_string());
+    let n = names.len();
+
+    let on_starvation = app.on_starvation.as_ref().map(|hook| {
+        let attrs = &hook.attrs;
+        let name = &hook.name;
+        let task = &hook.task;
+        let stmts = &hook.stmts;
+
+        quote!(
+            #(#attrs)*
+            #[allow(non_snake_case)]
+            fn #name(#task) {
+                #(#stmts)*
+            }
+        )
+    });
+
+    quote!(
+        #on_starvation
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        static #checkins: rtic::export::watchdog::Checkins<#n> =
+            rtic::export::watchdog::Checkins::new([#(#names,)*]);
+    )
+}
@@ -0,0 +1,179 @@
+//! Components, modules of tasks and resources that are merged into an `#[app]`
+//!
+//! Procedural macros only see their own input, so a component is turned into a `macro_rules!`
+//! macro that carries the tokens of its items. `#[app(components = [a, b])]` expands to a call of
+//! `a::component!`, which appends its items and calls `b::component!`; the last component expands
+//! the `#[app]` attribute again, with the collected items in a hidden argument. Spans are kept, so
+//! errors point into the component that caused them.
+
+use std::path::{Component, Path};
+
+use proc_macro2::{Group, Punct, Spacing, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote};
+use syn::{parse, AttrStyle, Ident, ItemMod};
+
+use crate::syntax::{ast::AppArgs, ComponentArgs};
+
+/// Expands `#[rtic::component]`
+pub fn expand(args: TokenStream2, input: TokenStream2) -> parse::Result<TokenStream2> {
+    // Only checked here, the arguments are parsed again when the app collects the component
+    ComponentArgs::parse(args.clone())?;
+
+    let item: ItemMod = syn::parse2(input)?;
+    let Some((_, items)) = &item.content else {
+        return Err(parse::Error::new(
+            item.ident.span(),
+            "`#[rtic::component]` must be applied to an inline module",
+        ));
+    };
+
+    let body = crate_to_dollar_crate(quote!(#(#items)*))?;
+
+    let attrs = item
+        .attrs
+        .iter()
+        .filter(|attr| matches!(attr.style, AttrStyle::Outer));
+    let vis = &item.vis;
+    let name = &item.ident;
+
+    // `#[macro_export]` places the macro at the root of the crate, make its name unique
+    let exported = exported_ident(name);
+
+    Ok(quote!(
+        #(#attrs)*
+        #vis mod #name {
+            #[doc(hidden)]
+            #[macro_export]
+            macro_rules! #exported {
+                // Last component, expand the app with all collected components
+                (@rtic [$($app:tt)*] [] [$($args:tt)*] [$($collected:tt)*] $($item:tt)*) => {
+                    #[$($app)*(
+                        __rtic_components = { $($collected)* #name (#args) { #body } },
+                        $($args)*
+                    )]
+                    $($item)*
+                };
+
+                // Pass the collected components on to the next one
+                (@rtic [$($app:tt)*] [[$($next:tt)*] $($rest:tt)*] [$($args:tt)*] [$($collected:tt)*] $($item:tt)*) => {
+                    $($next)*::component! {
+                        @rtic [$($app)*] [$($rest)*] [$($args)*] [$($collected)* #name (#args) { #body }] $($item)*
+                    }
+                };
+            }
+
+            #[doc(hidden)]
+            #[allow(macro_expanded_macro_exports_accessed_by_absolute_paths)]
+            pub use #exported as component;
+        }
+    ))
+}
+
+// The name of the exported macro, from the path of the component: the modules given by the file
+// that defines it, relative to `src`, then the name of the component module. Modules declared
+// inline in that file are not visible to the macro, so the line and column of the attribute tell
+// apart components of the same name nested in them.
+fn exported_ident(name: &Ident) -> Ident {
+    let call_site = proc_macro::Span::call_site();
+    let file = call_site.file();
+    let file = Path::new(&file).with_extension("");
+    let components = file
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let modules = match components.iter().rposition(|part| *part == "src") {
+        Some(src) => &components[src + 1..],
+        None => &components[components.len().saturating_sub(1)..],
+    };
+
+    let mut exported = String::from("__rtic_component");
+    for module in modules
+        .iter()
+        .filter(|module| !matches!(**module, "lib" | "main" | "mod"))
+    {
+        exported.push('_');
+        exported.extend(
+            module
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' }),
+        );
+    }
+
+    format_ident!(
+        "{exported}_{name}_{}_{}",
+        call_site.line(),
+        call_site.column()
+    )
+}
+
+/// Expands `#[app]` into the call of its first component, if it has components left to collect
+pub fn chain(
+    attribute: TokenStream2,
+    args: TokenStream2,
+    input: TokenStream2,
+) -> parse::Result<Option<TokenStream2>> {
+    let app_args = AppArgs::parse(args.clone())?;
+    if app_args.collected_components.is_some() {
+        return Ok(None);
+    }
+
+    let mut components = app_args.components.iter();
+    let Some(first) = components.next() else {
+        return Ok(None);
+    };
+    let rest = components.map(|path| quote!([#path]));
+
+    Ok(Some(quote!(
+        #first::component! { @rtic [#attribute] [#(#rest)*] [#args] [] #input }
+    )))
+}
+
+// The items of a component end up in the crate of the app, so `crate::` paths are replaced by
+// `$crate::`, which the `macro_rules!` macro resolves to the crate of the component. `self::` and
+// `super::` paths would resolve relative to the app instead of the component and are rejected.
+fn crate_to_dollar_crate(tokens: TokenStream2) -> parse::Result<TokenStream2> {
+    let mut out = vec![];
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(tt) = tokens.next() {
+        match tt {
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), crate_to_dollar_crate(group.stream())?);
+                new.set_span(group.span());
+                out.push(TokenTree::Group(new));
+            }
+            TokenTree::Punct(punct) if punct.as_char() == '$' => {
+                return Err(parse::Error::new(
+                    punct.span(),
+                    "`$` can't be used in a component; define macros outside of it",
+                ));
+            }
+            TokenTree::Ident(ident)
+                if (ident == "self" || ident == "super")
+                    && matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ':') =>
+            {
+                return Err(parse::Error::new(
+                    ident.span(),
+                    format!(
+                        "`{ident}::` paths can't be used in a component, its items are moved into the app; use a `crate::` path instead"
+                    ),
+                ));
+            }
+            TokenTree::Ident(ident)
+                if ident == "crate"
+                    && matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ':') =>
+            {
+                let mut dollar = Punct::new('$', Spacing::Alone);
+                dollar.set_span(ident.span());
+                out.push(TokenTree::Punct(dollar));
+                out.push(TokenTree::Ident(ident));
+            }
+            tt => out.push(tt),
+        }
+    }
+
+    Ok(out.into_iter().collect())
+}
@@ -6,6 +6,7 @@
 mod analyze;
 mod check;
 mod codegen;
+mod component;
 mod preprocess;
 mod syntax;
 use proc_macro::TokenStream;
@@ -15,6 +16,16 @@ use std::{env, fs, path::Path};
 #[doc(hidden)]
 #[proc_macro_attribute]
 pub fn mock_app(args: TokenStream, input: TokenStream) -> TokenStream {
+    match component::chain(
+        quote::quote!(::rtic_macros::mock_app),
+        args.clone().into(),
+        input.clone().into(),
+    ) {
+        Err(e) => return e.to_compile_error().into(),
+        Ok(Some(ts)) => return ts.into(),
+        Ok(None) => {}
+    }
+
     if let Err(e) = syntax::parse(args, input) {
         e.to_compile_error().into()
     } else {
@@ -31,6 +42,17 @@ pub fn mock_app(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Should never panic, cargo feeds a path which is later converted to a string
 #[proc_macro_attribute]
 pub fn app(_args: TokenStream, _input: TokenStream) -> TokenStream {
+    // Collect the items of the components first, this expands `#[app]` again
+    match component::chain(
+        quote::quote!(::rtic::app),
+        _args.clone().into(),
+        _input.clone().into(),
+    ) {
+        Err(e) => return e.to_compile_error().into(),
+        Ok(Some(ts)) => return ts.into(),
+        Ok(None) => {}
+    }
+
     let (mut app, analysis) = match syntax::parse(_args, _input) {
         Err(e) => return e.to_compile_error().into(),
         Ok(x) => x,
@@ -94,3 +116,17 @@ pub fn app(_args: TokenStream, _input: TokenStream) -> TokenStream {
 
     ts.into()
 }
+
+/// Attribute used to declare a component of a RTIC application
+///
+/// The tasks and resources of the module are merged into the apps that list it in
+/// `#[app(components = [..])]`.
+///
+/// For user documentation see the [RTIC book](https://rtic.rs)
+#[proc_macro_attribute]
+pub fn component(args: TokenStream, input: TokenStream) -> TokenStream {
+    match component::expand(args.into(), input.into()) {
+        Err(e) => e.to_compile_error().into(),
+        Ok(ts) => ts.into(),
+    }
+}
@@ -16,6 +16,8 @@ mod backend;
 mod check;
 mod parse;
 
+pub(crate) use parse::{checkins_ident, supervisor_ident, ComponentArgs};
+
 /// An ordered map keyed by identifier
 pub type Map<T> = IndexMap<Ident, T>;
 
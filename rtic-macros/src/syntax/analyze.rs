@@ -239,6 +239,31 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
         }
     }
 
+    // Resources that some tasks only read get a read ceiling, which only the writers count towards
+    let mut read_ceilings = ReadCeilings::new();
+    for (name, res) in &app.shared_resources {
+        if res.properties.lock_free {
+            continue;
+        }
+
+        let accesses = app
+            .shared_resource_accesses()
+            .filter_map(|(priority, resource, access)| {
+                priority.filter(|_| resource == name).map(|p| (p, access))
+            })
+            .collect::<Vec<_>>();
+        let read = accesses.iter().any(|(_, access)| access.is_shared());
+        let written = accesses
+            .iter()
+            .filter(|(_, access)| access.is_exclusive())
+            .map(|(priority, _)| *priority)
+            .max();
+
+        if let (true, Some(ceiling)) = (read, written) {
+            read_ceilings.insert(name.clone(), ceiling);
+        }
+    }
+
     // Create the list of used local resource Idents
     let mut used_local_resource = IndexSet::new();
 
@@ -314,6 +339,7 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
         shared_resources: used_shared_resource,
         local_resources: used_local_resource,
         ownerships,
+        read_ceilings,
         send_types,
         sync_types,
     })
@@ -351,6 +377,9 @@ pub struct Analysis {
     /// Resource ownership
     pub ownerships: Ownerships,
 
+    /// Ceilings of the `read` locks of the resources that are accessed both as `&x` and as `x`
+    pub read_ceilings: ReadCeilings,
+
     /// These types must implement the `Send` trait
     pub send_types: SendTypes,
 
@@ -370,6 +399,9 @@ pub type UsedLocalResource = IndexSet<Resource>;
 /// Resource ownership
 pub type Ownerships = IndexMap<Resource, Ownership>;
 
+/// The highest priority among the tasks writing a resource, keyed by resource
+pub type ReadCeilings = IndexMap<Resource, Priority>;
+
 /// These types must implement the `Send` trait
 pub type SendTypes = Set<Box<Type>>;
 
@@ -1,6 +1,8 @@
 //! Abstract Syntax Tree
 
-use syn::{Attribute, Expr, Ident, Item, ItemUse, Meta, Pat, PatType, Path, Stmt, Type};
+use syn::{
+    Attribute, Expr, Ident, Item, ItemUse, LitInt, LitStr, Meta, Pat, PatType, Path, Stmt, Type,
+};
 
 use crate::syntax::{backend::BackendArgs, Map};
 
@@ -44,6 +46,15 @@ pub struct App {
 
     /// Async software tasks: `#[task]`
     pub software_tasks: Map<SoftwareTask>,
+
+    /// The `#[on_stack_overflow]` function
+    pub on_stack_overflow: Option<OnStackOverflow>,
+
+    /// The `#[on_fault]` function
+    pub on_fault: Option<OnFault>,
+
+    /// The `#[on_starvation]` function
+    pub on_starvation: Option<OnStarvation>,
 }
 
 /// Interrupts used to dispatch software tasks
@@ -61,7 +72,14 @@ pub struct Dispatcher {
 #[derive(Debug)]
 pub struct AppArgs {
     /// Device
-    pub device: Path,
+    pub device: Option<Path>,
+
+    /// The interrupt enumeration, `#device::interrupt` when not given
+    pub interrupts: Option<Path>,
+
+    /// The number of priority bits implemented by the interrupt controller, `#device::NVIC_PRIO_BITS`
+    /// when not given
+    pub prio_bits: Option<LitInt>,
 
     /// Core peripherals
     pub core: bool,
@@ -72,9 +90,61 @@ pub struct AppArgs {
     /// Interrupts used to dispatch software tasks
     pub dispatchers: Dispatchers,
 
+    /// Poll the software tasks of each priority in rounds, starting after the task polled last
+    pub fair_dispatch: bool,
+
+    /// Paint the stack and record stack usage per priority level
+    pub stack_usage: bool,
+
+    /// Bytes at the bottom of the stack reserved for the `#[on_stack_overflow]` function
+    pub stack_guard: Option<LitInt>,
+
+    /// Isolate the resources of each task using the MPU
+    pub mpu: bool,
+
+    /// The `rtic::watchdog::Watchdog` fed on behalf of the `supervised` tasks
+    pub watchdog: Option<Path>,
+
+    /// The window in which each `supervised` task must check in, required by `watchdog`
+    pub timeout: Option<Box<Expr>>,
+
     /// Backend-specific arguments
     #[allow(dead_code)]
     pub backend: Option<BackendArgs>,
+
+    /// Paths to the `#[rtic::component]` modules listed in `components`
+    pub components: Vec<Path>,
+
+    /// The components collected from the modules in `components`, `None` until they have been
+    /// expanded into the `#[app]` attribute
+    pub collected_components: Option<Vec<Component>>,
+}
+
+impl AppArgs {
+    /// The `mhartid`s of a multi-hart app, the first one running `#[init]` and `#[idle]`; empty on
+    /// single-hart apps
+    pub fn harts(&self) -> Vec<u32> {
+        #[cfg(feature = "riscv-clint")]
+        if let Some(backend) = &self.backend {
+            return backend.harts.iter().map(|hart| hart.id).collect();
+        }
+
+        vec![]
+    }
+}
+
+/// A `#[rtic::component]`, whose items are merged into the `#[app]` module
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Component {
+    /// The name of the component module
+    pub name: Ident,
+
+    /// Interrupts the component adds to the ones used to dispatch software tasks
+    pub dispatchers: Dispatchers,
+
+    /// The items of the component module
+    pub items: Vec<Item>,
 }
 
 /// The `init`-ialization function
@@ -165,11 +235,74 @@ impl Default for IdleArgs {
     }
 }
 
+/// The `#[on_stack_overflow]` function
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct OnStackOverflow {
+    /// Attributes that will apply to this function
+    pub attrs: Vec<Attribute>,
+
+    /// The name of the `#[on_stack_overflow]` function
+    pub name: Ident,
+
+    /// The argument receiving the priority of the task that overflowed the stack
+    pub priority: Box<Pat>,
+
+    /// The statements that make up this function
+    pub stmts: Vec<Stmt>,
+}
+
+/// The `#[on_fault]` function
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct OnFault {
+    /// Attributes that will apply to this function
+    pub attrs: Vec<Attribute>,
+
+    /// The name of the `#[on_fault]` function
+    pub name: Ident,
+
+    /// The argument receiving the fault frame
+    pub frame: PatType,
+
+    /// The argument receiving the tasks running when the fault was taken
+    pub tasks: PatType,
+
+    /// The argument receiving the `#[persistent]` resources, if any
+    pub persisted: Option<PatType>,
+
+    /// The statements that make up this function
+    pub stmts: Vec<Stmt>,
+}
+
+/// The `#[on_starvation]` function
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct OnStarvation {
+    /// Attributes that will apply to this function
+    pub attrs: Vec<Attribute>,
+
+    /// The name of the `#[on_starvation]` function
+    pub name: Ident,
+
+    /// The argument receiving the name of a task that starved, called once per such task
+    pub task: PatType,
+
+    /// The statements that make up this function
+    pub stmts: Vec<Stmt>,
+}
+
 /// Shared resource properties
 #[derive(Debug)]
 pub struct SharedResourceProperties {
     /// A lock free (exclusive resource)
     pub lock_free: bool,
+
+    /// Initialized by `#[init]` through a slot rather than returned by value
+    pub init_in_place: bool,
+
+    /// Kept across warm resets, see `#[persistent]`
+    pub persistent: bool,
 }
 
 /// A shared resource, defined in `#[shared]`
@@ -213,6 +346,12 @@ pub struct LocalResource {
 
     /// The visibility of this resource
     pub vis: syn::Visibility,
+
+    /// Initialized by `#[init]` through a slot rather than returned by value
+    pub init_in_place: bool,
+
+    /// Kept across warm resets, see `#[persistent]`
+    pub persistent: bool,
 }
 
 /// An async software task
@@ -251,6 +390,12 @@ pub struct SoftwareTaskArgs {
     /// The priority of this task
     pub priority: u8,
 
+    /// The `priority` when given as a constant expression rather than a literal
+    ///
+    /// `priority` then holds a placeholder level that is unique to the expression, so that tasks
+    /// can still be grouped by priority before the expression is evaluated.
+    pub priority_expr: Option<Box<Expr>>,
+
     /// Local resources that can be accessed from this context
     pub local_resources: LocalResources,
 
@@ -262,15 +407,37 @@ pub struct SoftwareTaskArgs {
     /// Local tasks can only be spawned from the same executor.
     /// However they do not require Send and Sync
     pub local_task: bool,
+
+    /// Checks in with the watchdog supervisor through `cx.checkin()`
+    pub supervised: bool,
+
+    /// Can be spawned with `spawn_wait`, which gives the task a queue of waiting spawners
+    pub spawn_wait: bool,
+
+    /// The hart this task runs on, in multi-hart apps
+    pub core: Option<LitInt>,
+
+    /// The linker section of the executor holding the future of this task
+    pub executor_section: Option<LitStr>,
+
+    /// `executor_section` was given as `unsafe("..")`: the user vouches that the runtime zeroes or
+    /// loads a section RTIC doesn't know about
+    pub executor_section_unsafe: bool,
 }
 
 impl Default for SoftwareTaskArgs {
     fn default() -> Self {
         Self {
             priority: 0,
+            priority_expr: None,
             local_resources: LocalResources::new(),
             shared_resources: SharedResources::new(),
             local_task: false,
+            supervised: false,
+            spawn_wait: false,
+            core: None,
+            executor_section: None,
+            executor_section_unsafe: false,
         }
     }
 }
@@ -302,17 +469,30 @@ pub struct HardwareTask {
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct HardwareTaskArgs {
-    /// The interrupt or exception that this task is bound to
-    pub binds: Ident,
+    /// The interrupts or exceptions that this task is bound to, more than one with
+    /// `binds = [..]`
+    pub binds: Vec<Ident>,
 
     /// The priority of this task
     pub priority: u8,
 
+    /// The `priority` when given as a constant expression rather than a literal
+    ///
+    /// `priority` then holds a placeholder level that is unique to the expression, so that tasks
+    /// can still be grouped by priority before the expression is evaluated.
+    pub priority_expr: Option<Box<Expr>>,
+
     /// Local resources that can be accessed from this context
     pub local_resources: LocalResources,
 
     /// Shared resources that can be accessed from this context
     pub shared_resources: SharedResources,
+
+    /// Checks in with the watchdog supervisor through `cx.checkin()`
+    pub supervised: bool,
+
+    /// The hart this task runs on, in multi-hart apps
+    pub core: Option<LitInt>,
 }
 
 /// A `static mut` variable local to and owned by a context
@@ -330,6 +510,10 @@ pub struct Local {
 
     /// Initial value
     pub expr: Box<Expr>,
+
+    /// Marked `#[unsafe(loaded_section)]`: the user vouches that the runtime loads its
+    /// `#[link_section]` from flash
+    pub loaded_section: bool,
 }
 
 /// A wrapper of the 2 kinds of locals that tasks can have
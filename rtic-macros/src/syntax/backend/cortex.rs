@@ -1,16 +1,59 @@
 use syn::{
+    braced, bracketed,
     parse::{Parse, ParseStream},
-    Error, Result,
+    Error, Ident, Result, Token,
 };
 
-#[derive(Debug)]
-pub struct BackendArgs();
+/// TrustZone configuration of a secure app on the `thumbv8main-backend`
+#[derive(Debug, Default)]
+pub struct BackendArgs {
+    /// Interrupts targeting the non-secure state, `non_secure_interrupts = [..]`
+    pub non_secure_interrupts: Vec<Ident>,
+
+    /// Software tasks the non-secure state can spawn, `non_secure_callable = [..]`
+    pub non_secure_callable: Vec<Ident>,
+}
 
 impl Parse for BackendArgs {
     fn parse(input: ParseStream) -> Result<Self> {
-        Err(Error::new(
-            input.span(),
-            "cortex backend does not accept any arguments",
-        ))
+        let mut args = BackendArgs::default();
+
+        // { non_secure_interrupts = [..], non_secure_callable = [..] }
+        let content;
+        braced!(content in input);
+        let mut seen = vec![];
+        while !content.is_empty() {
+            let ident: Ident = content.parse()?;
+            if seen.contains(&ident) {
+                return Err(Error::new(ident.span(), "argument appears more than once"));
+            }
+            content.parse::<Token![=]>()?;
+
+            let list;
+            bracketed!(list in content);
+            let idents = list
+                .parse_terminated(Ident::parse, Token![,])?
+                .into_iter()
+                .collect();
+
+            match &*ident.to_string() {
+                "non_secure_interrupts" => args.non_secure_interrupts = idents,
+                "non_secure_callable" => args.non_secure_callable = idents,
+                _ => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "expected `non_secure_interrupts` or `non_secure_callable`",
+                    ))
+                }
+            }
+            seen.push(ident);
+
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+
+        Ok(args)
     }
 }
@@ -0,0 +1,18 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    Result,
+};
+
+#[derive(Debug)]
+pub struct BackendArgs {
+    /// The type implementing `rtic::Backend`
+    pub path: syn::Path,
+}
+
+impl Parse for BackendArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        Ok(BackendArgs {
+            path: input.parse()?,
+        })
+    }
+}
@@ -0,0 +1,69 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    Error, Ident, LitInt, Result, Token,
+};
+
+#[derive(Debug)]
+pub struct BackendArgs {
+    /// Base address of the PLIC
+    pub base: LitInt,
+
+    /// The PLIC context of the hart running the app, 0 (hart 0, machine mode) by default
+    pub context: Option<LitInt>,
+
+    /// Highest priority supported by the PLIC, 7 by default
+    pub max_priority: Option<LitInt>,
+}
+
+impl BackendArgs {
+    /// Highest priority supported by the PLIC
+    pub fn max_priority(&self) -> Result<u8> {
+        match &self.max_priority {
+            Some(max) => max.base10_parse(),
+            None => Ok(7),
+        }
+    }
+}
+
+impl Parse for BackendArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        // { base = 0x0c00_0000, context = 0, max_priority = 7 }
+        let content;
+        let braces = syn::braced!(content in input);
+
+        let mut base = None;
+        let mut context = None;
+        let mut max_priority = None;
+        while !content.is_empty() {
+            let ident: Ident = content.parse()?;
+            content.parse::<Token![=]>()?;
+            let lit: LitInt = content.parse()?;
+
+            let arg = match &*ident.to_string() {
+                "base" => &mut base,
+                "context" => &mut context,
+                "max_priority" => &mut max_priority,
+                _ => return Err(Error::new(ident.span(), "unexpected argument")),
+            };
+            if arg.is_some() {
+                return Err(Error::new(ident.span(), "argument appears more than once"));
+            }
+            *arg = Some(lit);
+
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+
+        let Some(base) = base else {
+            return Err(Error::new(braces.span.join(), "`base` is required"));
+        };
+
+        Ok(BackendArgs {
+            base,
+            context,
+            max_priority,
+        })
+    }
+}
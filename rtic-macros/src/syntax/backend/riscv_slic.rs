@@ -5,8 +5,27 @@ use syn::{
 
 #[derive(Debug)]
 pub struct BackendArgs {
+    /// The `Hart` of the device running the app, the first one of `harts` on multi-hart apps
     #[cfg(feature = "riscv-clint")]
     pub hart_id: syn::Ident,
+
+    /// The harts of a multi-hart app, `backend = { harts = [0, 1] }`; empty otherwise
+    #[cfg(feature = "riscv-clint")]
+    pub harts: Vec<Hart>,
+}
+
+/// A hart of a multi-hart app
+#[cfg(feature = "riscv-clint")]
+#[derive(Debug)]
+pub struct Hart {
+    /// The `mhartid` of the hart
+    pub id: u32,
+
+    /// The literal in `harts`, for error messages
+    pub lit: syn::LitInt,
+
+    /// The `Hart` of the device, `H<id>`
+    pub ident: syn::Ident,
 }
 
 impl Parse for BackendArgs {
@@ -14,8 +33,41 @@ impl Parse for BackendArgs {
         match () {
             #[cfg(feature = "riscv-clint")]
             () => {
-                let hart_id = input.parse()?;
-                Ok(BackendArgs { hart_id })
+                if !input.peek(syn::token::Brace) {
+                    let hart_id = input.parse()?;
+                    return Ok(BackendArgs {
+                        hart_id,
+                        harts: vec![],
+                    });
+                }
+
+                // { harts = [0, 1] }
+                let content;
+                syn::braced!(content in input);
+                let ident: syn::Ident = content.parse()?;
+                if ident != "harts" {
+                    return Err(syn::Error::new(ident.span(), "expected `harts`"));
+                }
+                content.parse::<syn::Token![=]>()?;
+
+                let list;
+                syn::bracketed!(list in content);
+                let mut harts = vec![];
+                for lit in list.parse_terminated(syn::LitInt::parse, syn::Token![,])? {
+                    let id = lit.base10_parse()?;
+                    let ident = syn::Ident::new(&format!("H{id}"), lit.span());
+                    harts.push(Hart { id, lit, ident });
+                }
+                let _ = content.parse::<Option<syn::Token![,]>>()?;
+
+                let Some(first) = harts.first() else {
+                    return Err(syn::Error::new(ident.span(), "`harts` must not be empty"));
+                };
+
+                Ok(BackendArgs {
+                    hart_id: first.ident.clone(),
+                    harts,
+                })
             }
             #[cfg(feature = "riscv-mecall")]
             () => Err(syn::Error::new(
@@ -1,9 +1,62 @@
 use std::collections::HashSet;
 
-use syn::parse;
+use syn::{parse, spanned::Spanned, Attribute, Expr, Lit, LitInt, LitStr, Meta};
 
 use crate::syntax::ast::App;
 
+/// The section of a `#[link_section = ".."]` or `#[unsafe(link_section = "..")]` attribute
+fn link_section(attrs: &[Attribute]) -> Option<LitStr> {
+    attrs.iter().find_map(|attr| {
+        let value = if attr.path().is_ident("link_section") {
+            match &attr.meta {
+                Meta::NameValue(nv) => nv.value.clone(),
+                _ => return None,
+            }
+        } else if attr.path().is_ident("unsafe") {
+            match attr.parse_args() {
+                Ok(Expr::Assign(assign)) if matches!(&*assign.left, Expr::Path(path) if path.path.is_ident("link_section")) => {
+                    *assign.right
+                }
+                _ => return None,
+            }
+        } else {
+            return None;
+        };
+
+        match value {
+            Expr::Lit(lit) => match lit.lit {
+                Lit::Str(section) => Some(section),
+                _ => None,
+            },
+            _ => None,
+        }
+    })
+}
+
+/// Whether `section` is `prefix` or one of its `prefix.*` subsections
+fn is_section(section: &LitStr, prefix: &str) -> bool {
+    let section = section.value();
+    section == prefix
+        || section
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Sections the runtime leaves as they were at reset
+fn is_uninit(section: &LitStr) -> bool {
+    is_section(section, ".uninit") || is_section(section, ".noinit")
+}
+
+/// Sections the runtime fills with zeroes
+fn is_zeroed(section: &LitStr) -> bool {
+    is_section(section, ".bss")
+}
+
+/// Sections the runtime loads from flash
+fn is_loaded(section: &LitStr) -> bool {
+    is_section(section, ".data")
+}
+
 pub fn app(app: &App) -> parse::Result<()> {
     // Check that all referenced resources have been declared
     // Check that resources are NOT `Exclusive`-ly shared
@@ -30,34 +83,187 @@ pub fn app(app: &App) -> parse::Result<()> {
         }
     }
 
-    // Check that no resource has both types of access (`Exclusive` & `Shared`)
-    let exclusive_accesses = app
-        .shared_resource_accesses()
-        .filter_map(|(priority, name, access)| {
-            if priority.is_some() && access.is_exclusive() {
-                Some(name)
-            } else {
-                None
+    // check that dispatchers are not used as hardware tasks
+    for task in app.hardware_tasks.values() {
+        for binds in &task.args.binds {
+            if app.args.dispatchers.contains_key(binds) {
+                return Err(parse::Error::new(
+                    binds.span(),
+                    "dispatcher interrupts can't be used as hardware tasks",
+                ));
             }
-        })
-        .collect::<HashSet<_>>();
-    for (_, name, access) in app.shared_resource_accesses() {
-        if access.is_shared() && exclusive_accesses.contains(name) {
+        }
+    }
+
+    // check that tasks are only pinned to harts in multi-hart apps
+    if app.args.harts().is_empty() {
+        let cores = app
+            .hardware_tasks
+            .values()
+            .map(|task| &task.args.core)
+            .chain(app.software_tasks.values().map(|task| &task.args.core));
+        if let Some(core) = cores.flatten().next() {
+            return Err(parse::Error::new(
+                core.span(),
+                "`core` requires a multi-hart app: `#[app(backend = { harts = [..] })]` on the `riscv-clint` backend",
+            ));
+        }
+    }
+
+    // check that resources used on several harts are not read with `&name` while others write
+    // them, the read ceiling only keeps out the writers on the hart of the reader
+    if let Some(&boot) = app.args.harts().first() {
+        let hart = |core: &Option<LitInt>| {
+            core.as_ref()
+                .and_then(|core| core.base10_parse().ok())
+                .unwrap_or(boot)
+        };
+        let accesses = app
+            .idle
+            .iter()
+            .map(|idle| (boot, &idle.args.shared_resources))
+            .chain(
+                app.hardware_tasks
+                    .values()
+                    .map(|task| (hart(&task.args.core), &task.args.shared_resources)),
+            )
+            .chain(
+                app.software_tasks
+                    .values()
+                    .map(|task| (hart(&task.args.core), &task.args.shared_resources)),
+            )
+            .flat_map(|(hart, resources)| {
+                resources
+                    .iter()
+                    .map(move |(name, access)| (hart, name, access))
+            })
+            .collect::<Vec<_>>();
+
+        for resource in app.shared_resources.keys() {
+            let accesses = accesses
+                .iter()
+                .filter(|(_, name, _)| *name == resource)
+                .collect::<Vec<_>>();
+            let harts = accesses
+                .iter()
+                .map(|(hart, _, _)| *hart)
+                .collect::<HashSet<_>>();
+            let written = accesses.iter().any(|(_, _, access)| access.is_exclusive());
+
+            if harts.len() > 1 && written {
+                if let Some((_, name, _)) =
+                    accesses.iter().find(|(_, _, access)| access.is_shared())
+                {
+                    return Err(parse::Error::new(
+                        name.span(),
+                        format!(
+                            "`&{name}` can't be used on a resource that is used on more than one hart and written by some tasks; lock it with `{name}` instead"
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    // check that `stack_guard` and `#[on_stack_overflow]` are used together
+    match (&app.args.stack_guard, &app.on_stack_overflow) {
+        (Some(bytes), None) => {
+            return Err(parse::Error::new(
+                bytes.span(),
+                "`stack_guard` requires an `#[on_stack_overflow]` function",
+            ));
+        }
+        (None, Some(hook)) => {
+            return Err(parse::Error::new(
+                hook.name.span(),
+                "`#[on_stack_overflow]` requires the `stack_guard` argument of `#[app]`",
+            ));
+        }
+        _ => {}
+    }
+
+    // check that `watchdog` supervises at least one task, and only tasks that always exist
+    let supervised = app
+        .hardware_tasks
+        .iter()
+        .filter(|(_, task)| task.args.supervised)
+        .map(|(name, task)| (name, &task.cfgs))
+        .chain(
+            app.software_tasks
+                .iter()
+                .filter(|(_, task)| task.args.supervised)
+                .map(|(name, task)| (name, &task.cfgs)),
+        )
+        .collect::<Vec<_>>();
+    if let Some((name, _)) = supervised.iter().find(|(_, cfgs)| !cfgs.is_empty()) {
+        return Err(parse::Error::new(
+            name.span(),
+            "`supervised` tasks can't have `#[cfg]` attributes",
+        ));
+    }
+    match (&app.args.watchdog, supervised.first(), &app.on_starvation) {
+        (Some(watchdog), None, _) => {
+            return Err(parse::Error::new(
+                watchdog.span(),
+                "`watchdog` requires at least one `supervised` task",
+            ));
+        }
+        (None, Some((name, _)), _) => {
             return Err(parse::Error::new(
                 name.span(),
-                "this implementation doesn't support shared (`&-`) - exclusive (`&mut-`) locks; use `x` instead of `&x`",
+                "`supervised` requires the `watchdog` argument of `#[app]`",
+            ));
+        }
+        (None, _, Some(hook)) => {
+            return Err(parse::Error::new(
+                hook.name.span(),
+                "`#[on_starvation]` requires the `watchdog` argument of `#[app]`",
             ));
         }
+        _ => {}
     }
 
-    // check that dispatchers are not used as hardware tasks
-    for task in app.hardware_tasks.values() {
-        let binds = &task.args.binds;
+    // check that storage RTIC expects to be initialized isn't placed where it won't be. Other
+    // sections than the ones of the runtime are only taken through an `unsafe` opt-in
+    for task in app.software_tasks.values() {
+        let Some(section) = &task.args.executor_section else {
+            continue;
+        };
+
+        if is_uninit(section) {
+            return Err(parse::Error::new(
+                section.span(),
+                "the executor of a task must start out zeroed, it can't be placed in a section left uninitialized",
+            ));
+        }
+
+        if !task.args.executor_section_unsafe && !is_zeroed(section) && !is_loaded(section) {
+            return Err(parse::Error::new(
+                section.span(),
+                "the executor of a task must start out zeroed, and the runtime only zeroes `.bss` and loads `.data`; use one of their subsections, or `executor_section = unsafe(\"..\")` if the runtime zeroes or loads this section",
+            ));
+        }
+    }
+    for (_, name, local) in app.declared_local_resources() {
+        let Some(section) = link_section(&local.attrs) else {
+            continue;
+        };
+
+        if is_uninit(&section) || is_zeroed(&section) {
+            return Err(parse::Error::new(
+                section.span(),
+                format!(
+                    "`{name}` is initialized with its expression by the runtime, it can't be placed in a section that is left uninitialized or zeroed; use a `#[local]` resource instead"
+                ),
+            ));
+        }
 
-        if app.args.dispatchers.contains_key(binds) {
+        if !local.loaded_section && !is_loaded(&section) {
             return Err(parse::Error::new(
-                binds.span(),
-                "dispatcher interrupts can't be used as hardware tasks",
+                section.span(),
+                format!(
+                    "`{name}` is initialized with its expression by the runtime, which only loads `.data`; use one of its subsections, or add `#[unsafe(loaded_section)]` if the runtime loads this section"
+                ),
             ));
         }
     }
@@ -1,17 +1,24 @@
 mod app;
+mod component;
 mod hardware_task;
 mod idle;
 mod init;
+mod instances;
+mod on_fault;
+mod on_stack_overflow;
+mod on_starvation;
 mod resource;
 mod software_task;
 mod util;
+mod watchdog;
 
 use proc_macro2::TokenStream as TokenStream2;
 use syn::{
-    braced,
+    braced, bracketed, parenthesized,
     parse::{self, Parse, ParseStream, Parser},
-    token::Brace,
-    Attribute, Ident, Item, LitBool, LitInt, Meta, Token,
+    punctuated::Punctuated,
+    token::{self, Brace},
+    Attribute, Expr, ExprLit, Ident, Item, Lit, LitBool, LitInt, LitStr, Meta, Token,
 };
 
 use crate::syntax::{
@@ -19,12 +26,69 @@ use crate::syntax::{
     Either,
 };
 
+pub(crate) use component::ComponentArgs;
+pub(crate) use watchdog::{checkins_ident, supervisor_ident};
+
 // Parse the app, both app arguments and body (input)
 pub fn app(args: TokenStream2, input: TokenStream2) -> parse::Result<App> {
-    let args = AppArgs::parse(args)?;
-    let input: Input = syn::parse2(input)?;
+    let mut args = AppArgs::parse(args)?;
+    let mut input: Input = syn::parse2(input)?;
+    component::merge(&mut args, &mut input)?;
+    instances::expand(&mut input.items)?;
+
+    let mut app = App::parse(args, input)?;
+    const_priority_levels(&mut app)?;
+    watchdog::supervisor(&mut app)?;
+
+    Ok(app)
+}
+
+// Give each distinct constant expression priority a level of its own, above all literal priorities
+//
+// The expressions are only evaluated by the compiler, so the analysis treats tasks with different
+// expressions as running at different priorities; ceilings are computed from the expressions.
+fn const_priority_levels(app: &mut App) -> parse::Result<()> {
+    let literal_max = app
+        .hardware_tasks
+        .values()
+        .filter(|task| task.args.priority_expr.is_none())
+        .map(|task| task.args.priority)
+        .chain(
+            app.software_tasks
+                .values()
+                .filter(|task| task.args.priority_expr.is_none())
+                .map(|task| task.args.priority),
+        )
+        .max()
+        .unwrap_or(0);
+
+    let mut levels: Vec<(String, u8)> = vec![];
+    let exprs = app
+        .hardware_tasks
+        .values_mut()
+        .map(|task| (&mut task.args.priority, &task.args.priority_expr))
+        .chain(
+            app.software_tasks
+                .values_mut()
+                .map(|task| (&mut task.args.priority, &task.args.priority_expr)),
+        );
+
+    for (priority, expr) in exprs {
+        let Some(expr) = expr else { continue };
+        let key = quote::quote!(#expr).to_string();
+
+        *priority = if let Some((_, level)) = levels.iter().find(|(k, _)| *k == key) {
+            *level
+        } else {
+            let level = literal_max
+                .checked_add(levels.len() as u8 + 1)
+                .ok_or_else(|| parse::Error::new_spanned(expr, "too many distinct priorities"))?;
+            levels.push((key, level));
+            level
+        };
+    }
 
-    App::parse(args, input)
+    Ok(())
 }
 
 pub(crate) struct Input {
@@ -95,6 +159,7 @@ fn init_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
 
                         local_resources = Some(util::parse_local_resources(input)?);
                     }
+
                     _ => {
                         return Err(parse::Error::new(ident.span(), "unexpected argument"));
                     }
@@ -194,10 +259,15 @@ fn task_args(tokens: TokenStream2) -> parse::Result<Either<HardwareTaskArgs, Sof
 
         let mut binds = None;
         let mut priority = None;
+        let mut priority_expr = None;
         let mut shared_resources = None;
         let mut local_resources = None;
         let mut prio_span = None;
         let mut local_task = None;
+        let mut supervised = None;
+        let mut spawn_wait = None;
+        let mut core = None;
+        let mut executor_section = None;
 
         loop {
             if input.is_empty() {
@@ -223,6 +293,36 @@ fn task_args(tokens: TokenStream2) -> parse::Result<Either<HardwareTaskArgs, Sof
                 } else {
                     local_task = Some(true); // Default to true
                 }
+            } else if &*ident_s == "supervised" {
+                if supervised.is_some() {
+                    return Err(parse::Error::new(
+                        ident.span(),
+                        "argument appears more than once",
+                    ));
+                }
+
+                // Same as `local_task`, the value is optional
+                if input.parse::<Token![=]>().is_ok() {
+                    let lit: LitBool = input.parse()?;
+                    supervised = Some(lit.value);
+                } else {
+                    supervised = Some(true);
+                }
+            } else if &*ident_s == "spawn_wait" {
+                if spawn_wait.is_some() {
+                    return Err(parse::Error::new(
+                        ident.span(),
+                        "argument appears more than once",
+                    ));
+                }
+
+                // Same as `local_task`, the value is optional
+                if input.parse::<Token![=]>().is_ok() {
+                    let lit: LitBool = input.parse()?;
+                    spawn_wait = Some((lit.value, ident.span()));
+                } else {
+                    spawn_wait = Some((true, ident.span()));
+                }
             } else {
                 // The equal sign is mandatory.
                 input.parse::<Token![=]>()?;
@@ -236,62 +336,123 @@ fn task_args(tokens: TokenStream2) -> parse::Result<Either<HardwareTaskArgs, Sof
                             ));
                         }
 
-                        // Parse identifier name
-                        let ident = input.parse()?;
-
-                        binds = Some(ident);
+                        // Either a single vector or a list of them sharing the task
+                        let vectors = if input.peek(token::Bracket) {
+                            let content;
+                            bracketed!(content in input);
+                            let vectors =
+                                Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                            if vectors.is_empty() {
+                                return Err(parse::Error::new(
+                                    ident.span(),
+                                    "a hardware task must be bound to at least one vector",
+                                ));
+                            }
+
+                            vectors.into_iter().collect()
+                        } else {
+                            vec![input.parse()?]
+                        };
+
+                        binds = Some(vectors);
                     }
 
                     "priority" => {
-                        if priority.is_some() {
+                        if priority.is_some() || priority_expr.is_some() {
                             return Err(parse::Error::new(
                                 ident.span(),
                                 "argument appears more than once",
                             ));
                         }
 
-                        // #lit
-                        let lit: LitInt = input.parse()?;
+                        match input.parse::<Expr>()? {
+                            // #lit
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Int(lit), ..
+                            }) => {
+                                if !lit.suffix().is_empty() {
+                                    return Err(parse::Error::new(
+                                        lit.span(),
+                                        "this literal must be unsuffixed",
+                                    ));
+                                }
+
+                                let value = lit.base10_parse::<u8>().ok();
+                                if value.is_none() {
+                                    return Err(parse::Error::new(
+                                        lit.span(),
+                                        "this literal must be in the range 0...255",
+                                    ));
+                                }
+
+                                prio_span = Some(lit.span());
+                                priority = Some(value.unwrap());
+                            }
+
+                            // A constant expression, evaluated by the compiler
+                            expr => priority_expr = Some(Box::new(expr)),
+                        }
+                    }
 
-                        if !lit.suffix().is_empty() {
+                    "shared" => {
+                        if shared_resources.is_some() {
                             return Err(parse::Error::new(
-                                lit.span(),
-                                "this literal must be unsuffixed",
+                                ident.span(),
+                                "argument appears more than once",
                             ));
                         }
 
-                        let value = lit.base10_parse::<u8>().ok();
-                        if value.is_none() {
+                        shared_resources = Some(util::parse_shared_resources(input)?);
+                    }
+
+                    "local" => {
+                        if local_resources.is_some() {
                             return Err(parse::Error::new(
-                                lit.span(),
-                                "this literal must be in the range 0...255",
+                                ident.span(),
+                                "argument appears more than once",
                             ));
                         }
 
-                        prio_span = Some(lit.span());
-                        priority = Some(value.unwrap());
+                        local_resources = Some(util::parse_local_resources(input)?);
                     }
 
-                    "shared" => {
-                        if shared_resources.is_some() {
+                    "core" => {
+                        if core.is_some() {
                             return Err(parse::Error::new(
                                 ident.span(),
                                 "argument appears more than once",
                             ));
                         }
 
-                        shared_resources = Some(util::parse_shared_resources(input)?);
+                        // #lit
+                        let lit: LitInt = input.parse()?;
+                        if !lit.suffix().is_empty() || lit.base10_parse::<u32>().is_err() {
+                            return Err(parse::Error::new(
+                                lit.span(),
+                                "this literal must be an unsuffixed hart id",
+                            ));
+                        }
+
+                        core = Some(lit);
                     }
 
-                    "local" => {
-                        if local_resources.is_some() {
+                    "executor_section" => {
+                        if executor_section.is_some() {
                             return Err(parse::Error::new(
                                 ident.span(),
                                 "argument appears more than once",
                             ));
                         }
 
-                        local_resources = Some(util::parse_local_resources(input)?);
+                        // `unsafe("..")` opts out of the check of the section
+                        if input.peek(Token![unsafe]) {
+                            input.parse::<Token![unsafe]>()?;
+                            let content;
+                            parenthesized!(content in input);
+                            executor_section = Some((content.parse::<LitStr>()?, true));
+                        } else {
+                            executor_section = Some((input.parse::<LitStr>()?, false));
+                        }
                     }
 
                     _ => {
@@ -311,8 +472,33 @@ fn task_args(tokens: TokenStream2) -> parse::Result<Either<HardwareTaskArgs, Sof
         let shared_resources = shared_resources.unwrap_or_default();
         let local_resources = local_resources.unwrap_or_default();
         let local_task = local_task.unwrap_or(false);
+        let supervised = supervised.unwrap_or(false);
+
+        if let Some((true, span)) = spawn_wait {
+            if binds.is_some() {
+                return Err(parse::Error::new(
+                    span,
+                    "hardware tasks can't be spawned, `spawn_wait` only applies to software tasks",
+                ));
+            }
+
+            if local_task {
+                return Err(parse::Error::new(
+                    span,
+                    "local tasks can't be spawned with `spawn_wait`",
+                ));
+            }
+        }
+        let spawn_wait = spawn_wait.is_some_and(|(spawn_wait, _)| spawn_wait);
 
         Ok(if let Some(binds) = binds {
+            if let Some((section, _)) = executor_section {
+                return Err(parse::Error::new(
+                    section.span(),
+                    "hardware tasks have no executor, `executor_section` only applies to software tasks",
+                ));
+            }
+
             // Hardware tasks can't run at anything lower than 1
             let priority = priority.unwrap_or(1);
 
@@ -326,8 +512,11 @@ fn task_args(tokens: TokenStream2) -> parse::Result<Either<HardwareTaskArgs, Sof
             Either::Left(HardwareTaskArgs {
                 binds,
                 priority,
+                priority_expr,
                 shared_resources,
                 local_resources,
+                supervised,
+                core,
             })
         } else {
             // Software tasks start at idle priority
@@ -335,9 +524,15 @@ fn task_args(tokens: TokenStream2) -> parse::Result<Either<HardwareTaskArgs, Sof
 
             Either::Right(SoftwareTaskArgs {
                 priority,
+                priority_expr,
                 shared_resources,
                 local_resources,
                 local_task,
+                supervised,
+                spawn_wait,
+                core,
+                executor_section_unsafe: executor_section.as_ref().is_some_and(|(_, unsafety)| *unsafety),
+                executor_section: executor_section.map(|(section, _)| section),
             })
         })
     })
@@ -3,15 +3,17 @@ use std::collections::HashSet;
 // use indexmap::map::Entry;
 use proc_macro2::TokenStream as TokenStream2;
 use syn::{
+    braced,
     parse::{self, ParseStream, Parser},
     spanned::Spanned,
-    Expr, ExprArray, Fields, ForeignItem, Ident, Item, LitBool, Path, Token, Visibility,
+    Expr, ExprArray, Fields, ForeignItem, Ident, Item, LitBool, LitInt, Path, Token, Visibility,
 };
 
 use crate::syntax::{
     ast::{
-        App, AppArgs, Dispatcher, Dispatchers, HardwareTask, Idle, IdleArgs, Init, InitArgs,
-        LocalResource, SharedResource, SoftwareTask,
+        App, AppArgs, Component, Dispatcher, Dispatchers, HardwareTask, Idle, IdleArgs, Init,
+        InitArgs, LocalResource, OnFault, OnStackOverflow, OnStarvation, SharedResource,
+        SoftwareTask,
     },
     backend::BackendArgs,
     parse::{self as syntax_parse, util},
@@ -26,9 +28,19 @@ impl AppArgs {
             let mut custom = Set::new();
             let mut device = None;
             let mut core = true;
-            let mut peripherals = true;
+            let mut peripherals = None;
+            let mut interrupts = None;
+            let mut prio_bits = None;
             let mut dispatchers = Dispatchers::new();
+            let mut stack_usage = false;
+            let mut fair_dispatch = None;
+            let mut stack_guard = None;
+            let mut mpu = false;
+            let mut watchdog = None;
+            let mut timeout = None;
             let mut backend = None;
+            let mut components = vec![];
+            let mut collected_components = None;
 
             loop {
                 if input.is_empty() {
@@ -62,6 +74,34 @@ impl AppArgs {
                         }
                     }
 
+                    "interrupts" => {
+                        if let Ok(p) = input.parse::<Path>() {
+                            interrupts = Some(p);
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be a path",
+                            ));
+                        }
+                    }
+
+                    "prio_bits" => {
+                        if let Ok(p) = input.parse::<LitInt>() {
+                            if !(1..=8).contains(&p.base10_parse::<u8>()?) {
+                                return Err(parse::Error::new(
+                                    p.span(),
+                                    "the number of priority bits must be in the range 1..=8",
+                                ));
+                            }
+                            prio_bits = Some(p);
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be an integer",
+                            ));
+                        }
+                    }
+
                     "core" => {
                         if let Ok(p) = input.parse::<LitBool>() {
                             core = p.value;
@@ -75,7 +115,7 @@ impl AppArgs {
 
                     "peripherals" => {
                         if let Ok(p) = input.parse::<LitBool>() {
-                            peripherals = p.value;
+                            peripherals = Some(p);
                         } else {
                             return Err(parse::Error::new(
                                 ident.span(),
@@ -85,38 +125,87 @@ impl AppArgs {
                     }
 
                     "dispatchers" => {
+                        if let Ok(p) = input.parse::<ExprArray>() {
+                            parse_dispatchers(p, &mut dispatchers)?;
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                // increasing the length of the error message will break rustfmt
+                                "unexpected argument value; expected an array",
+                            ));
+                        }
+                    }
+
+                    "stack_usage" => {
+                        if let Ok(p) = input.parse::<LitBool>() {
+                            stack_usage = p.value;
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be a boolean",
+                            ));
+                        }
+                    }
+
+                    "fair_dispatch" => {
+                        if let Ok(p) = input.parse::<LitBool>() {
+                            fair_dispatch = Some(p);
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be a boolean",
+                            ));
+                        }
+                    }
+
+                    "stack_guard" => {
+                        if let Ok(p) = input.parse::<LitInt>() {
+                            p.base10_parse::<u32>()?;
+                            stack_guard = Some(p);
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be an integer",
+                            ));
+                        }
+                    }
+
+                    "mpu" => {
+                        if let Ok(p) = input.parse::<LitBool>() {
+                            mpu = p.value;
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be a boolean",
+                            ));
+                        }
+                    }
+
+                    "watchdog" => {
+                        if let Ok(p) = input.parse::<Path>() {
+                            watchdog = Some(p);
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; this should be a path",
+                            ));
+                        }
+                    }
+
+                    "timeout" => {
+                        timeout = Some((ident.clone(), Box::new(input.parse::<Expr>()?)));
+                    }
+
+                    "components" => {
                         if let Ok(p) = input.parse::<ExprArray>() {
                             for e in p.elems {
-                                match e {
-                                    Expr::Path(ep) => {
-                                        let path = ep.path;
-                                        let ident = if path.leading_colon.is_some()
-                                            || path.segments.len() != 1
-                                        {
-                                            return Err(parse::Error::new(
-                                                path.span(),
-                                                "interrupt must be an identifier, not a path",
-                                            ));
-                                        } else {
-                                            path.segments[0].ident.clone()
-                                        };
-                                        let span = ident.span();
-                                        if dispatchers.contains_key(&ident) {
-                                            return Err(parse::Error::new(
-                                                span,
-                                                "this extern interrupt is listed more than once",
-                                            ));
-                                        } else {
-                                            dispatchers
-                                                .insert(ident, Dispatcher { attrs: ep.attrs });
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(parse::Error::new(
-                                            e.span(),
-                                            "interrupt must be an identifier",
-                                        ));
-                                    }
+                                if let Expr::Path(ep) = e {
+                                    components.push(ep.path);
+                                } else {
+                                    return Err(parse::Error::new(
+                                        e.span(),
+                                        "component must be a path to a `#[rtic::component]` module",
+                                    ));
                                 }
                             }
                         } else {
@@ -128,6 +217,18 @@ impl AppArgs {
                         }
                     }
 
+                    // Inserted by the `#[rtic::component]` macros once all components are expanded
+                    "__rtic_components" => {
+                        let content;
+                        braced!(content in input);
+
+                        let mut components = vec![];
+                        while !content.is_empty() {
+                            components.push(content.parse::<Component>()?);
+                        }
+                        collected_components = Some(components);
+                    }
+
                     "backend" => {
                         if let Ok(p) = input.parse::<BackendArgs>() {
                             backend = Some(p);
@@ -152,24 +253,106 @@ impl AppArgs {
                 let _: Token![,] = input.parse()?;
             }
 
-            let device = if let Some(device) = device {
-                device
-            } else {
-                return Err(parse::Error::new(input.span(), "missing `device = ...`"));
+            // Device peripherals are only available from a device crate
+            let peripherals = match peripherals {
+                Some(p) if p.value && device.is_none() => {
+                    return Err(parse::Error::new(
+                        p.span(),
+                        "device peripherals require `device = ...`",
+                    ));
+                }
+                Some(p) => p.value,
+                None => device.is_some(),
+            };
+
+            // The supervisor needs both the watchdog and the window of the tasks
+            let timeout = match (&watchdog, timeout) {
+                (Some(_), Some((_, timeout))) => Some(timeout),
+                (Some(watchdog), None) => {
+                    return Err(parse::Error::new(
+                        watchdog.span(),
+                        "`watchdog` requires a `timeout`",
+                    ));
+                }
+                (None, Some((ident, _))) => {
+                    return Err(parse::Error::new(
+                        ident.span(),
+                        "`timeout` requires a `watchdog`",
+                    ));
+                }
+                (None, None) => None,
             };
 
-            Ok(AppArgs {
+            let args = AppArgs {
                 device,
+                interrupts,
+                prio_bits,
                 core,
                 peripherals,
                 dispatchers,
+                fair_dispatch: fair_dispatch.as_ref().is_some_and(|p| p.value),
+                stack_usage,
+                stack_guard,
+                mpu,
+                watchdog,
+                timeout,
                 backend,
-            })
+                components,
+                collected_components,
+            };
+
+            // Each hart runs the dispatchers, they would race on the start of the rounds
+            if let Some(p) = fair_dispatch.filter(|p| p.value) {
+                if args.harts().len() > 1 {
+                    return Err(parse::Error::new(
+                        p.span(),
+                        "`fair_dispatch` is not supported in multi-hart apps",
+                    ));
+                }
+            }
+
+            Ok(args)
         })
         .parse2(tokens)
     }
 }
 
+// Interrupts listed in `dispatchers = [..]`, of the `#[app]` or of a component
+pub(crate) fn parse_dispatchers(p: ExprArray, dispatchers: &mut Dispatchers) -> parse::Result<()> {
+    for e in p.elems {
+        match e {
+            Expr::Path(ep) => {
+                let path = ep.path;
+                let ident = if path.leading_colon.is_some() || path.segments.len() != 1 {
+                    return Err(parse::Error::new(
+                        path.span(),
+                        "interrupt must be an identifier, not a path",
+                    ));
+                } else {
+                    path.segments[0].ident.clone()
+                };
+                let span = ident.span();
+                if dispatchers.contains_key(&ident) {
+                    return Err(parse::Error::new(
+                        span,
+                        "this extern interrupt is listed more than once",
+                    ));
+                } else {
+                    dispatchers.insert(ident, Dispatcher { attrs: ep.attrs });
+                }
+            }
+            _ => {
+                return Err(parse::Error::new(
+                    e.span(),
+                    "interrupt must be an identifier",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl App {
     pub(crate) fn parse(args: AppArgs, input: Input) -> parse::Result<Self> {
         let mut init = None;
@@ -185,20 +368,25 @@ impl App {
 
         let mut hardware_tasks = Map::new();
         let mut software_tasks = Map::new();
+        let mut on_stack_overflow = None;
+        let mut on_fault = None;
+        let mut on_starvation = None;
         let mut user_imports = vec![];
         let mut user_code = vec![];
 
         let mut seen_idents = HashSet::<Ident>::new();
         let mut bindings = HashSet::<Ident>::new();
 
-        let mut check_binding = |ident: &Ident| {
-            if bindings.contains(ident) {
-                return Err(parse::Error::new(
-                    ident.span(),
-                    "this interrupt is already bound",
-                ));
-            } else {
-                bindings.insert(ident.clone());
+        let mut check_binding = |idents: &[Ident]| {
+            for ident in idents {
+                if bindings.contains(ident) {
+                    return Err(parse::Error::new(
+                        ident.span(),
+                        "this interrupt is already bound",
+                    ));
+                } else {
+                    bindings.insert(ident.clone());
+                }
             }
 
             Ok(())
@@ -261,6 +449,57 @@ impl App {
                         check_ident(&item.sig.ident)?;
 
                         idle = Some(Idle::parse(args, item)?);
+                    } else if let Some(pos) = item
+                        .attrs
+                        .iter()
+                        .position(|attr| util::attr_eq(attr, "on_stack_overflow"))
+                    {
+                        item.attrs.remove(pos);
+
+                        if on_stack_overflow.is_some() {
+                            return Err(parse::Error::new(
+                                span,
+                                "`#[on_stack_overflow]` function must appear at most once",
+                            ));
+                        }
+
+                        check_ident(&item.sig.ident)?;
+
+                        on_stack_overflow = Some(OnStackOverflow::parse(item)?);
+                    } else if let Some(pos) = item
+                        .attrs
+                        .iter()
+                        .position(|attr| util::attr_eq(attr, "on_fault"))
+                    {
+                        item.attrs.remove(pos);
+
+                        if on_fault.is_some() {
+                            return Err(parse::Error::new(
+                                span,
+                                "`#[on_fault]` function must appear at most once",
+                            ));
+                        }
+
+                        check_ident(&item.sig.ident)?;
+
+                        on_fault = Some(OnFault::parse(item)?);
+                    } else if let Some(pos) = item
+                        .attrs
+                        .iter()
+                        .position(|attr| util::attr_eq(attr, "on_starvation"))
+                    {
+                        item.attrs.remove(pos);
+
+                        if on_starvation.is_some() {
+                            return Err(parse::Error::new(
+                                span,
+                                "`#[on_starvation]` function must appear at most once",
+                            ));
+                        }
+
+                        check_ident(&item.sig.ident)?;
+
+                        on_starvation = Some(OnStarvation::parse(item)?);
                     } else if let Some(pos) = item
                         .attrs
                         .iter()
@@ -544,6 +783,9 @@ impl App {
             user_code,
             hardware_tasks,
             software_tasks,
+            on_stack_overflow,
+            on_fault,
+            on_starvation,
         })
     }
 }
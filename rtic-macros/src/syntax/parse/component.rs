@@ -0,0 +1,288 @@
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+    braced, parenthesized,
+    parse::{self, Parse, ParseStream, Parser},
+    spanned::Spanned,
+    Attribute, ExprArray, Fields, ForeignItem, Ident, Item, Meta, Token,
+};
+
+use crate::syntax::{
+    ast::{AppArgs, Component, Dispatchers, TaskLocal},
+    parse::util,
+    Either,
+};
+
+use super::{app::parse_dispatchers, task_args, Input};
+
+/// The arguments of the `#[rtic::component]` attribute
+pub(crate) struct ComponentArgs {
+    pub dispatchers: Dispatchers,
+}
+
+impl ComponentArgs {
+    pub(crate) fn parse(tokens: TokenStream2) -> parse::Result<Self> {
+        (|input: ParseStream<'_>| -> parse::Result<Self> {
+            let mut dispatchers = None;
+
+            loop {
+                if input.is_empty() {
+                    break;
+                }
+
+                // #ident = ..
+                let ident: Ident = input.parse()?;
+                let _eq_token: Token![=] = input.parse()?;
+
+                match &*ident.to_string() {
+                    "dispatchers" => {
+                        if dispatchers.is_some() {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "argument appears more than once",
+                            ));
+                        }
+
+                        let mut list = Dispatchers::new();
+                        if let Ok(p) = input.parse::<ExprArray>() {
+                            parse_dispatchers(p, &mut list)?;
+                        } else {
+                            return Err(parse::Error::new(
+                                ident.span(),
+                                "unexpected argument value; expected an array",
+                            ));
+                        }
+                        dispatchers = Some(list);
+                    }
+
+                    _ => {
+                        return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    }
+                }
+
+                if input.is_empty() {
+                    break;
+                }
+
+                // ,
+                let _: Token![,] = input.parse()?;
+            }
+
+            Ok(ComponentArgs {
+                dispatchers: dispatchers.unwrap_or_default(),
+            })
+        })
+        .parse2(tokens)
+    }
+}
+
+// A collected component, `name (args) { items }`
+impl Parse for Component {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let name = input.parse()?;
+
+        let args;
+        parenthesized!(args in input);
+        let args = ComponentArgs::parse(args.parse()?)?;
+
+        let content;
+        braced!(content in input);
+        let mut items = vec![];
+        while !content.is_empty() {
+            items.push(content.parse()?);
+        }
+
+        Ok(Component {
+            name,
+            dispatchers: args.dispatchers,
+            items,
+        })
+    }
+}
+
+// Merge the collected components into the `#[app]` module
+//
+// The fields of a component's `#[shared]` and `#[local]` structs are appended to the ones of the
+// app, everything else becomes an item of the app module. Conflicting names are reported by the
+// app parser, with the spans of the component.
+pub(crate) fn merge(args: &mut AppArgs, input: &mut Input) -> parse::Result<()> {
+    let Some(components) = args.collected_components.take() else {
+        return Ok(());
+    };
+
+    for component in components {
+        let shared = declared_resources(&component.items, "shared");
+        let local = declared_resources(&component.items, "local");
+
+        for (ident, dispatcher) in component.dispatchers {
+            if args.dispatchers.contains_key(&ident) {
+                return Err(parse::Error::new(
+                    ident.span(),
+                    "this extern interrupt is listed more than once",
+                ));
+            }
+            args.dispatchers.insert(ident, dispatcher);
+        }
+
+        for item in component.items {
+            check_item(&component.name, &item)?;
+            check_resources(&component.name, &item, &shared, &local)?;
+
+            let kind = match &item {
+                Item::Struct(s) if s.attrs.iter().any(|attr| util::attr_eq(attr, "shared")) => {
+                    "shared"
+                }
+                Item::Struct(s) if s.attrs.iter().any(|attr| util::attr_eq(attr, "local")) => {
+                    "local"
+                }
+                // The same import in the app and a component is only kept once
+                Item::Use(u)
+                    if input
+                        .items
+                        .iter()
+                        .any(|i| matches!(i, Item::Use(a) if a == u)) =>
+                {
+                    continue;
+                }
+                _ => {
+                    input.items.push(item);
+                    continue;
+                }
+            };
+
+            let Item::Struct(s) = item else {
+                unreachable!()
+            };
+            let span = s.span();
+            let Fields::Named(fields) = s.fields else {
+                return Err(parse::Error::new(
+                    span,
+                    "this `struct` must have named fields",
+                ));
+            };
+
+            let app_struct = input.items.iter_mut().find_map(|item| match item {
+                Item::Struct(s) if s.attrs.iter().any(|attr| util::attr_eq(attr, kind)) => Some(s),
+                _ => None,
+            });
+
+            match app_struct {
+                Some(syn::ItemStruct {
+                    fields: Fields::Named(app_fields),
+                    ..
+                }) => app_fields.named.extend(fields.named),
+                // Let the app parser report a malformed struct
+                Some(_) => {}
+                None => {
+                    return Err(parse::Error::new(
+                        s.ident.span(),
+                        format!("the app has no `#[{kind}]` struct to add these resources to"),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The functions of `item`, with their attributes
+fn functions(item: &Item) -> Vec<(&Ident, &Vec<Attribute>)> {
+    match item {
+        Item::Fn(item) => vec![(&item.sig.ident, &item.attrs)],
+        Item::ForeignMod(mod_) => mod_
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ForeignItem::Fn(item) => Some((&item.sig.ident, &item.attrs)),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// Components provide tasks and resources, the entry points belong to the app
+fn check_item(component: &Ident, item: &Item) -> parse::Result<()> {
+    for (ident, attrs) in functions(item) {
+        for name in [
+            "init",
+            "idle",
+            "on_stack_overflow",
+            "on_fault",
+            "on_starvation",
+        ] {
+            if attrs.iter().any(|attr| util::attr_eq(attr, name)) {
+                return Err(parse::Error::new(
+                    ident.span(),
+                    format!("`#[{name}]` can't be defined in component `{component}`"),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The fields of the `#[shared]` or `#[local]` struct of a component
+fn declared_resources(items: &[Item], kind: &str) -> Vec<Ident> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Struct(s) if s.attrs.iter().any(|attr| util::attr_eq(attr, kind)) => Some(s),
+            _ => None,
+        })
+        .flat_map(|s| s.fields.iter().filter_map(|field| field.ident.clone()))
+        .collect()
+}
+
+// The arguments of `#[task(..)]`, none for a bare `#[task]`
+fn task_attr_args(attr: &Attribute) -> parse::Result<TokenStream2> {
+    match &attr.meta {
+        Meta::Path(_) => Ok(TokenStream2::new()),
+        _ => attr.parse_args(),
+    }
+}
+
+// The tasks of a component only use the resources the component declares, the ones of the app and
+// of other components are not part of its interface
+fn check_resources(
+    component: &Ident,
+    item: &Item,
+    shared: &[Ident],
+    local: &[Ident],
+) -> parse::Result<()> {
+    for (_, attrs) in functions(item) {
+        let Some(attr) = attrs.iter().find(|attr| util::attr_eq(attr, "task")) else {
+            continue;
+        };
+
+        let (shared_resources, local_resources) = match task_args(task_attr_args(attr)?)? {
+            Either::Left(args) => (args.shared_resources, args.local_resources),
+            Either::Right(args) => (args.shared_resources, args.local_resources),
+        };
+
+        let undeclared = shared_resources
+            .keys()
+            .filter(|name| !shared.contains(name))
+            .map(|name| (name, "shared"))
+            .chain(
+                local_resources
+                    .iter()
+                    .filter(|(name, task_local)| {
+                        matches!(task_local, TaskLocal::External) && !local.contains(name)
+                    })
+                    .map(|(name, _)| (name, "local")),
+            )
+            .next();
+        if let Some((name, kind)) = undeclared {
+            return Err(parse::Error::new(
+                name.span(),
+                format!(
+                    "component `{component}` doesn't declare the {kind} resource `{name}`; its tasks can only use the resources of its own `#[{kind}]` struct"
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
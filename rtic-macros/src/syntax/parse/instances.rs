@@ -0,0 +1,319 @@
+use proc_macro2::{Group, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{self, Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+    Attribute, Expr, GenericArgument, GenericParam, Ident, Item, ItemFn, Macro, Meta, Path,
+    PathArguments, PathSegment, Token, Type,
+};
+
+use crate::syntax::parse::util;
+
+// Expand `#[task(instances = [a<A>, b<B>])] async fn task<T: Bound>(..)` into one task per instance
+//
+// Each instance is a copy of the generic task, named after the instance, in which the generic
+// parameters refer to type aliases (or constants) of the instance's arguments and `task::` paths
+// refer to the instance's module. The bounds of the generic parameters are checked for every
+// instance in a separate `const` item.
+pub(crate) fn expand(items: &mut Vec<Item>) -> parse::Result<()> {
+    let mut expanded = Vec::with_capacity(items.len());
+
+    for item in items.drain(..) {
+        match item {
+            Item::Fn(item) if item.attrs.iter().any(|attr| util::attr_eq(attr, "task")) => {
+                expanded.extend(instances(item)?);
+            }
+            item => expanded.push(item),
+        }
+    }
+
+    *items = expanded;
+
+    Ok(())
+}
+
+fn instances(mut item: ItemFn) -> parse::Result<Vec<Item>> {
+    let pos = item
+        .attrs
+        .iter()
+        .position(|attr| util::attr_eq(attr, "task"))
+        .expect("UNREACHABLE");
+    let Meta::List(list) = &item.attrs[pos].meta else {
+        return Ok(vec![Item::Fn(item)]);
+    };
+
+    // Split the arguments of `#[task(..)]` and take out `instances = [..]`
+    let mut args: Vec<Vec<TokenTree>> = vec![vec![]];
+    for tt in list.tokens.clone() {
+        match tt {
+            TokenTree::Punct(p) if p.as_char() == ',' => args.push(vec![]),
+            tt => args.last_mut().unwrap().push(tt),
+        }
+    }
+    args.retain(|arg| !arg.is_empty());
+
+    let is_arg = |arg: &[TokenTree], name: &str| match arg.first() {
+        Some(TokenTree::Ident(ident)) => ident == name,
+        _ => false,
+    };
+    let Some(instances_pos) = args.iter().position(|arg| is_arg(arg, "instances")) else {
+        return Ok(vec![Item::Fn(item)]);
+    };
+    let instances_arg = args.remove(instances_pos);
+
+    if let Some(binds) = args.iter().find(|arg| is_arg(arg, "binds")) {
+        return Err(parse::Error::new(
+            binds[0].span(),
+            "`instances` can only be used on software tasks",
+        ));
+    }
+
+    let instances = match &instances_arg[1..] {
+        [TokenTree::Punct(eq), TokenTree::Group(group)] if eq.as_char() == '=' => {
+            Punctuated::<PathSegment, Token![,]>::parse_terminated.parse2(group.stream())?
+        }
+        _ => {
+            return Err(parse::Error::new(
+                instances_arg[0].span(),
+                "unexpected argument value; expected an array",
+            ))
+        }
+    };
+
+    let generics = std::mem::take(&mut item.sig.generics);
+    if generics.params.is_empty() {
+        return Err(parse::Error::new(
+            item.sig.ident.span(),
+            "`instances` requires a task with generic parameters",
+        ));
+    }
+    for param in &generics.params {
+        if let GenericParam::Lifetime(lt) = param {
+            return Err(parse::Error::new(
+                lt.span(),
+                "tasks with `instances` can't have lifetime parameters",
+            ));
+        }
+    }
+
+    let task = item.sig.ident.clone();
+    let params = &generics.params;
+    let where_clause = &generics.where_clause;
+    item.attrs.remove(pos);
+
+    let mut items = vec![];
+    for instance in &instances {
+        let name = &instance.ident;
+        let arguments = match &instance.arguments {
+            PathArguments::AngleBracketed(a) => a.args.iter().collect(),
+            _ => vec![],
+        };
+
+        if arguments.len() != params.len() {
+            return Err(parse::Error::new_spanned(
+                instance,
+                format!(
+                    "expected {} generic argument(s), one for each parameter of `{task}`",
+                    params.len()
+                ),
+            ));
+        }
+
+        // One alias per generic parameter, named after the instance
+        let mut substitutions = vec![];
+        for (param, arg) in params.iter().zip(&arguments) {
+            match (param, arg) {
+                (GenericParam::Type(param), GenericArgument::Type(ty)) => {
+                    let alias = format_ident!(
+                        "__rtic_internal_{}_{}",
+                        name,
+                        param.ident,
+                        span = param.ident.span()
+                    );
+                    items.push(Item::Verbatim(quote!(
+                        #[allow(non_camel_case_types)]
+                        type #alias = #ty;
+                    )));
+                    substitutions.push((param.ident.clone(), alias));
+                }
+                (
+                    GenericParam::Const(param),
+                    GenericArgument::Const(_) | GenericArgument::Type(_),
+                ) => {
+                    let alias = format_ident!(
+                        "__rtic_internal_{}_{}",
+                        name,
+                        param.ident,
+                        span = param.ident.span()
+                    );
+                    let ty = &param.ty;
+                    items.push(Item::Verbatim(quote!(
+                        #[allow(non_upper_case_globals)]
+                        const #alias: #ty = #arg;
+                    )));
+                    substitutions.push((param.ident.clone(), alias));
+                }
+                _ => {
+                    return Err(parse::Error::new(
+                        arg.span(),
+                        "this argument doesn't match the kind of the generic parameter",
+                    ))
+                }
+            }
+        }
+
+        let mut substitute = Substitute {
+            substitutions: &substitutions,
+            task: &task,
+            instance: name,
+        };
+
+        let task_args = args
+            .iter()
+            .map(|arg| substitute.task_arg(arg))
+            .collect::<parse::Result<Vec<_>>>()?;
+
+        let mut item = item.clone();
+        substitute.visit_item_fn_mut(&mut item);
+        item.sig.ident = name.clone();
+        item.attrs
+            .insert(pos, syn::parse_quote!(#[task(#(#task_args),*)]));
+        items.push(Item::Fn(item));
+
+        // Check the bounds of the generic parameters
+        items.push(Item::Verbatim(quote!(
+            const _: () = {
+                fn #task<#params>() #where_clause {}
+                let _ = #task::<#(#arguments),*>;
+            };
+        )));
+    }
+
+    Ok(items)
+}
+
+// Replaces the generic parameters by the aliases of the instance, and the first segment of
+// `task::` paths by the name of the instance
+//
+// Only paths are rewritten, so bindings, fields and methods named like the task or a parameter are
+// kept. Nested items are skipped, as the generic parameters are not in scope there. The arguments
+// of macros are rewritten if they parse as comma separated expressions, e.g. `hprintln!`.
+struct Substitute<'a> {
+    substitutions: &'a [(Ident, Ident)],
+    task: &'a Ident,
+    instance: &'a Ident,
+}
+
+impl Substitute<'_> {
+    // Only the priority and the types and values of declared local resources can use the generic
+    // parameters, the other arguments are names
+    fn task_arg(&mut self, arg: &[TokenTree]) -> parse::Result<TokenStream2> {
+        let tokens: TokenStream2 = arg.iter().cloned().collect();
+
+        match arg {
+            [TokenTree::Ident(key), TokenTree::Punct(eq), value @ ..]
+                if key == "priority" && eq.as_char() == '=' =>
+            {
+                let mut expr: Expr = syn::parse2(value.iter().cloned().collect())?;
+                self.visit_expr_mut(&mut expr);
+                Ok(quote!(#key #eq #expr))
+            }
+            [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Group(group)]
+                if key == "local" && eq.as_char() == '=' =>
+            {
+                let mut locals =
+                    Punctuated::<LocalArg, Token![,]>::parse_terminated.parse2(group.stream())?;
+                for local in &mut locals {
+                    if let Some((_, ty, _, expr)) = &mut local.declared {
+                        self.visit_type_mut(ty);
+                        self.visit_expr_mut(expr);
+                    }
+                }
+
+                let mut new = Group::new(group.delimiter(), locals.into_token_stream());
+                new.set_span(group.span());
+                Ok(quote!(#key #eq #new))
+            }
+            _ => Ok(tokens),
+        }
+    }
+}
+
+impl VisitMut for Substitute<'_> {
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        if path.leading_colon.is_none() {
+            let segments = path.segments.len();
+            let first = &mut path.segments[0];
+
+            if segments > 1 && first.ident == *self.task {
+                first.ident = Ident::new(&self.instance.to_string(), first.ident.span());
+            } else if let Some((_, alias)) = self
+                .substitutions
+                .iter()
+                .find(|(param, _)| *param == first.ident)
+            {
+                first.ident = Ident::new(&alias.to_string(), first.ident.span());
+            }
+        }
+
+        visit_mut::visit_path_mut(self, path);
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        if let Ok(mut args) =
+            Punctuated::<Expr, Token![,]>::parse_terminated.parse2(mac.tokens.clone())
+        {
+            for arg in &mut args {
+                self.visit_expr_mut(arg);
+            }
+            mac.tokens = args.into_token_stream();
+        }
+    }
+}
+
+// An entry of `local = [..]`, either `name` or `name: Type = expr`, with its attributes
+struct LocalArg {
+    attrs: Vec<Attribute>,
+    name: Ident,
+    declared: Option<(Token![:], Type, Token![=], Expr)>,
+}
+
+impl Parse for LocalArg {
+    fn parse(input: ParseStream<'_>) -> parse::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name = input.parse()?;
+        let declared = if input.peek(Token![:]) {
+            Some((
+                input.parse()?,
+                input.parse()?,
+                input.parse()?,
+                input.parse()?,
+            ))
+        } else {
+            None
+        };
+
+        Ok(LocalArg {
+            attrs,
+            name,
+            declared,
+        })
+    }
+}
+
+impl ToTokens for LocalArg {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        tokens.extend(self.attrs.iter().map(ToTokens::to_token_stream));
+        self.name.to_tokens(tokens);
+        if let Some((colon, ty, eq, expr)) = &self.declared {
+            colon.to_tokens(tokens);
+            ty.to_tokens(tokens);
+            eq.to_tokens(tokens);
+            expr.to_tokens(tokens);
+        }
+    }
+}
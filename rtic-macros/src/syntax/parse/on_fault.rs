@@ -0,0 +1,41 @@
+use syn::{parse, FnArg, ItemFn};
+
+use crate::syntax::{ast::OnFault, parse::util};
+
+impl OnFault {
+    pub(crate) fn parse(item: ItemFn) -> parse::Result<Self> {
+        let valid_signature = util::check_fn_signature(&item, false)
+            && (item.sig.inputs.len() == 2 || item.sig.inputs.len() == 3)
+            && util::type_is_bottom(&item.sig.output);
+
+        let inputs = item
+            .sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(input) => Some(input.clone()),
+                FnArg::Receiver(_) => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        if let (true, Some(inputs)) = (valid_signature, inputs) {
+            let mut inputs = inputs.into_iter();
+            return Ok(OnFault {
+                frame: inputs.next().expect("UNREACHABLE"),
+                tasks: inputs.next().expect("UNREACHABLE"),
+                persisted: inputs.next(),
+                attrs: item.attrs,
+                name: item.sig.ident,
+                stmts: item.block.stmts,
+            });
+        }
+
+        Err(parse::Error::new(
+            item.sig.ident.span(),
+            format!(
+                "this `#[on_fault]` function must have signature `fn(&rtic::fault::FaultFrame, &[rtic::fault::ActiveTask]) -> !`, optionally followed by a `{}::Persisted` argument",
+                item.sig.ident
+            ),
+        ))
+    }
+}
@@ -0,0 +1,34 @@
+use syn::{parse, FnArg, ItemFn, Type};
+
+use crate::syntax::{ast::OnStackOverflow, parse::util};
+
+impl OnStackOverflow {
+    pub(crate) fn parse(item: ItemFn) -> parse::Result<Self> {
+        let valid_signature = util::check_fn_signature(&item, false)
+            && item.sig.inputs.len() == 1
+            && util::type_is_bottom(&item.sig.output);
+
+        let priority = match item.sig.inputs.first() {
+            Some(FnArg::Typed(arg)) if valid_signature && is_u8(&arg.ty) => Some(arg.pat.clone()),
+            _ => None,
+        };
+
+        if let Some(priority) = priority {
+            return Ok(OnStackOverflow {
+                attrs: item.attrs,
+                name: item.sig.ident,
+                priority,
+                stmts: item.block.stmts,
+            });
+        }
+
+        Err(parse::Error::new(
+            item.sig.ident.span(),
+            "this `#[on_stack_overflow]` function must have signature `fn(u8) -> !`",
+        ))
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.qself.is_none() && p.path.is_ident("u8"))
+}
@@ -0,0 +1,25 @@
+use syn::{parse, FnArg, ItemFn};
+
+use crate::syntax::{ast::OnStarvation, parse::util};
+
+impl OnStarvation {
+    pub(crate) fn parse(item: ItemFn) -> parse::Result<Self> {
+        let valid_signature = util::check_fn_signature(&item, false)
+            && item.sig.inputs.len() == 1
+            && util::type_is_unit(&item.sig.output);
+
+        if let (true, Some(FnArg::Typed(task))) = (valid_signature, item.sig.inputs.first()) {
+            return Ok(OnStarvation {
+                task: task.clone(),
+                attrs: item.attrs,
+                name: item.sig.ident,
+                stmts: item.block.stmts,
+            });
+        }
+
+        Err(parse::Error::new(
+            item.sig.ident.span(),
+            "this `#[on_starvation]` function must have signature `fn(&'static str)`",
+        ))
+    }
+}
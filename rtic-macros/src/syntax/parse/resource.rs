@@ -6,6 +6,18 @@ use crate::syntax::{
     parse::util,
 };
 
+/// `#[persistent]` resources are initialized by value, so they can't use a slot as well
+fn check_persistent(item: &Field, init_in_place: bool, persistent: bool) -> parse::Result<()> {
+    if init_in_place && persistent {
+        return Err(parse::Error::new(
+            item.ident.as_ref().expect("UNREACHABLE").span(),
+            "a resource can't be both `#[init_in_place]` and `#[persistent]`",
+        ));
+    }
+
+    Ok(())
+}
+
 impl SharedResource {
     pub(crate) fn parse(item: &Field) -> parse::Result<Self> {
         let FilterAttrs {
@@ -15,13 +27,20 @@ impl SharedResource {
         } = util::filter_attributes(item.attrs.clone());
 
         let lock_free = util::extract_lock_free(&mut attrs)?;
+        let init_in_place = util::extract_init_in_place(&mut attrs);
+        let persistent = util::extract_persistent(&mut attrs);
+        check_persistent(item, init_in_place, persistent)?;
 
         Ok(SharedResource {
             cfgs,
             attrs,
             docs,
             ty: Box::new(item.ty.clone()),
-            properties: SharedResourceProperties { lock_free },
+            properties: SharedResourceProperties {
+                lock_free,
+                init_in_place,
+                persistent,
+            },
             vis: item.vis.clone(),
         })
     }
@@ -29,7 +48,15 @@ impl SharedResource {
 
 impl LocalResource {
     pub(crate) fn parse(item: &Field) -> parse::Result<Self> {
-        let FilterAttrs { cfgs, attrs, docs } = util::filter_attributes(item.attrs.clone());
+        let FilterAttrs {
+            cfgs,
+            mut attrs,
+            docs,
+        } = util::filter_attributes(item.attrs.clone());
+
+        let init_in_place = util::extract_init_in_place(&mut attrs);
+        let persistent = util::extract_persistent(&mut attrs);
+        check_persistent(item, init_in_place, persistent)?;
 
         Ok(LocalResource {
             cfgs,
@@ -37,6 +64,8 @@ impl LocalResource {
             docs,
             ty: Box::new(item.ty.clone()),
             vis: item.vis.clone(),
+            init_in_place,
+            persistent,
         })
     }
 }
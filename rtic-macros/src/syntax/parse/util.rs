@@ -91,6 +91,42 @@ pub fn extract_lock_free(attrs: &mut Vec<Attribute>) -> parse::Result<bool> {
     }
 }
 
+pub fn extract_init_in_place(attrs: &mut Vec<Attribute>) -> bool {
+    if let Some(pos) = attrs.iter().position(|attr| attr_eq(attr, "init_in_place")) {
+        attrs.remove(pos);
+        true
+    } else {
+        false
+    }
+}
+
+/// `#[unsafe(loaded_section)]`, vouching that the runtime loads the `#[link_section]` of a
+/// declared local from flash
+fn extract_loaded_section(attrs: &mut Vec<Attribute>) -> bool {
+    let position = attrs.iter().position(|attr| {
+        attr.path().is_ident("unsafe")
+            && attr
+                .parse_args::<Ident>()
+                .is_ok_and(|ident| ident == "loaded_section")
+    });
+
+    if let Some(pos) = position {
+        attrs.remove(pos);
+        true
+    } else {
+        false
+    }
+}
+
+pub fn extract_persistent(attrs: &mut Vec<Attribute>) -> bool {
+    if let Some(pos) = attrs.iter().position(|attr| attr_eq(attr, "persistent")) {
+        attrs.remove(pos);
+        true
+    } else {
+        false
+    }
+}
+
 pub fn parse_shared_resources(content: ParseStream<'_>) -> parse::Result<SharedResources> {
     let inner;
     bracketed!(inner in content);
@@ -178,7 +214,7 @@ pub fn parse_local_resources(content: ParseStream<'_>) -> parse::Result<LocalRes
 
         // Extract attributes
         let ExprPath { attrs, .. } = exprpath;
-        let (cfgs, attrs) = {
+        let (cfgs, mut attrs) = {
             let FilterAttrs { cfgs, attrs, .. } = filter_attributes(attrs);
             (cfgs, attrs)
         };
@@ -223,11 +259,14 @@ pub fn parse_local_resources(content: ParseStream<'_>) -> parse::Result<LocalRes
                 }
             };
 
+            let loaded_section = extract_loaded_section(&mut attrs);
+
             TaskLocal::Declared(Local {
                 attrs,
                 cfgs,
                 ty,
                 expr,
+                loaded_section,
             })
         } else if input.peek(Token![=]) {
             // Missing type ascription is not valid
@@ -0,0 +1,59 @@
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse, parse_quote, Ident, ItemFn};
+
+use crate::syntax::ast::{App, SoftwareTask, SoftwareTaskArgs};
+
+/// The name of the async task feeding the watchdog
+pub(crate) fn supervisor_ident() -> Ident {
+    Ident::new("__rtic_watchdog", Span::call_site())
+}
+
+/// The `static` recording which supervised tasks checked in
+pub(crate) fn checkins_ident() -> Ident {
+    Ident::new("__rtic_internal_WATCHDOG_CHECKINS", Span::call_site())
+}
+
+// Add the supervisor of `watchdog` as an async task at the highest priority of the async tasks,
+// so that it is not starved by the tasks it supervises
+pub(crate) fn supervisor(app: &mut App) -> parse::Result<()> {
+    let (Some(watchdog), Some(timeout)) = (&app.args.watchdog, &app.args.timeout) else {
+        return Ok(());
+    };
+
+    let priority = app
+        .software_tasks
+        .values()
+        .filter(|task| task.args.priority_expr.is_none())
+        .map(|task| task.args.priority)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let on_starvation = match &app.on_starvation {
+        Some(hook) => {
+            let name = &hook.name;
+            quote!(#name)
+        }
+        None => quote!(|_| {}),
+    };
+
+    let name = supervisor_ident();
+    let checkins = checkins_ident();
+    let item: ItemFn = parse_quote!(
+        async fn #name(_: #name::Context) {
+            #checkins
+                .supervise::<#watchdog>(#timeout, #on_starvation)
+                .await
+        }
+    );
+
+    let args = SoftwareTaskArgs {
+        priority,
+        ..SoftwareTaskArgs::default()
+    };
+    app.software_tasks
+        .insert(name, SoftwareTask::parse(args, item)?);
+
+    Ok(())
+}
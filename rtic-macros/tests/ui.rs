@@ -5,3 +5,10 @@ fn ui() {
     let t = TestCases::new();
     t.compile_fail("ui/*.rs");
 }
+
+#[cfg(feature = "riscv-clint")]
+#[test]
+fn ui_riscv_clint() {
+    let t = TestCases::new();
+    t.compile_fail("ui/riscv-clint/*.rs");
+}
@@ -0,0 +1,28 @@
+#![no_main]
+
+const LIMIT: u32 = 3;
+
+#[rtic_macros::component]
+mod counter {
+    #[shared]
+    struct Shared {
+        count: u32,
+    }
+
+    #[task(shared = [count])]
+    async fn tick(mut cx: tick::Context) {
+        cx.shared.count.lock(|count| *count = super::LIMIT);
+    }
+}
+
+#[rtic_macros::mock_app(device = mock, components = [counter])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {}
+}
@@ -0,0 +1,26 @@
+#![no_main]
+
+#[rtic_macros::component]
+mod counter {
+    #[shared]
+    struct Shared {
+        count: u32,
+    }
+
+    #[task(shared = [count, total])]
+    async fn tick(_: tick::Context) {}
+}
+
+#[rtic_macros::mock_app(device = mock, components = [counter])]
+mod app {
+    #[shared]
+    struct Shared {
+        total: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {}
+}
@@ -0,0 +1,17 @@
+#![no_main]
+
+#[rtic_macros::mock_app(device = mock)]
+mod app {
+    #[shared]
+    struct Shared {
+        #[persistent]
+        #[init_in_place]
+        boot_count: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {}
+}
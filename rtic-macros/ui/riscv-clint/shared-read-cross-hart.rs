@@ -0,0 +1,22 @@
+#![no_main]
+
+#[rtic_macros::mock_app(device = mock, backend = { harts = [0, 1] })]
+mod app {
+    #[shared]
+    struct Shared {
+        rounds: u32,
+    }
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {}
+
+    #[task(shared = [rounds], priority = 1)]
+    async fn ping(_: ping::Context) {}
+
+    // the read ceiling doesn't keep out `ping`, which runs on the other hart
+    #[task(shared = [&rounds], priority = 1, core = 1)]
+    async fn pong(_: pong::Context) {}
+}
@@ -0,0 +1,13 @@
+#![no_main]
+
+#[rtic_macros::mock_app(device = mock, stack_guard = 256)]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_: init::Context) -> (Shared, Local) {}
+}
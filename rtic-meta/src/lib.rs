@@ -0,0 +1,403 @@
+//! Reads the `.rtic_meta` section RTIC apps emit with the `meta-section` feature of `rtic`.
+//!
+//! The section describes the tasks and shared resources of an app and where their state lives in
+//! RAM. A debugger parses it from the ELF once, then decodes dumps of the RAM of the target:
+//!
+//! ```no_run
+//! # fn read_ram(_: u64, _: usize) -> Vec<u8> { vec![] }
+//! let elf = std::fs::read("target/thumbv7m-none-eabi/debug/app").unwrap();
+//! let meta = rtic_meta::Meta::from_elf(&elf).unwrap();
+//!
+//! let ram = read_ram(0x2000_0000, 0x1_0000);
+//! print!("{}", meta.task_table(&rtic_meta::Ram::new(0x2000_0000, &ram)));
+//! ```
+//!
+//! See `rtic::meta` for the layout of the section.
+
+#![deny(missing_docs)]
+
+use std::fmt;
+
+use object::{Object, ObjectSection};
+
+/// The name of the section
+pub const SECTION: &str = ".rtic_meta";
+
+/// The first bytes of the section
+pub const MAGIC: [u8; 4] = *b"RTIC";
+
+/// The version of the layout of the section this crate reads
+pub const VERSION: u32 = 1;
+
+const HEADER_SIZE: usize = 24;
+
+/// An error reading the section.
+#[derive(Debug)]
+pub enum Error {
+    /// The file is not an ELF file `object` can read
+    Elf(object::Error),
+    /// The ELF has no `.rtic_meta` section, the app wasn't built with `meta-section`
+    NoSection,
+    /// The section doesn't start with [`MAGIC`]
+    BadMagic,
+    /// The section has a layout this crate doesn't know
+    UnsupportedVersion(u32),
+    /// The section is cut short, or its entries are smaller than they should be
+    Truncated,
+    /// A name of the section isn't valid UTF-8 or lies outside of the string table
+    InvalidString,
+    /// A task has a kind this crate doesn't know
+    UnknownKind(u8),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Elf(e) => write!(f, "can't read the ELF: {e}"),
+            Error::NoSection => {
+                write!(f, "no `{SECTION}` section, enable `meta-section` of `rtic`")
+            }
+            Error::BadMagic => write!(f, "the `{SECTION}` section doesn't start with `RTIC`"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "version {version} of `{SECTION}` isn't supported")
+            }
+            Error::Truncated => write!(f, "the `{SECTION}` section is truncated"),
+            Error::InvalidString => write!(f, "invalid string in the `{SECTION}` section"),
+            Error::UnknownKind(kind) => write!(f, "unknown task kind {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<object::Error> for Error {
+    fn from(e: object::Error) -> Self {
+        Error::Elf(e)
+    }
+}
+
+/// The kind of a task.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TaskKind {
+    /// `#[init]`
+    Init,
+    /// `#[idle]`
+    Idle,
+    /// A task bound to interrupts
+    Hardware,
+    /// An async task run by an executor
+    Software,
+}
+
+impl fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            TaskKind::Init => "init",
+            TaskKind::Idle => "idle",
+            TaskKind::Hardware => "hardware",
+            TaskKind::Software => "software",
+        })
+    }
+}
+
+/// A task of the app.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Task {
+    /// The name of the task
+    pub name: String,
+    /// What kind of task it is
+    pub kind: TaskKind,
+    /// The priority of the task, 0 for `#[init]`
+    pub priority: u8,
+    /// The interrupts a hardware task is bound to
+    pub binds: Vec<String>,
+    /// The address of the `running` flag of the executor of a software task
+    pub running: Option<u64>,
+    /// The address of the `pending` flag of the executor of a software task
+    pub pending: Option<u64>,
+}
+
+/// A shared resource of the app.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resource {
+    /// The name of the resource
+    pub name: String,
+    /// The priority ceiling of the resource
+    pub ceiling: u8,
+    /// Where the resource is stored
+    pub address: u64,
+    /// The size of the resource, in bytes
+    pub size: u32,
+}
+
+impl Resource {
+    /// The bytes of the resource in `ram`, if it holds all of them.
+    pub fn value<'a>(&self, ram: &Ram<'a>) -> Option<&'a [u8]> {
+        ram.read(self.address, self.size as usize)
+    }
+}
+
+/// The contents of the section.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Meta {
+    /// The tasks, in the order of their `rtic::TaskId`
+    pub tasks: Vec<Task>,
+    /// The shared resources
+    pub resources: Vec<Resource>,
+}
+
+/// Reads the integers of the section in the byte order of the target.
+struct Reader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N], Error> {
+        let bytes = self.data.get(offset..offset + N).ok_or(Error::Truncated)?;
+        let mut bytes: [u8; N] = bytes.try_into().expect("UNREACHABLE");
+        if !self.little_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    fn u8(&self, offset: usize) -> Result<u8, Error> {
+        self.data.get(offset).copied().ok_or(Error::Truncated)
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, Error> {
+        self.bytes(offset).map(u16::from_le_bytes)
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, Error> {
+        self.bytes(offset).map(u32::from_le_bytes)
+    }
+
+    fn address(&self, offset: usize, size: usize) -> Result<Option<u64>, Error> {
+        let address = match size {
+            4 => u64::from(self.u32(offset)?),
+            _ => self.bytes(offset).map(u64::from_le_bytes)?,
+        };
+        Ok(Some(address).filter(|address| *address != 0))
+    }
+}
+
+impl Meta {
+    /// Reads the section from the ELF file in `elf`.
+    pub fn from_elf(elf: &[u8]) -> Result<Meta, Error> {
+        let file = object::File::parse(elf)?;
+        let section = file.section_by_name(SECTION).ok_or(Error::NoSection)?;
+
+        Meta::parse(section.data()?, file.is_little_endian())
+    }
+
+    /// Parses the contents of the section, as stored by a target of the given byte order.
+    pub fn parse(section: &[u8], little_endian: bool) -> Result<Meta, Error> {
+        let r = Reader {
+            data: section,
+            little_endian,
+        };
+
+        if r.data.get(..4) != Some(&MAGIC[..]) {
+            return Err(Error::BadMagic);
+        }
+        let version = r.u32(4)?;
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let n_tasks = usize::from(r.u16(8)?);
+        let task_size = usize::from(r.u16(10)?);
+        let n_resources = usize::from(r.u16(12)?);
+        let resource_size = usize::from(r.u16(14)?);
+        let n_strings = r.u32(16)? as usize;
+        let a = r.u32(20)? as usize;
+        // Fields may be appended to the entries without a new version, they never shrink
+        if !matches!(a, 4 | 8) || task_size < 2 * a + 18 || resource_size < a + 13 {
+            return Err(Error::Truncated);
+        }
+
+        let resources_start = HEADER_SIZE + n_tasks * task_size;
+        let strings_start = resources_start + n_resources * resource_size;
+        let strings = section
+            .get(strings_start..strings_start + n_strings)
+            .ok_or(Error::Truncated)?;
+        let string = |offset: usize| -> Result<String, Error> {
+            let start = r.u32(offset)? as usize;
+            let len = r.u32(offset + 4)? as usize;
+            let bytes = strings
+                .get(start..start + len)
+                .ok_or(Error::InvalidString)?;
+            let s = std::str::from_utf8(bytes).map_err(|_| Error::InvalidString)?;
+            Ok(s.to_string())
+        };
+
+        let mut tasks = vec![];
+        for i in 0..n_tasks {
+            let entry = HEADER_SIZE + i * task_size;
+            let binds = string(entry + 2 * a + 8)?;
+            let kind = match r.u8(entry + 2 * a + 17)? {
+                0 => TaskKind::Init,
+                1 => TaskKind::Idle,
+                2 => TaskKind::Hardware,
+                3 => TaskKind::Software,
+                kind => return Err(Error::UnknownKind(kind)),
+            };
+
+            tasks.push(Task {
+                name: string(entry + 2 * a)?,
+                kind,
+                priority: r.u8(entry + 2 * a + 16)?,
+                binds: binds
+                    .split(',')
+                    .filter(|binds| !binds.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                running: r.address(entry, a)?,
+                pending: r.address(entry + a, a)?,
+            });
+        }
+
+        let mut resources = vec![];
+        for i in 0..n_resources {
+            let entry = resources_start + i * resource_size;
+
+            resources.push(Resource {
+                name: string(entry + a + 4)?,
+                ceiling: r.u8(entry + a + 12)?,
+                address: r.address(entry, a)?.unwrap_or(0),
+                size: r.u32(entry + a)?,
+            });
+        }
+
+        Ok(Meta { tasks, resources })
+    }
+
+    /// The task called `name`.
+    pub fn task(&self, name: &str) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.name == name)
+    }
+
+    /// The shared resource called `name`.
+    pub fn resource(&self, name: &str) -> Option<&Resource> {
+        self.resources.iter().find(|res| res.name == name)
+    }
+
+    /// The state of every task, as recorded in `ram`.
+    pub fn task_table<'a>(&'a self, ram: &Ram<'_>) -> TaskTable<'a> {
+        let rows = self
+            .tasks
+            .iter()
+            .map(|task| {
+                let flag = |address: Option<u64>| ram.read(address?, 1).map(|flag| flag[0] != 0);
+                let state = match (flag(task.running), flag(task.pending)) {
+                    (Some(false), Some(_)) => Some(State::Inactive),
+                    (Some(true), Some(false)) => Some(State::Blocked),
+                    (Some(true), Some(true)) => Some(State::Ready),
+                    _ => None,
+                };
+
+                TaskRow { task, state }
+            })
+            .collect();
+
+        TaskTable { rows }
+    }
+}
+
+/// A dump of the memory of the target.
+#[derive(Clone, Copy, Debug)]
+pub struct Ram<'a> {
+    base: u64,
+    bytes: &'a [u8],
+}
+
+impl<'a> Ram<'a> {
+    /// `bytes` read from the target starting at `base`.
+    pub fn new(base: u64, bytes: &'a [u8]) -> Self {
+        Ram { base, bytes }
+    }
+
+    /// The `len` bytes at `address`, if the dump holds all of them.
+    pub fn read(&self, address: u64, len: usize) -> Option<&'a [u8]> {
+        let start = usize::try_from(address.checked_sub(self.base)?).ok()?;
+        self.bytes.get(start..start.checked_add(len)?)
+    }
+}
+
+/// The state of the executor of a software task.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum State {
+    /// Not spawned, or returned
+    Inactive,
+    /// Spawned and waiting to be woken
+    Blocked,
+    /// Spawned and woken, waiting to be polled by its dispatcher
+    Ready,
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            State::Inactive => "inactive",
+            State::Blocked => "blocked",
+            State::Ready => "ready",
+        })
+    }
+}
+
+/// A task and its state in a dump.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TaskRow<'a> {
+    /// The task
+    pub task: &'a Task,
+    /// The state of a software task, `None` for other tasks or if the dump misses its executor
+    pub state: Option<State>,
+}
+
+/// The state of every task in a dump, printed as a table with `Display`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaskTable<'a> {
+    /// One row per task, in the order of [`Meta::tasks`]
+    pub rows: Vec<TaskRow<'a>>,
+}
+
+impl fmt::Display for TaskTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let binds: Vec<_> = self
+            .rows
+            .iter()
+            .map(|row| row.task.binds.join(","))
+            .collect();
+        let name_width = self
+            .rows
+            .iter()
+            .map(|row| row.task.name.len())
+            .chain(["task".len()])
+            .max()
+            .unwrap_or(0);
+        let binds_width = binds
+            .iter()
+            .map(String::len)
+            .chain(["binds".len()])
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            f,
+            "{:name_width$}  {:8}  {:8}  {:binds_width$}  state",
+            "task", "kind", "priority", "binds"
+        )?;
+        for (row, binds) in self.rows.iter().zip(&binds) {
+            let state = row.state.map_or("-".to_string(), |state| state.to_string());
+            writeln!(
+                f,
+                "{:name_width$}  {:8}  {:<8}  {:binds_width$}  {}",
+                row.task.name, row.task.kind, row.task.priority, binds, state
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! Reads the section of ELFs built from the lm3s6965 examples.
+//!
+//! The examples are built with `cargo` for `thumbv7m-none-eabi`, unless `RTIC_META_ELF_DIR` points
+//! at a directory holding them, built with the `thumbv7-backend,meta-section` features. These tests
+//! are ignored by default, run them with `cargo test -p rtic-meta -- --ignored`.
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+use rtic_meta::{Error, Meta, Ram, State, TaskKind};
+
+const RAM: u64 = 0x2000_0000;
+const TARGET: &str = "thumbv7m-none-eabi";
+
+fn elf(example: &str) -> Vec<u8> {
+    let dir = match env::var_os("RTIC_META_ELF_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("lm3s6965");
+            let status = Command::new(env!("CARGO"))
+                .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../examples/lm3s6965"))
+                .args(["build", "--example", example, "--target", TARGET])
+                .args(["--features", "thumbv7-backend,meta-section", "--target-dir"])
+                .arg(&target_dir)
+                .status()
+                .unwrap();
+            assert!(status.success(), "building `{example}` failed");
+
+            target_dir.join(TARGET).join("debug/examples")
+        }
+    };
+
+    fs::read(dir.join(example)).unwrap()
+}
+
+#[test]
+#[ignore = "builds lm3s6965 examples for thumbv7m-none-eabi"]
+fn lock() {
+    let meta = Meta::from_elf(&elf("lock")).unwrap();
+
+    let tasks: Vec<_> = meta
+        .tasks
+        .iter()
+        .map(|task| (&*task.name, task.kind, task.priority))
+        .collect();
+    assert_eq!(
+        tasks,
+        [
+            ("init", TaskKind::Init, 0),
+            ("foo", TaskKind::Software, 0),
+            ("bar", TaskKind::Software, 2),
+            ("baz", TaskKind::Software, 3),
+        ]
+    );
+    assert!(meta.tasks.iter().all(|task| task.binds.is_empty()));
+
+    let init = meta.task("init").unwrap();
+    assert_eq!((init.running, init.pending), (None, None));
+    for task in &meta.tasks[1..] {
+        let running = task.running.unwrap();
+        let pending = task.pending.unwrap();
+        assert!((RAM..RAM + 0x1_0000).contains(&running));
+        assert_ne!(running, pending);
+    }
+
+    assert_eq!(meta.resources.len(), 1);
+    let shared = meta.resource("shared").unwrap();
+    assert_eq!((shared.ceiling, shared.size), (2, 4));
+    assert!((RAM..RAM + 0x1_0000).contains(&shared.address));
+}
+
+#[test]
+#[ignore = "builds lm3s6965 examples for thumbv7m-none-eabi"]
+fn binds_multiple() {
+    let meta = Meta::from_elf(&elf("binds_multiple")).unwrap();
+
+    let uart = meta.task("uart").unwrap();
+    assert_eq!(uart.kind, TaskKind::Hardware);
+    assert_eq!(uart.priority, 1);
+    assert_eq!(uart.binds, ["UART0", "UART1"]);
+    assert_eq!((uart.running, uart.pending), (None, None));
+
+    assert_eq!(meta.resource("bytes").unwrap().ceiling, 1);
+}
+
+#[test]
+#[ignore = "builds lm3s6965 examples for thumbv7m-none-eabi"]
+fn decode_ram() {
+    let meta = Meta::from_elf(&elf("lock")).unwrap();
+    let task = |name| meta.task(name).unwrap();
+    let offset = |address: Option<u64>| (address.unwrap() - RAM) as usize;
+
+    // `foo` is holding the lock, `bar` was spawned and waits for it to be released
+    let mut dump = vec![0; 0x1_0000];
+    dump[offset(task("foo").running)] = 1;
+    dump[offset(task("bar").running)] = 1;
+    dump[offset(task("bar").pending)] = 1;
+    let shared = meta.resource("shared").unwrap();
+    dump[offset(Some(shared.address))..][..4].copy_from_slice(&1u32.to_le_bytes());
+    let ram = Ram::new(RAM, &dump);
+
+    let states: Vec<_> = meta
+        .task_table(&ram)
+        .rows
+        .iter()
+        .map(|row| row.state)
+        .collect();
+    assert_eq!(
+        states,
+        [
+            None,
+            Some(State::Blocked),
+            Some(State::Ready),
+            Some(State::Inactive)
+        ]
+    );
+    assert_eq!(
+        meta.task_table(&ram).to_string(),
+        "\
+task  kind      priority  binds  state
+init  init      0                -
+foo   software  0                blocked
+bar   software  2                ready
+baz   software  3                inactive
+"
+    );
+    assert_eq!(shared.value(&ram), Some(&1u32.to_le_bytes()[..]));
+
+    // A dump of part of the RAM only knows about what it holds
+    let ram = Ram::new(RAM + 0x8000, &dump[0x8000..]);
+    assert!(
+        meta.task_table(&ram)
+            .rows
+            .iter()
+            .all(|row| row.state.is_none())
+    );
+    assert_eq!(shared.value(&ram), None);
+}
+
+#[test]
+fn errors() {
+    // The test itself is an ELF without the section
+    let test = fs::read(env::current_exe().unwrap()).unwrap();
+    assert!(matches!(Meta::from_elf(&test), Err(Error::NoSection)));
+    assert!(matches!(Meta::from_elf(b"RTIC"), Err(Error::Elf(_))));
+
+    assert!(matches!(Meta::parse(b"RTIX", true), Err(Error::BadMagic)));
+    assert!(matches!(
+        Meta::parse(b"RTIC\x02\0\0\0", true),
+        Err(Error::UnsupportedVersion(2))
+    ));
+    assert!(matches!(
+        Meta::parse(b"RTIC\x01\0\0\0", true),
+        Err(Error::Truncated)
+    ));
+}
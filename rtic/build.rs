@@ -32,6 +32,10 @@ fn main() {
         ("riscv-esp32c6-backend", "riscv-esp32c6"),
         ("riscv-clint-backend", "riscv-slic"),
         ("riscv-mecall-backend", "riscv-slic"),
+        ("riscv-plic-backend", "riscv-plic"),
+        ("custom-backend", "custom"),
+        ("host-backend", "host"),
+        ("sim-backend", "sim"),
     ];
 
     let cfg_values: Vec<_> = impls
@@ -0,0 +1,73 @@
+//! Extension point for out-of-tree backends
+//!
+//! With the `custom-backend` feature, `#[app(backend = path::to::MyBackend)]` generates code that
+//! calls into the [`Backend`] implementation of `MyBackend` wherever the built-in backends call
+//! their own interrupt controller code. A crate supporting an MCU that RTIC has no backend for can
+//! then implement [`Backend`] instead of adding a backend to `rtic` and `rtic-macros`.
+//!
+//! Interrupt handlers are generated as `#[no_mangle] unsafe fn NAME()`, named after the interrupt
+//! they are bound to; the backend (or its runtime crate) is responsible for calling them, usually
+//! from its vector table.
+
+/// The interrupt controller operations RTIC needs to run an application.
+///
+/// Priorities are logical: `1` is the lowest priority of a task and higher numbers preempt lower
+/// ones, `0` is the priority of `#[idle]`. The backend maps them to the hardware.
+///
+/// # Safety
+///
+/// The generated code relies on the implementation for memory safety. In particular [`Backend::lock`]
+/// must keep every task with a priority up to `ceiling` from running until it returns, and an
+/// interrupt enabled with [`Backend::enable_interrupt`] must only preempt code running at a lower
+/// priority.
+pub unsafe trait Backend {
+    /// The interrupts that hardware tasks are bound to and software tasks are dispatched from.
+    ///
+    /// `#[app(interrupts = ..)]` names the enumeration, whose variants are the interrupts listed
+    /// in `binds` and `dispatchers`.
+    type Interrupt: Copy;
+
+    /// The highest priority supported by the interrupt controller.
+    const MAX_PRIORITY: u8;
+
+    /// Disables all interrupts, called first thing in `main`.
+    fn interrupt_disable();
+
+    /// Enables interrupts, called once `#[init]` has returned.
+    ///
+    /// # Safety
+    ///
+    /// Only called by the generated code, once all resources have been initialized.
+    unsafe fn interrupt_enable();
+
+    /// Sets the priority of `interrupt` and unmasks it, called before interrupts are enabled.
+    ///
+    /// # Safety
+    ///
+    /// Only called by the generated code, with interrupts disabled.
+    unsafe fn enable_interrupt(interrupt: Self::Interrupt, priority: u8);
+
+    /// Pends `interrupt`, used to dispatch software tasks.
+    fn pend(interrupt: Self::Interrupt);
+
+    /// Runs the handler `f` of a task with the given `priority`, from its interrupt handler.
+    ///
+    /// The priority at which the interrupted code was running must be in effect again when `run`
+    /// returns.
+    fn run(priority: u8, f: impl FnOnce());
+
+    /// Runs `f` on the resource behind `ptr`, with the system ceiling raised to `ceiling`.
+    ///
+    /// # Safety
+    ///
+    /// Only called by the generated code, with a pointer to a resource whose ceiling is `ceiling`.
+    unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R;
+
+    /// Called on entry of every interrupt handler, before the task runs.
+    #[inline(always)]
+    fn interrupt_entry() {}
+
+    /// Called at the end of every interrupt handler, after the task has run.
+    #[inline(always)]
+    fn interrupt_exit() {}
+}
@@ -3,6 +3,18 @@ pub use portable_atomic as atomic;
 
 pub mod executor;
 
+#[cfg(not(any(
+    implementation = "custom",
+    implementation = "host",
+    implementation = "sim"
+)))]
+pub use crate::stack::{enter as stack_enter, paint as paint_stack};
+
+pub mod fault;
+pub mod persistent;
+pub mod stack_guard;
+pub mod watchdog;
+
 // Cortex-M target (any)
 #[cfg(any(
     implementation = "cortex-m-basepri",
@@ -23,6 +35,12 @@ mod cortex_basepri;
 #[cfg(implementation = "cortex-m-basepri")]
 pub use cortex_basepri::*;
 
+#[cfg(implementation = "cortex-m-basepri")]
+pub mod mpu;
+
+#[cfg(implementation = "cortex-m-basepri")]
+pub mod trustzone;
+
 // Cortex-M target with source mask support
 #[cfg(implementation = "cortex-m-source-masking")]
 mod cortex_source_mask;
@@ -40,13 +58,54 @@ mod riscv_esp32c6;
 #[cfg(implementation = "riscv-esp32c6")]
 pub use riscv_esp32c6::*;
 
+#[cfg(implementation = "host")]
+mod host;
+#[cfg(implementation = "host")]
+pub use host::*;
+
+#[cfg(implementation = "sim")]
+mod sim;
+#[cfg(implementation = "sim")]
+pub use sim::*;
+
 #[cfg(implementation = "riscv-slic")]
 mod slic;
 #[cfg(implementation = "riscv-slic")]
 pub use slic::*;
 
+#[cfg(implementation = "riscv-plic")]
+mod riscv_plic;
+#[cfg(implementation = "riscv-plic")]
+pub use riscv_plic::*;
+
 #[inline(always)]
 pub fn assert_send<T: Send>() {}
 
 #[inline(always)]
 pub fn assert_sync<T: Sync>() {}
+
+/// Highest of `priorities`, the ceiling of a resource accessed at priorities given as constants.
+pub const fn max_priority<const N: usize>(priorities: [u8; N]) -> u8 {
+    let mut max = 0;
+    let mut i = 0;
+    while i < N {
+        if priorities[i] > max {
+            max = priorities[i];
+        }
+        i += 1;
+    }
+    max
+}
+
+/// Lowest of `priorities`.
+pub const fn min_priority<const N: usize>(priorities: [u8; N]) -> u8 {
+    let mut min = u8::MAX;
+    let mut i = 0;
+    while i < N {
+        if priorities[i] < min {
+            min = priorities[i];
+        }
+        i += 1;
+    }
+    min
+}
@@ -1,12 +1,22 @@
+#[cfg(feature = "executor-stats")]
+use super::atomic::AtomicU32;
 use super::atomic::{AtomicBool, Ordering};
 use core::{
     cell::UnsafeCell,
     convert::Infallible,
-    future::Future,
-    mem::{self, MaybeUninit},
+    future::{Future, poll_fn},
+    mem::MaybeUninit,
     pin::Pin,
+    ptr,
     task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
 };
+use rtic_common::{dropper::OnDrop, wait_queue::Link};
+
+/// The callers of `spawn_wait` parked until the task returns, declared next to the executor of
+/// the tasks with `spawn_wait`.
+pub use rtic_common::wait_queue::WaitQueue;
+
+use crate::task_id::TaskId;
 
 static WAKER_VTABLE: RawWakerVTable =
     RawWakerVTable::new(waker_clone, waker_wake, waker_wake, waker_drop);
@@ -17,15 +27,41 @@ unsafe fn waker_clone(p: *const ()) -> RawWaker {
 
 unsafe fn waker_wake(p: *const ()) {
     // The only thing we need from a waker is the function to call to pend the async
-    // dispatcher.
-    let f: fn() = unsafe { mem::transmute(p) };
-    f();
+    // dispatcher, the task id is only there to be looked at.
+    let waker = unsafe { &*p.cast::<TaskWaker>() };
+    (waker.wake)();
 }
 
 unsafe fn waker_drop(_: *const ()) {
     // nop
 }
 
+/// The data of the wakers of a task, one `static` per task generated by `#[app]`.
+pub struct TaskWaker {
+    wake: fn(),
+    id: TaskId,
+}
+
+impl TaskWaker {
+    /// The waker data of the task at `index` in `names`, pending it with `wake`.
+    pub const fn new(index: u16, names: &'static [&'static str], wake: fn()) -> Self {
+        TaskWaker {
+            wake,
+            id: TaskId::new(index, names),
+        }
+    }
+}
+
+/// The task of `waker`, if it was created by an executor of RTIC.
+pub(crate) fn task_id(waker: &Waker) -> Option<TaskId> {
+    if ptr::eq(waker.vtable(), &WAKER_VTABLE) {
+        // SAFETY: all wakers with this vtable point at a `TaskWaker`.
+        Some(unsafe { &*waker.data().cast::<TaskWaker>() }.id)
+    } else {
+        None
+    }
+}
+
 //============
 // Naming a task's future
 
@@ -108,8 +144,8 @@ where
 /// Storage for one [`AsyncTaskExecutor`], sized and aligned for a future that cannot be named.
 ///
 /// Declared as bytes so the `static` needs no type parameter, and initialized by transmuting an
-/// [`exec_new`]. Both flags are false in that image, so this lands in `.bss` and costs no
-/// initializer.
+/// [`exec_new`]. Both flags are false, the wait queue is empty and the statistics are zero in that
+/// image, so this lands in `.bss` and costs no initializer.
 #[allow(private_bounds)]
 #[repr(C)]
 pub struct ExecutorHolder<const SIZE: usize, const ALIGN: usize>
@@ -155,13 +191,46 @@ where
     unsafe { &*holder.data.get().cast() }
 }
 
+/// The addresses of the `running` and `pending` flags of the executor in `holder`, for
+/// [`meta`](crate::meta).
+#[cfg(feature = "meta-section")]
+#[allow(private_bounds)]
+pub const fn exec_flags<F, Args, Fut, const SIZE: usize, const ALIGN: usize>(
+    _f: F,
+    holder: *const ExecutorHolder<SIZE, ALIGN>,
+) -> [crate::meta::Address; 2]
+where
+    F: ExecFn<Args, Fut = Fut>,
+    Fut: Future + 'static,
+    Align<ALIGN>: Alignment,
+{
+    let executor = holder.cast::<u8>();
+    let running = core::mem::offset_of!(AsyncTaskExecutor<Fut>, running);
+    let pending = core::mem::offset_of!(AsyncTaskExecutor<Fut>, pending);
+
+    [
+        crate::meta::Address::of(executor.wrapping_add(running)),
+        crate::meta::Address::of(executor.wrapping_add(pending)),
+    ]
+}
+
 #[allow(private_bounds)]
 #[repr(transparent)]
 pub struct Align<const N: usize>([<Self as Alignment>::Archetype; 0])
 where
     Self: Alignment;
 
-trait Alignment {
+impl<const N: usize> Align<N>
+where
+    Self: Alignment,
+{
+    #[cfg(implementation = "cortex-m-basepri")]
+    pub(crate) const fn new() -> Self {
+        Align([])
+    }
+}
+
+pub trait Alignment {
     /// A zero-sized type of particular alignment.
     type Archetype: Copy + Eq + PartialEq + Send + Sync + Unpin;
 }
@@ -171,7 +240,7 @@ macro_rules! aligns {
         $(
             #[derive(Copy, Clone, Eq, PartialEq)]
             #[repr(align($n))]
-            struct $AlignX {}
+            pub struct $AlignX {}
 
             impl Alignment for Align<$n> {
                 type Archetype = $AlignX;
@@ -196,17 +265,104 @@ aligns!(
     Align4096: 4096,
     Align8192: 8192,
     Align16384: 16384,
+    Align32768: 32768,
+    Align65536: 65536,
+    Align131072: 131072,
+    Align262144: 262144,
 );
 
+//============
+// Statistics
+
+/// Counts events of an executor into `$counter` of its `Counters`.
+#[cfg(feature = "executor-stats")]
+macro_rules! count {
+    ($exec:expr, $counter:ident) => {
+        $exec.stats.$counter.fetch_add(1, Ordering::Relaxed)
+    };
+}
+
+#[cfg(not(feature = "executor-stats"))]
+macro_rules! count {
+    ($exec:expr, $counter:ident) => {
+        ()
+    };
+}
+
+#[cfg(feature = "executor-stats")]
+struct Counters {
+    spawns: AtomicU32,
+    polls: AtomicU32,
+    wakeups: AtomicU32,
+    spurious_wakeups: AtomicU32,
+    completions: AtomicU32,
+    failed_allocations: AtomicU32,
+}
+
+#[cfg(feature = "executor-stats")]
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            spawns: AtomicU32::new(0),
+            polls: AtomicU32::new(0),
+            wakeups: AtomicU32::new(0),
+            spurious_wakeups: AtomicU32::new(0),
+            completions: AtomicU32::new(0),
+            failed_allocations: AtomicU32::new(0),
+        }
+    }
+
+    fn snapshot(&self) -> crate::stats::TaskStats {
+        crate::stats::TaskStats {
+            spawns: self.spawns.load(Ordering::Relaxed),
+            polls: self.polls.load(Ordering::Relaxed),
+            wakeups: self.wakeups.load(Ordering::Relaxed),
+            spurious_wakeups: self.spurious_wakeups.load(Ordering::Relaxed),
+            completions: self.completions.load(Ordering::Relaxed),
+            failed_allocations: self.failed_allocations.load(Ordering::Relaxed),
+        }
+    }
+
+    #[cfg(implementation = "sim")]
+    fn reset(&self) {
+        for counter in [
+            &self.spawns,
+            &self.polls,
+            &self.wakeups,
+            &self.spurious_wakeups,
+            &self.completions,
+            &self.failed_allocations,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
 //============
 // AsyncTaskExecutor
 
+/// Lets the future of `allocate` stay `Send` while it points at its own link.
+#[derive(Clone)]
+struct LinkPtr(*mut Option<Link<Waker>>);
+
+impl LinkPtr {
+    /// This will dereference the pointer stored within and give out an `&mut`.
+    unsafe fn get(&mut self) -> &mut Option<Link<Waker>> {
+        unsafe { &mut *self.0 }
+    }
+}
+
+unsafe impl Send for LinkPtr {}
+unsafe impl Sync for LinkPtr {}
+
 /// Executor for an async task.
 pub struct AsyncTaskExecutor<F: Future + 'static> {
     // `task` is protected by the `running` flag.
     task: UnsafeCell<MaybeUninit<F>>,
     running: AtomicBool,
     pending: AtomicBool,
+    #[cfg(feature = "executor-stats")]
+    stats: Counters,
 }
 
 unsafe impl<F: Future + 'static> Sync for AsyncTaskExecutor<F> {}
@@ -225,6 +381,8 @@ impl<F: Future + 'static> AsyncTaskExecutor<F> {
             task: UnsafeCell::new(MaybeUninit::uninit()),
             running: AtomicBool::new(false),
             pending: AtomicBool::new(false),
+            #[cfg(feature = "executor-stats")]
+            stats: Counters::new(),
         }
     }
 
@@ -234,6 +392,12 @@ impl<F: Future + 'static> AsyncTaskExecutor<F> {
         self.running.load(Ordering::Relaxed)
     }
 
+    /// Check if the task is running and has been woken, so that `poll` will poll it.
+    #[inline(always)]
+    pub fn is_ready(&self) -> bool {
+        self.is_running() && self.pending.load(Ordering::Relaxed)
+    }
+
     /// Checks if a waker has pended the executor and simultaneously clears the flag.
     #[inline(always)]
     fn check_and_clear_pending(&self) -> bool {
@@ -246,6 +410,7 @@ impl<F: Future + 'static> AsyncTaskExecutor<F> {
     // Used by wakers to indicate that the executor needs to run.
     #[inline(always)]
     pub fn set_pending(&self) {
+        count!(self, wakeups);
         self.pending.store(true, Ordering::Release);
     }
 
@@ -253,9 +418,86 @@ impl<F: Future + 'static> AsyncTaskExecutor<F> {
     #[inline(always)]
     pub unsafe fn try_allocate(&self) -> bool {
         // Try to reserve the executor for a future.
-        self.running
+        let allocated = self
+            .running
             .compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed)
-            .is_ok()
+            .is_ok();
+        if !allocated {
+            count!(self, failed_allocations);
+        }
+
+        allocated
+    }
+
+    /// Wait until the executor is free and allocate it. To use with `spawn`.
+    ///
+    /// Waiters are woken one at a time, in FIFO order, as the task returns. A plain `spawn` may
+    /// still take the executor before a woken waiter gets to run, which then queues up again.
+    /// `waiters` must be the queue passed to `poll`.
+    pub async fn allocate(&self, waiters: &WaitQueue) {
+        let mut link_ptr: Option<Link<Waker>> = None;
+
+        // SAFETY(link_ptr): Shadow the original definition of `link_ptr` so we can't abuse it.
+        let mut link_ptr = LinkPtr(&mut link_ptr as *mut Option<Link<Waker>>);
+
+        let mut link_ptr2 = link_ptr.clone();
+        // Only runs if this future is dropped before the executor was allocated.
+        let dropper = OnDrop::new(|| {
+            // SAFETY: We only run this closure and dereference the pointer if we have
+            // exited the `poll_fn` below. The other dereference of this pointer is in the
+            // `poll_fn`.
+            if let Some(link) = unsafe { link_ptr2.get() } {
+                if link.is_popped() {
+                    // Woken, but gone without taking the executor: pass the wake-up on.
+                    Self::wake_waiter(Some(waiters));
+                } else {
+                    link.remove_from_list(waiters);
+                }
+            }
+        });
+
+        poll_fn(|cx| {
+            // The critical section orders this against `poll`, so the executor can't become
+            // free between a failed `try_allocate` and the `push` without this waiter being
+            // woken.
+            critical_section::with(|_| {
+                if unsafe { self.try_allocate() } {
+                    return Poll::Ready(());
+                }
+
+                // SAFETY: This pointer is only dereferenced here and on drop of the future
+                // which happens outside this `poll_fn`'s stack frame.
+                let link = unsafe { link_ptr.get() };
+                if link.as_ref().is_none_or(Link::is_popped) {
+                    // Queue up on the first poll, or again if someone else took the executor
+                    // after this waiter was woken.
+                    let link_ref = link.insert(Link::new(cx.waker().clone()));
+
+                    // SAFETY(new_unchecked): The address to the link is stable as it is defined
+                    // outside this stack frame.
+                    // SAFETY(push): `link_ref` lifetime comes from `link_ptr` that is shadowed,
+                    // and the link is removed from the queue before the end of this function.
+                    unsafe { waiters.push(Pin::new_unchecked(link_ref)) };
+                }
+
+                Poll::Pending
+            })
+        })
+        .await;
+
+        dropper.defuse();
+
+        // Leave the queue if the executor was allocated without a wake-up.
+        if let Some(link) = unsafe { link_ptr.get() } {
+            link.remove_from_list(waiters);
+        }
+    }
+
+    #[inline(always)]
+    fn wake_waiter(waiters: Option<&WaitQueue>) {
+        if let Some(waker) = waiters.and_then(WaitQueue::pop) {
+            waker.wake();
+        }
     }
 
     /// Spawn a future
@@ -265,28 +507,56 @@ impl<F: Future + 'static> AsyncTaskExecutor<F> {
         unsafe {
             self.task.get().write(MaybeUninit::new(future));
         }
+        count!(self, spawns);
         self.set_pending();
     }
 
     #[inline(always)]
-    pub const fn waker(&self, wake: fn()) -> Waker {
-        unsafe { Waker::from_raw(RawWaker::new(wake as *const (), &WAKER_VTABLE)) }
+    pub const fn waker(&self, waker: &'static TaskWaker) -> Waker {
+        let data = ptr::from_ref(waker).cast::<()>();
+        unsafe { Waker::from_raw(RawWaker::new(data, &WAKER_VTABLE)) }
+    }
+
+    /// Forget the task of a previous simulation, leaking its future.
+    #[cfg(implementation = "sim")]
+    pub fn reset(&self, waiters: Option<&WaitQueue>) {
+        // The waiters live in the futures of other tasks, leaked as well.
+        while waiters.and_then(WaitQueue::pop).is_some() {}
+        self.running.store(false, Ordering::Relaxed);
+        self.pending.store(false, Ordering::Relaxed);
+        #[cfg(feature = "executor-stats")]
+        self.stats.reset();
     }
 
-    /// Poll the future in the executor.
+    /// The counters of the executor.
+    #[cfg(feature = "executor-stats")]
+    pub fn stats(&self) -> crate::stats::TaskStats {
+        self.stats.snapshot()
+    }
+
+    /// Poll the future in the executor, waking the next of `waiters` once the task returns.
     #[inline(always)]
-    pub fn poll(&self, wake: fn()) {
-        if self.is_running() && self.check_and_clear_pending() {
-            let waker = self.waker(wake);
+    pub fn poll(&self, waker: &'static TaskWaker, waiters: Option<&WaitQueue>) {
+        if !self.is_running() {
+            return;
+        }
+
+        if self.check_and_clear_pending() {
+            let waker = self.waker(waker);
             let mut cx = Context::from_waker(&waker);
             let future = unsafe { Pin::new_unchecked(&mut *(self.task.get() as *mut F)) };
 
+            count!(self, polls);
             match future.poll(&mut cx) {
                 Poll::Ready(_) => {
+                    count!(self, completions);
                     self.running.store(false, Ordering::Release);
+                    Self::wake_waiter(waiters);
                 }
                 Poll::Pending => {}
             }
+        } else {
+            count!(self, spurious_wakeups);
         }
     }
 }
@@ -0,0 +1,136 @@
+//! The record of the running tasks and the fault handler of `#[on_fault]`, see [`crate::fault`]
+
+use super::atomic::{AtomicU16, Ordering};
+use crate::fault::{ActiveTask, FaultFrame};
+use core::ops::Deref;
+
+/// `#[app]` rejects `#[on_fault]` at compile time through this constant.
+pub const SUPPORTED: bool = cfg!(any(
+    implementation = "cortex-m-basepri",
+    implementation = "cortex-m-source-masking"
+));
+
+unsafe extern "Rust" {
+    /// Defined by `#[app]`, calls the `#[on_fault]` function.
+    fn __rtic_on_fault(frame: &FaultFrame) -> !;
+}
+
+/// HardFault handler, `#[app]` points the vector table here.
+///
+/// Passes the frame stacked on the main or process stack, depending on EXC_RETURN, to the app.
+#[cfg(all(
+    any(
+        implementation = "cortex-m-basepri",
+        implementation = "cortex-m-source-masking"
+    ),
+    target_arch = "arm"
+))]
+#[unsafe(naked)]
+pub unsafe extern "C" fn hard_fault() -> ! {
+    core::arch::naked_asm!(
+        "mov r0, lr",
+        "movs r1, #4",
+        "tst r0, r1",
+        "bne 0f",
+        "mrs r0, MSP",
+        "bl {on_fault}",
+        "0:",
+        "mrs r0, PSP",
+        "bl {on_fault}",
+        on_fault = sym on_fault,
+    )
+}
+
+#[cfg(not(all(
+    any(
+        implementation = "cortex-m-basepri",
+        implementation = "cortex-m-source-masking"
+    ),
+    target_arch = "arm"
+)))]
+pub unsafe extern "C" fn hard_fault() -> ! {
+    unreachable!()
+}
+
+#[allow(dead_code)]
+extern "C" fn on_fault(frame: &FaultFrame) -> ! {
+    // SAFETY: only referenced from the handler `#[app]` emits together with the hook.
+    unsafe { __rtic_on_fault(frame) }
+}
+
+/// The tasks running at the `N` priority levels of an app, as recorded by `#[app]`.
+pub struct RunningTasks<const N: usize> {
+    /// The task running at each level, `NONE` when there is none
+    tasks: [AtomicU16; N],
+    priorities: [u8; N],
+    names: &'static [&'static str],
+}
+
+const NONE: u16 = u16::MAX;
+
+impl<const N: usize> RunningTasks<N> {
+    /// The levels of `priorities`, for the tasks of `names`.
+    pub const fn new(priorities: [u8; N], names: &'static [&'static str]) -> Self {
+        RunningTasks {
+            tasks: [const { AtomicU16::new(NONE) }; N],
+            priorities,
+            names,
+        }
+    }
+
+    /// Marks `task` as running at `level`.
+    #[inline(always)]
+    pub fn enter(&self, level: usize, task: u16) {
+        // Only tasks of the same level write its entry, and they don't preempt each other
+        self.tasks[level].store(task, Ordering::Relaxed);
+    }
+
+    /// Marks the task running at `level` as done.
+    #[inline(always)]
+    pub fn exit(&self, level: usize) {
+        self.tasks[level].store(NONE, Ordering::Relaxed);
+    }
+
+    /// The tasks currently running, from the lowest priority level to the highest.
+    pub fn active(&self) -> ActiveTasks<N> {
+        let mut active = ActiveTasks {
+            tasks: [ActiveTask {
+                priority: 0,
+                name: "",
+            }; N],
+            len: 0,
+        };
+
+        for (task, &priority) in self.tasks.iter().zip(&self.priorities) {
+            let task = task.load(Ordering::Relaxed);
+            let Some(&name) = self.names.get(usize::from(task)) else {
+                continue;
+            };
+
+            // Levels given as constant expressions are not sorted by priority
+            let mut i = active.len;
+            while i > 0 && active.tasks[i - 1].priority > priority {
+                active.tasks[i] = active.tasks[i - 1];
+                i -= 1;
+            }
+            active.tasks[i] = ActiveTask { priority, name };
+            active.len += 1;
+        }
+
+        active
+    }
+}
+
+/// The stack of tasks running when the fault was taken.
+pub struct ActiveTasks<const N: usize> {
+    tasks: [ActiveTask; N],
+    len: usize,
+}
+
+impl<const N: usize> Deref for ActiveTasks<N> {
+    type Target = [ActiveTask];
+
+    fn deref(&self) -> &[ActiveTask] {
+        &self.tasks[..self.len]
+    }
+}
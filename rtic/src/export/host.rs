@@ -0,0 +1,311 @@
+//! Simulated interrupt controller of the host backend
+//!
+//! Interrupts are flags in a global state, serviced by the scheduler thread: it runs the handler
+//! of the highest priority pending interrupt whenever that priority is above the priority the app
+//! currently runs at. Handlers nest on the scheduler thread, so a running handler is preempted
+//! when it calls into the backend: when it pends an interrupt or releases a lock.
+//!
+//! `#[init]` and `#[idle]` run on a thread of their own, at priority 0. Handlers run concurrently
+//! with `#[idle]`, which is as if it was preempted; its locks wait for the handlers to return.
+
+use std::{
+    cell::Cell,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+    vec::Vec,
+};
+
+pub use crate::host::InterruptNumber;
+
+#[derive(Clone, Copy)]
+struct Vector {
+    priority: u8,
+    handler: unsafe fn(),
+}
+
+struct State {
+    started: bool,
+    enabled: bool,
+    panicked: bool,
+    /// The enabled interrupts, by number
+    vectors: Vec<Option<Vector>>,
+    pending: Vec<bool>,
+    /// Priority of the scheduler thread, raised by the handlers it runs and their locks
+    running: u8,
+    /// Number of handlers running on the scheduler thread
+    depth: usize,
+    /// Ceiling of the locks taken by `#[idle]`
+    idle_ceiling: u8,
+    /// Number of handlers that have returned
+    serviced: usize,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    started: false,
+    enabled: false,
+    panicked: false,
+    vectors: Vec::new(),
+    pending: Vec::new(),
+    running: 0,
+    depth: 0,
+    idle_ceiling: 0,
+    serviced: 0,
+});
+
+/// Notified on every change of `STATE`
+static CHANGED: Condvar = Condvar::new();
+
+std::thread_local! {
+    static SCHEDULER: Cell<bool> = const { Cell::new(false) };
+}
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn wait(state: MutexGuard<'static, State>) -> MutexGuard<'static, State> {
+    CHANGED.wait(state).unwrap_or_else(PoisonError::into_inner)
+}
+
+impl State {
+    /// The highest priority pending interrupt that can preempt the scheduler thread
+    fn next(&self) -> Option<(usize, Vector)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let ceiling = self.running.max(self.idle_ceiling);
+        let mut next: Option<(usize, Vector)> = None;
+        for (number, pending) in self.pending.iter().enumerate() {
+            let Some(vector) = self.vectors.get(number).copied().flatten() else {
+                continue;
+            };
+
+            // On equal priorities the lowest interrupt number goes first
+            if *pending
+                && vector.priority > ceiling
+                && next.is_none_or(|(_, next)| vector.priority > next.priority)
+            {
+                next = Some((number, vector));
+            }
+        }
+
+        next
+    }
+
+    /// Is any enabled interrupt pending
+    fn any_pending(&self) -> bool {
+        self.pending
+            .iter()
+            .enumerate()
+            .any(|(number, pending)| *pending && matches!(self.vectors.get(number), Some(Some(_))))
+    }
+}
+
+/// Runs the handlers that can preempt the scheduler thread, on the scheduler thread
+fn service(mut state: MutexGuard<'static, State>) -> MutexGuard<'static, State> {
+    while let Some((number, vector)) = state.next() {
+        state.pending[number] = false;
+        let interrupted = state.running;
+        state.running = vector.priority;
+        state.depth += 1;
+        drop(state);
+
+        unsafe { (vector.handler)() };
+
+        state = self::state();
+        state.running = interrupted;
+        state.depth -= 1;
+        state.serviced = state.serviced.wrapping_add(1);
+        CHANGED.notify_all();
+    }
+
+    state
+}
+
+/// Records a panic of the thread it is dropped on
+struct PanicGuard;
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            state().panicked = true;
+            CHANGED.notify_all();
+        }
+    }
+}
+
+fn scheduler() {
+    SCHEDULER.set(true);
+    let _guard = PanicGuard;
+
+    let mut state = state();
+    loop {
+        state = service(state);
+        state = wait(state);
+    }
+}
+
+/// Starts the app on threads of its own and returns once `#[init]` has run.
+///
+/// `main` is the `main` function generated in the app module.
+///
+/// # Panics
+///
+/// If an app has already been started in this process, or if the app panics before interrupts
+/// are enabled.
+pub fn start(main: unsafe fn() -> !) {
+    let mut state = state();
+    assert!(
+        !state.started,
+        "an app has already been started in this process"
+    );
+    state.started = true;
+    drop(state);
+
+    thread::Builder::new()
+        .name("rtic-scheduler".into())
+        .spawn(scheduler)
+        .expect("failed to spawn the scheduler thread");
+    thread::Builder::new()
+        .name("rtic-main".into())
+        .spawn(move || {
+            let _guard = PanicGuard;
+            unsafe { main() }
+        })
+        .expect("failed to spawn the main thread");
+
+    // Return once `#[init]` has run
+    let mut state = self::state();
+    while !state.enabled && !state.panicked {
+        state = wait(state);
+    }
+    assert!(
+        !state.panicked,
+        "the app panicked before enabling interrupts"
+    );
+}
+
+/// Blocks until no handler is running or pending.
+///
+/// # Panics
+///
+/// If a task of the app has panicked.
+pub fn wait_idle() {
+    let mut state = state();
+    loop {
+        assert!(!state.panicked, "a task of the app has panicked");
+
+        if state.enabled && state.depth == 0 && !state.any_pending() {
+            return;
+        }
+
+        state = wait(state);
+    }
+}
+
+/// Blocks until the next handler returns, for use in `#[idle]` instead of a busy loop.
+pub fn wfi() {
+    let mut state = state();
+    let serviced = state.serviced;
+    while state.serviced == serviced {
+        state = wait(state);
+    }
+}
+
+/// Sets the priority of `interrupt` and enables it
+pub fn enable_interrupt(interrupt: impl InterruptNumber, priority: u8, handler: unsafe fn()) {
+    let number = usize::from(interrupt.number());
+    let mut state = state();
+    if state.vectors.len() <= number {
+        state.vectors.resize(number + 1, None);
+    }
+    state.vectors[number] = Some(Vector { priority, handler });
+}
+
+/// Pends `interrupt`.
+///
+/// Called from a handler, the handlers that preempt it run before `pend` returns; from any other
+/// thread the interrupt is serviced by the scheduler thread.
+pub fn pend(interrupt: impl InterruptNumber) {
+    let number = usize::from(interrupt.number());
+    let mut state = state();
+    if state.pending.len() <= number {
+        state.pending.resize(number + 1, false);
+    }
+    state.pending[number] = true;
+
+    if SCHEDULER.get() {
+        drop(service(state));
+    } else {
+        CHANGED.notify_all();
+    }
+}
+
+#[inline(always)]
+pub fn run<F>(_priority: u8, f: F)
+where
+    F: FnOnce(),
+{
+    // The scheduler thread runs at the priority of the handler until it returns
+    f();
+}
+
+/// Lock implementation raising the simulated priority ceiling
+///
+/// # Safety
+///
+/// On the scheduler thread the ceiling of the running handler is raised, which keeps handlers
+/// with a priority up to `ceiling` from being serviced until the lock is released. From `#[idle]`
+/// the lock first waits for all handlers to return, as `#[idle]` would not run on hardware while
+/// they do.
+#[inline(always)]
+pub unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+    if SCHEDULER.get() {
+        let mut state = state();
+        let current = state.running;
+        state.running = current.max(ceiling);
+        drop(state);
+
+        let r = f(unsafe { &mut *ptr });
+
+        let mut state = self::state();
+        state.running = current;
+        drop(service(state));
+        r
+    } else {
+        let mut state = state();
+        while state.depth > 0 {
+            state = wait(state);
+        }
+        let current = state.idle_ceiling;
+        state.idle_ceiling = current.max(ceiling);
+        drop(state);
+
+        let r = f(unsafe { &mut *ptr });
+
+        state = self::state();
+        state.idle_ceiling = current;
+        CHANGED.notify_all();
+        r
+    }
+}
+
+pub mod interrupt {
+    use super::{CHANGED, state};
+
+    /// Keeps the scheduler thread from servicing interrupts
+    pub fn disable() {
+        state().enabled = false;
+    }
+
+    /// Lets the scheduler thread service interrupts
+    ///
+    /// # Safety
+    ///
+    /// Called once all resources have been initialized.
+    pub unsafe fn enable() {
+        state().enabled = true;
+        CHANGED.notify_all();
+    }
+}
@@ -0,0 +1,227 @@
+//! Memory protection for `#[app(mpu = true)]`
+//!
+//! All local and shared resources live in one container, made of a [`Block`] per task holding its
+//! local resources and a [`Block`] per shared resource. Each task has a [`Domain`] listing the
+//! blocks it may access, and the MPU is reprogrammed with the domain of the task about to run.
+//!
+//! - ARMv7-M: one region denies all access to the container, higher-numbered (and thus higher
+//!   precedence) regions grant access to the blocks of the running task.
+//! - ARMv8-M: regions can't overlap and can't deny reads, so the parts of the container between
+//!   the blocks of the running task are made read-only.
+//!
+//! Memory outside the container keeps the default memory map.
+
+use super::atomic::{AtomicPtr, Ordering};
+use super::executor::{Align, Alignment};
+use core::cell::UnsafeCell;
+use core::ops::Range;
+use core::ptr;
+
+/// Number of MPU regions used, the minimum any ARMv7-M or ARMv8-M MPU implements.
+pub const REGIONS: usize = 8;
+
+const MPU_CTRL: *mut u32 = 0xE000_ED94 as *mut u32;
+const MPU_RNR: *mut u32 = 0xE000_ED98 as *mut u32;
+const MPU_RBAR: *mut u32 = 0xE000_ED9C as *mut u32;
+/// `MPU_RASR` on ARMv7-M, `MPU_RLAR` on ARMv8-M
+const MPU_RASR_RLAR: *mut u32 = 0xE000_EDA0 as *mut u32;
+#[cfg(feature = "thumbv8main-backend")]
+const MPU_MAIR0: *mut u32 = 0xE000_EDC0 as *mut u32;
+
+const CTRL_ENABLE: u32 = 1 << 0;
+const CTRL_PRIVDEFENA: u32 = 1 << 2;
+
+/// Alignment of a [`Block`] holding a `T`.
+///
+/// ARMv7-M regions are a power of two in size and aligned to it, ARMv8-M regions have a 32 byte
+/// granularity.
+pub const fn block_align<T>() -> usize {
+    let align = align_of::<T>();
+
+    #[cfg(feature = "thumbv7-backend")]
+    let align = {
+        let size = size_of::<T>().next_power_of_two();
+        if size > align { size } else { align }
+    };
+
+    if align > 32 { align } else { 32 }
+}
+
+/// Storage that can be covered by exactly one MPU region.
+#[repr(C)]
+pub struct Block<T, const ALIGN: usize>
+where
+    Align<ALIGN>: Alignment,
+{
+    data: T,
+    align: Align<ALIGN>,
+}
+
+impl<T, const ALIGN: usize> Block<T, ALIGN>
+where
+    Align<ALIGN>: Alignment,
+{
+    pub const fn new(data: T) -> Self {
+        Block {
+            data,
+            align: Align::new(),
+        }
+    }
+
+    pub const fn get(&self) -> &T {
+        &self.data
+    }
+
+    /// Addresses covered by this block.
+    pub fn range(&self) -> Range<usize> {
+        let start = ptr::from_ref(self) as usize;
+        start..start + size_of::<Self>()
+    }
+}
+
+/// MPU configuration of one task, as `(RBAR, RASR)` or `(RBAR, RLAR)` register pairs.
+pub struct Domain {
+    regions: UnsafeCell<[(u32, u32); REGIONS]>,
+}
+
+unsafe impl Sync for Domain {}
+
+/// Domain the MPU is currently programmed with.
+static CURRENT: AtomicPtr<Domain> = AtomicPtr::new(ptr::null_mut());
+
+impl Domain {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Domain {
+            regions: UnsafeCell::new([(0, 0); REGIONS]),
+        }
+    }
+
+    /// Computes the regions protecting `container` except for `grants`.
+    ///
+    /// # Safety
+    ///
+    /// Must be called before [`enable`], `grants` must be sorted by address and lie within
+    /// `container`, and there can be no more than `REGIONS - 1` of them.
+    pub unsafe fn configure<C, const ALIGN: usize>(
+        &self,
+        container: &Block<C, ALIGN>,
+        grants: &[Range<usize>],
+    ) where
+        Align<ALIGN>: Alignment,
+    {
+        let regions = unsafe { &mut *self.regions.get() };
+        let container = container.range();
+
+        #[cfg(feature = "thumbv7-backend")]
+        {
+            // Normal memory, write-back, read and write allocate, never executable
+            const RASR_ATTRS: u32 = (1 << 28) | (0b001 << 19) | (1 << 17) | (1 << 16);
+            const AP_NO_ACCESS: u32 = 0b000 << 24;
+            const AP_FULL_ACCESS: u32 = 0b011 << 24;
+
+            let rasr = |range: &Range<usize>, ap: u32| {
+                let size_field = (range.len().trailing_zeros() - 1) << 1;
+                RASR_ATTRS | ap | size_field | 1
+            };
+
+            regions[0] = (container.start as u32, rasr(&container, AP_NO_ACCESS));
+
+            for (region, grant) in regions[1..].iter_mut().zip(grants) {
+                *region = (grant.start as u32, rasr(grant, AP_FULL_ACCESS));
+            }
+        }
+
+        #[cfg(feature = "thumbv8main-backend")]
+        {
+            // Read-only at privileged level, never executable
+            const RBAR_ATTRS: u32 = (0b10 << 1) | 1;
+
+            let mut denied = regions.iter_mut();
+            let mut deny = |start: usize, end: usize| {
+                if end > start {
+                    let region = denied.next().expect("too many MPU regions");
+                    *region = (start as u32 | RBAR_ATTRS, (end - 32) as u32 | 1);
+                }
+            };
+
+            let mut start = container.start;
+
+            for grant in grants {
+                deny(start, grant.start);
+                start = grant.end;
+            }
+
+            deny(start, container.end);
+        }
+    }
+
+    /// Writes the regions to the MPU, must run in a critical section.
+    fn apply(&self) {
+        let regions = unsafe { &*self.regions.get() };
+
+        for (number, &(rbar, rasr_rlar)) in regions.iter().enumerate() {
+            unsafe {
+                ptr::write_volatile(MPU_RNR, number as u32);
+                ptr::write_volatile(MPU_RBAR, rbar);
+                ptr::write_volatile(MPU_RASR_RLAR, rasr_rlar);
+            }
+        }
+
+        cortex_m::asm::dsb();
+        cortex_m::asm::isb();
+    }
+}
+
+/// Programs the MPU with `domain`, returns the domain that was active before.
+///
+/// # Safety
+///
+/// The returned domain must be passed to [`restore`] before the caller returns.
+#[inline(always)]
+pub unsafe fn switch(domain: &'static Domain) -> *const Domain {
+    // A task preempting us between the `MPU_RNR` write and the region writes would leave
+    // `MPU_RNR` pointing at another region, so the whole update runs in a critical section
+    cortex_m::interrupt::free(|_| {
+        let previous = CURRENT.load(Ordering::Relaxed);
+        CURRENT.store(ptr::from_ref(domain).cast_mut(), Ordering::Relaxed);
+        domain.apply();
+        previous
+    })
+}
+
+/// Programs the MPU with the domain [`switch`] returned.
+///
+/// # Safety
+///
+/// Must be paired with [`switch`].
+#[inline(always)]
+pub unsafe fn restore(previous: *const Domain) {
+    cortex_m::interrupt::free(|_| {
+        CURRENT.store(previous.cast_mut(), Ordering::Relaxed);
+
+        if let Some(domain) = unsafe { previous.as_ref() } {
+            domain.apply();
+        }
+    });
+}
+
+/// Enables the MPU with `domain` active.
+///
+/// # Safety
+///
+/// Every [`Domain`] must have been configured, and all resources initialized.
+pub unsafe fn enable(domain: &'static Domain) {
+    unsafe {
+        // Attribute 0, used by all regions: normal memory, write-back, read and write allocate
+        #[cfg(feature = "thumbv8main-backend")]
+        ptr::write_volatile(MPU_MAIR0, (ptr::read_volatile(MPU_MAIR0) & !0xFF) | 0xFF);
+
+        switch(domain);
+
+        ptr::write_volatile(MPU_CTRL, CTRL_ENABLE | CTRL_PRIVDEFENA);
+    }
+
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+}
@@ -0,0 +1,112 @@
+//! The seal of `#[persistent]` resources
+//!
+//! A persistent resource lives in a `.uninit` section, next to a [`Seal`] holding a magic number,
+//! generated by `#[app]` from the name and type of the resource, and a CRC of the magic, the size
+//! and the bytes of the resource. After a reset the resource is handed to `#[init]` only if both
+//! match, otherwise the memory is taken to hold garbage. The type of the resource implements
+//! [`Persistent`], so whatever survived the reset is a valid value and all of its bytes are
+//! covered by the CRC.
+//!
+//! [`Persistent`]: crate::persistent::Persistent
+
+use core::{
+    cell::UnsafeCell,
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+/// The magic number and CRC of a persistent resource, kept across resets.
+pub struct Seal {
+    words: UnsafeCell<MaybeUninit<[u32; 2]>>,
+}
+
+unsafe impl Sync for Seal {}
+
+impl Seal {
+    /// Placed in a `.uninit` section, so the value is only a placeholder.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Seal {
+            words: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// The resource, if it was sealed before the reset and hasn't changed since.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be the storage of the resource this seal belongs to, and must not be
+    /// accessed otherwise while the returned reference lives. `T` must implement
+    /// `Persistent`, which `#[app]` asserts next to the seal.
+    #[inline(always)]
+    pub unsafe fn restore<'a, T>(
+        &self,
+        magic: u32,
+        value: *mut MaybeUninit<T>,
+    ) -> Option<&'a mut T> {
+        let words = self.words.get().cast::<u32>();
+        let (seal_magic, seal_crc) =
+            unsafe { (words.read_volatile(), words.add(1).read_volatile()) };
+
+        if seal_magic == magic && seal_crc == unsafe { crc::<T>(magic, value.cast()) } {
+            // Any bit pattern is a valid `T`, the CRC only tells a sealed value apart from garbage
+            Some(unsafe { (*value).assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Seals the current value of the resource.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be the initialized storage of the resource this seal belongs to, and nothing
+    /// may write to it or to the seal meanwhile. `T` must implement `Persistent`.
+    #[inline(always)]
+    pub unsafe fn seal<T>(&self, magic: u32, value: *const T) {
+        let words = self.words.get().cast::<u32>();
+
+        unsafe {
+            // Invalidate the seal first, so a reset in between doesn't leave a stale CRC behind
+            words.write_volatile(!magic);
+            words.add(1).write_volatile(crc::<T>(magic, value));
+            words.write_volatile(magic);
+        }
+    }
+}
+
+/// Lookup table of the CRC-32 (IEEE 802.3) polynomial, one entry per byte value.
+static TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < table.len() {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE 802.3) of `magic`, the size of `T` and the bytes of `*value`.
+unsafe fn crc<T>(magic: u32, value: *const T) -> u32 {
+    fn update(crc: u32, byte: u8) -> u32 {
+        (crc >> 8) ^ TABLE[((crc ^ u32::from(byte)) & 0xff) as usize]
+    }
+
+    let header = magic.to_le_bytes().into_iter();
+    let size = (mem::size_of::<T>() as u32).to_le_bytes().into_iter();
+    let mut crc = header.chain(size).fold(!0, update);
+
+    let bytes = value.cast::<u8>();
+    for i in 0..mem::size_of::<T>() {
+        // Volatile, as the bytes may be left over from before the reset
+        crc = update(crc, unsafe { ptr::read_volatile(bytes.add(i)) });
+    }
+
+    !crc
+}
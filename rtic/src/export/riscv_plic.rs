@@ -0,0 +1,247 @@
+//! Backend for RISC-V devices routing their interrupts through a standard PLIC
+//!
+//! Hardware tasks are bound to PLIC sources, whose PLIC priority is the priority of the task, and
+//! the PLIC threshold of the context of the app is the priority the app currently runs at: the
+//! handler of a source raises it to the priority of the source, and `lock` to the ceiling.
+//!
+//! The PLIC can't pend its sources from software, so the dispatchers of software tasks are
+//! software interrupts of the backend. Pending one runs it right away when its priority is above
+//! the threshold, and otherwise as soon as the threshold drops below its priority: when a lock is
+//! released or a handler returns.
+
+use core::sync::atomic::{AtomicU8, AtomicU32, Ordering, compiler_fence};
+use riscv::register::{mie, mstatus};
+
+/// Offset of the priority registers of the sources
+const PRIORITY: usize = 0x0;
+/// Offset of the enable bits of the sources for context 0, then every `ENABLE_STRIDE` bytes
+const ENABLE: usize = 0x2000;
+const ENABLE_STRIDE: usize = 0x80;
+/// Offset of the threshold register of context 0, followed by its claim/complete register
+const THRESHOLD: usize = 0x20_0000;
+const CONTEXT_STRIDE: usize = 0x1000;
+
+/// The PLIC context of an app and its `N` software interrupts
+pub struct Plic<const N: usize> {
+    base: usize,
+    context: usize,
+    /// Pending software interrupts, one bit per interrupt number
+    pending: AtomicU32,
+    /// Priority of each software interrupt, 0 until set
+    priorities: [AtomicU8; N],
+    handlers: [unsafe fn(); N],
+}
+
+impl<const N: usize> Plic<N> {
+    /// The PLIC at `base`, serving the app on `context`, with the software interrupts `handlers`
+    pub const fn new(base: usize, context: usize, handlers: [unsafe fn(); N]) -> Self {
+        assert!(
+            N <= 32,
+            "the PLIC backend supports up to 32 software interrupts"
+        );
+
+        Plic {
+            base,
+            context,
+            pending: AtomicU32::new(0),
+            priorities: [const { AtomicU8::new(0) }; N],
+            handlers,
+        }
+    }
+
+    #[inline(always)]
+    fn register(&self, offset: usize) -> *mut u32 {
+        (self.base + offset) as *mut u32
+    }
+
+    #[inline(always)]
+    fn threshold_register(&self) -> *mut u32 {
+        self.register(THRESHOLD + self.context * CONTEXT_STRIDE)
+    }
+
+    #[inline(always)]
+    fn claim_register(&self) -> *mut u32 {
+        self.register(THRESHOLD + self.context * CONTEXT_STRIDE + 4)
+    }
+
+    /// The priority the app runs at
+    #[inline(always)]
+    fn threshold(&self) -> u8 {
+        unsafe { self.threshold_register().read_volatile() as u8 }
+    }
+
+    #[inline(always)]
+    fn set_threshold(&self, threshold: u8) {
+        unsafe {
+            self.threshold_register()
+                .write_volatile(u32::from(threshold))
+        };
+        // The PLIC masks the sources up to `threshold` before the caller goes on
+        riscv::asm::fence();
+    }
+
+    /// Sets the priority of the PLIC source `source` and enables it for the context of the app.
+    ///
+    /// # Safety
+    ///
+    /// Called with interrupts disabled, before the handler of the source can run.
+    pub unsafe fn enable_source(&self, source: u16, priority: u8) {
+        let source = usize::from(source);
+        let enable = self.register(ENABLE + self.context * ENABLE_STRIDE + 4 * (source / 32));
+
+        unsafe {
+            self.register(PRIORITY + 4 * source)
+                .write_volatile(u32::from(priority));
+            enable.write_volatile(enable.read_volatile() | (1 << (source % 32)));
+        }
+    }
+
+    /// Sets the priority of the software interrupt `interrupt`
+    #[inline]
+    pub fn set_priority(&self, interrupt: u16, priority: u8) {
+        self.priorities[usize::from(interrupt)].store(priority, Ordering::Relaxed);
+    }
+
+    /// Pends the software interrupt `interrupt`, which runs before `pend` returns if its priority
+    /// is above the current one and interrupts are enabled.
+    pub fn pend(&self, interrupt: u16) {
+        riscv::interrupt::free(|| {
+            let pending = self.pending.load(Ordering::Relaxed);
+            self.pending
+                .store(pending | (1 << interrupt), Ordering::Relaxed);
+        });
+
+        if mstatus::read().mie() {
+            unsafe { self.run_pending() };
+        }
+    }
+
+    /// Takes the highest priority pending software interrupt above the threshold
+    fn take_next(&self) -> Option<(usize, u8)> {
+        riscv::interrupt::free(|| {
+            let pending = self.pending.load(Ordering::Relaxed);
+            let threshold = self.threshold();
+
+            let mut next: Option<(usize, u8)> = None;
+            for interrupt in (0..N).filter(|i| pending & (1 << i) != 0) {
+                let priority = self.priorities[interrupt].load(Ordering::Relaxed);
+
+                // On equal priorities the lowest interrupt number goes first
+                if priority > threshold && next.is_none_or(|(_, next)| priority > next) {
+                    next = Some((interrupt, priority));
+                }
+            }
+
+            if let Some((interrupt, _)) = next {
+                self.pending
+                    .store(pending & !(1 << interrupt), Ordering::Relaxed);
+            }
+
+            next
+        })
+    }
+
+    /// Runs the pending software interrupts above the threshold, each at its priority.
+    ///
+    /// # Safety
+    ///
+    /// Called with interrupts enabled, where the app may be preempted.
+    unsafe fn run_pending(&self) {
+        while let Some((interrupt, priority)) = self.take_next() {
+            let previous = self.threshold();
+            self.set_threshold(priority);
+
+            unsafe { (self.handlers[interrupt])() };
+
+            self.set_threshold(previous);
+        }
+    }
+
+    /// Claims the pending PLIC source of the highest priority and runs `handler` for it at the
+    /// priority of the source, then runs the software interrupts it pended.
+    ///
+    /// # Safety
+    ///
+    /// Only called from the machine external interrupt handler.
+    pub unsafe fn external(&self, handler: impl FnOnce(u16)) {
+        // 0 when the source was masked by a threshold raised as the interrupt was taken
+        let source = unsafe { self.claim_register().read_volatile() };
+        if source == 0 {
+            return;
+        }
+
+        let previous = self.threshold();
+        let priority = unsafe {
+            self.register(PRIORITY + 4 * source as usize)
+                .read_volatile()
+        };
+        self.set_threshold(priority as u8);
+
+        // Sources and software interrupts of higher priority preempt the handler
+        unsafe { riscv::interrupt::nested(|| handler(source as u16)) };
+
+        unsafe { self.claim_register().write_volatile(source) };
+        self.set_threshold(previous);
+
+        unsafe { riscv::interrupt::nested(|| self.run_pending()) };
+    }
+
+    /// Lock implementation raising the threshold to `ceiling`
+    ///
+    /// # Safety
+    ///
+    /// `ptr` is only accessed from tasks with a priority up to `ceiling`.
+    #[inline(always)]
+    pub unsafe fn lock<T, R>(&self, ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+        let current = self.threshold();
+        if ceiling <= current {
+            return f(unsafe { &mut *ptr });
+        }
+
+        self.set_threshold(ceiling);
+        compiler_fence(Ordering::SeqCst);
+
+        let r = f(unsafe { &mut *ptr });
+
+        compiler_fence(Ordering::SeqCst);
+        self.set_threshold(current);
+
+        // Run the software interrupts pended while the threshold was raised
+        if mstatus::read().mie() {
+            unsafe { self.run_pending() };
+        }
+
+        r
+    }
+
+    /// Disables interrupts
+    #[inline]
+    pub fn disable(&self) {
+        riscv::interrupt::disable();
+    }
+
+    /// Enables interrupts and runs the software interrupts pended by `#[init]`.
+    ///
+    /// # Safety
+    ///
+    /// Called once all resources have been initialized.
+    pub unsafe fn enable(&self) {
+        self.set_threshold(0);
+
+        unsafe {
+            mie::set_mext();
+            riscv::interrupt::enable();
+            self.run_pending();
+        }
+    }
+}
+
+/// Run a function at the priority of the running handler, which the backend already raised the
+/// threshold to
+#[inline(always)]
+pub fn run<F>(_priority: u8, f: F)
+where
+    F: FnOnce(),
+{
+    f();
+}
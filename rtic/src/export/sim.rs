@@ -0,0 +1,328 @@
+//! Deterministic simulation of the interrupt controller, for the sim backend
+//!
+//! Everything runs on the thread of the simulation: handlers nest on its stack, preempting the
+//! code that was running at one of the points where the generated code calls into the backend.
+//! The points are the start of a handler (`run`) and the acquisition and release of a lock. At
+//! every point the virtual time advances by one tick and the simulation injects the interrupts
+//! its script and schedule ask for.
+
+use std::{
+    boxed::Box,
+    cell::RefCell,
+    format,
+    panic::{self, AssertUnwindSafe},
+    string::String,
+    vec::Vec,
+};
+
+pub use crate::sim::InterruptNumber;
+
+/// The calls into the backend that are points of the schedule
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Point {
+    Run,
+    Lock,
+    Unlock,
+}
+
+impl Point {
+    fn name(self) -> &'static str {
+        match self {
+            Point::Run => "run",
+            Point::Lock => "lock",
+            Point::Unlock => "unlock",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Interrupt {
+    pub number: u16,
+    pub name: String,
+}
+
+impl Interrupt {
+    pub(crate) fn new(interrupt: impl InterruptNumber) -> Self {
+        Interrupt {
+            number: interrupt.number(),
+            name: format!("{interrupt:?}"),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Vector {
+    priority: u8,
+    handler: unsafe fn(),
+    name: String,
+}
+
+/// What a run of the simulation injects
+pub(crate) struct Config {
+    /// Interrupts pended at virtual instants, sorted by instant
+    pub at: Vec<(u64, Interrupt)>,
+    /// Interrupts pended at every lock release
+    pub on_unlock: Vec<Interrupt>,
+    /// Interrupt pended at each point, from the schedule being explored
+    pub schedule: Vec<Option<Interrupt>>,
+    pub max_points: u64,
+}
+
+struct State {
+    config: Config,
+    /// Index of the next entry of `config.at`
+    next_at: usize,
+    enabled: bool,
+    vectors: Vec<Option<Vector>>,
+    pending: Vec<bool>,
+    /// Priority the simulated CPU runs at, raised by handlers and locks
+    running: u8,
+    now: u64,
+    points: u64,
+    trace: Vec<String>,
+}
+
+std::thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// Unwinds the simulation once `#[idle]` waits for an interrupt that will never come
+struct End;
+
+fn with<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    STATE.with_borrow_mut(|state| {
+        f(state
+            .as_mut()
+            .expect("the app must be run by `rtic::sim::Sim`"))
+    })
+}
+
+impl State {
+    fn pend(&mut self, interrupt: &Interrupt, why: &str) {
+        let number = usize::from(interrupt.number);
+        if self.pending.len() <= number {
+            self.pending.resize(number + 1, false);
+        }
+        self.pending[number] = true;
+
+        let line = format!("t={} pend {} ({why})", self.now, interrupt.name);
+        self.trace.push(line);
+    }
+
+    /// Pends the scripted interrupts that are due
+    fn pend_due(&mut self) {
+        while let Some((at, interrupt)) = self.config.at.get(self.next_at) {
+            if *at > self.now {
+                break;
+            }
+            let interrupt = interrupt.clone();
+            self.next_at += 1;
+            self.pend(&interrupt, "script");
+        }
+    }
+
+    /// The highest priority pending interrupt that can preempt the running code
+    fn next(&self) -> Option<(usize, Vector)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut next: Option<(usize, &Vector)> = None;
+        for (number, pending) in self.pending.iter().enumerate() {
+            let Some(Some(vector)) = self.vectors.get(number) else {
+                continue;
+            };
+
+            // On equal priorities the lowest interrupt number goes first
+            if *pending
+                && vector.priority > self.running
+                && next.is_none_or(|(_, next)| vector.priority > next.priority)
+            {
+                next = Some((number, vector));
+            }
+        }
+
+        next.map(|(number, vector)| (number, vector.clone()))
+    }
+}
+
+/// Runs the handlers that preempt the running code
+fn service() {
+    while let Some((vector, interrupted)) = with(|state| {
+        let (number, vector) = state.next()?;
+        state.pending[number] = false;
+        let interrupted = state.running;
+        state.running = vector.priority;
+        let line = format!("t={} run {}", state.now, vector.name);
+        state.trace.push(line);
+        Some((vector, interrupted))
+    }) {
+        unsafe { (vector.handler)() };
+
+        with(|state| state.running = interrupted);
+    }
+}
+
+/// A point of the schedule: advances the virtual time and injects interrupts
+fn point(point: Point) {
+    with(|state| {
+        state.now += 1;
+        let index = state.points;
+        state.points += 1;
+        assert!(
+            state.points <= state.config.max_points,
+            "the app did not wait for interrupts within {} points",
+            state.config.max_points
+        );
+
+        state.pend_due();
+
+        if point == Point::Unlock {
+            for interrupt in state.config.on_unlock.clone() {
+                state.pend(&interrupt, "unlock");
+            }
+        }
+
+        if let Some(Some(interrupt)) = usize::try_from(index)
+            .ok()
+            .and_then(|index| state.config.schedule.get(index))
+            .cloned()
+        {
+            let why = format!("schedule, point {index}: {}", point.name());
+            state.pend(&interrupt, &why);
+        }
+    });
+
+    service();
+}
+
+/// Runs `main` once with `config`, returning its trace and the panic that ended it, if any
+pub(crate) fn simulate(
+    main: unsafe fn() -> !,
+    config: Config,
+) -> (Vec<String>, Option<Box<dyn core::any::Any + Send>>) {
+    STATE.set(Some(State {
+        config,
+        next_at: 0,
+        enabled: false,
+        vectors: Vec::new(),
+        pending: Vec::new(),
+        running: 0,
+        now: 0,
+        points: 0,
+        trace: Vec::new(),
+    }));
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        unsafe { main() };
+    }));
+    let trace = STATE.take().map(|state| state.trace).unwrap_or_default();
+
+    match result {
+        Ok(()) => (trace, None),
+        Err(payload) if payload.is::<End>() => (trace, None),
+        Err(payload) => (trace, Some(payload)),
+    }
+}
+
+/// The virtual time of the running simulation, in ticks.
+pub fn now() -> u64 {
+    with(|state| state.now)
+}
+
+/// Waits for an interrupt, for use in `#[idle]` instead of a busy loop.
+///
+/// Advances the virtual time to the next scripted interrupt. The simulation ends when there is
+/// none left.
+pub fn wfi() {
+    service();
+
+    let more = with(|state| match state.config.at.get(state.next_at) {
+        Some((at, _)) => {
+            state.now = state.now.max(*at);
+            state.pend_due();
+            true
+        }
+        None => false,
+    });
+
+    if !more {
+        panic::resume_unwind(Box::new(End));
+    }
+
+    service();
+}
+
+/// Sets the priority of `interrupt` and enables it
+pub fn enable_interrupt(interrupt: impl InterruptNumber, priority: u8, handler: unsafe fn()) {
+    let interrupt = Interrupt::new(interrupt);
+    with(|state| {
+        let number = usize::from(interrupt.number);
+        if state.vectors.len() <= number {
+            state.vectors.resize(number + 1, None);
+        }
+        state.vectors[number] = Some(Vector {
+            priority,
+            handler,
+            name: interrupt.name,
+        });
+    });
+}
+
+/// Pends `interrupt`, which preempts the caller right away if its priority is higher.
+pub fn pend(interrupt: impl InterruptNumber) {
+    let interrupt = Interrupt::new(interrupt);
+    with(|state| state.pend(&interrupt, "pend"));
+    service();
+}
+
+#[inline(always)]
+pub fn run<F>(_priority: u8, f: F)
+where
+    F: FnOnce(),
+{
+    point(Point::Run);
+    f();
+}
+
+/// Lock implementation raising the simulated priority ceiling
+///
+/// # Safety
+///
+/// Handlers with a priority up to `ceiling` are not serviced until the lock is released. Both the
+/// acquisition and the release are points of the schedule.
+#[inline(always)]
+pub unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+    point(Point::Lock);
+
+    let current = with(|state| {
+        let current = state.running;
+        state.running = current.max(ceiling);
+        current
+    });
+
+    let r = f(unsafe { &mut *ptr });
+
+    with(|state| state.running = current);
+    point(Point::Unlock);
+    r
+}
+
+pub mod interrupt {
+    use super::{service, with};
+
+    /// Masks all interrupts
+    pub fn disable() {
+        with(|state| state.enabled = false);
+    }
+
+    /// Unmasks interrupts, servicing the ones pended by `#[init]`
+    ///
+    /// # Safety
+    ///
+    /// Called once all resources have been initialized.
+    pub unsafe fn enable() {
+        with(|state| state.enabled = true);
+        service();
+    }
+}
@@ -14,3 +14,7 @@ pub mod interrupt {
         unsafe { riscv_slic::enable() };
     }
 }
+
+/// Multi-hart apps, with `backend = { harts = [..] }`
+#[cfg(feature = "riscv-clint-backend")]
+pub mod harts;
@@ -0,0 +1,356 @@
+//! Software interrupt controller of multi-hart apps on the CLINT backend
+//!
+//! Every hart has its own priority threshold and set of pending software interrupts. Pending an
+//! interrupt on a hart sets its bit and raises the machine software interrupt (MSIP) of the hart
+//! through the CLINT, whose handler runs the pending interrupts above the threshold of the hart,
+//! nested by priority like the SLIC does on single-hart apps.
+//!
+//! Resources shared by tasks on different harts are additionally protected by a spin lock, taken
+//! once the priority threshold has been raised to the ceiling. Nested spin locks are taken in the
+//! order the resources are declared in on every hart, otherwise two harts could each wait for the
+//! spin lock the other one holds.
+
+use core::{
+    hint,
+    sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicUsize, Ordering, compiler_fence},
+};
+use riscv_slic::riscv::{
+    self,
+    register::{mhartid, mie, mip},
+};
+
+/// Pends (`true`) or clears (`false`) the MSIP of the hart with the given id
+pub type Msip = unsafe fn(hart: usize, pend: bool);
+
+/// The interrupts state of a hart
+struct Hart {
+    /// Pending interrupts, one bit per interrupt number
+    pending: AtomicU32,
+    /// Only interrupts above this priority preempt the hart
+    threshold: AtomicU8,
+    /// Order of the innermost spin lock held by the hart, 0 if none
+    spin: AtomicUsize,
+}
+
+impl Hart {
+    const fn new() -> Self {
+        Hart {
+            pending: AtomicU32::new(0),
+            threshold: AtomicU8::new(0),
+            spin: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// The software interrupts of the `H` harts of an app, `N` of them.
+pub struct Harts<const H: usize, const N: usize> {
+    /// The hart ids, the first one runs `#[init]` and `#[idle]`
+    ids: [usize; H],
+    harts: [Hart; H],
+    /// The hart each interrupt is pended on by `pend`
+    owners: [usize; N],
+    /// Priority of each interrupt, 0 until set
+    priorities: [AtomicU8; N],
+    handlers: [unsafe fn(); N],
+    msip: Msip,
+    /// Set once `#[init]` has returned, letting the other harts enable their interrupts
+    started: AtomicBool,
+}
+
+impl<const H: usize, const N: usize> Harts<H, N> {
+    /// The interrupts of the harts `ids`, each pended by `pend` on the hart of `owners`.
+    pub const fn new(
+        ids: [usize; H],
+        owners: [usize; N],
+        handlers: [unsafe fn(); N],
+        msip: Msip,
+    ) -> Self {
+        assert!(
+            N <= 32,
+            "multi-hart apps support up to 32 software interrupts"
+        );
+
+        Harts {
+            ids,
+            harts: [const { Hart::new() }; H],
+            owners,
+            priorities: [const { AtomicU8::new(0) }; N],
+            handlers,
+            msip,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    fn hart(&self, id: usize) -> &Hart {
+        let index = self.ids.iter().position(|hart| *hart == id);
+        &self.harts[index.expect("the hart is not used by the app")]
+    }
+
+    fn current(&self) -> (usize, &Hart) {
+        let id = mhartid::read();
+        (id, self.hart(id))
+    }
+
+    /// Is the current hart the one running `#[init]` and `#[idle]`
+    #[inline]
+    pub fn is_boot_hart(&self) -> bool {
+        mhartid::read() == self.ids[0]
+    }
+
+    /// Is the current hart `hart`
+    #[inline]
+    pub fn on_hart(&self, hart: usize) -> bool {
+        mhartid::read() == hart
+    }
+
+    /// Sets the priority of `interrupt`
+    #[inline]
+    pub fn set_priority(&self, interrupt: u16, priority: u8) {
+        self.priorities[usize::from(interrupt)].store(priority, Ordering::Relaxed);
+    }
+
+    /// Pends `interrupt` on the hart it belongs to.
+    #[inline]
+    pub fn pend(&self, interrupt: u16) {
+        self.pend_on(self.owners[usize::from(interrupt)], interrupt);
+    }
+
+    /// Pends `interrupt` on `hart`, which may be another hart than the current one.
+    pub fn pend_on(&self, hart: usize, interrupt: u16) {
+        self.hart(hart)
+            .pending
+            .fetch_or(1 << interrupt, Ordering::Release);
+
+        // The handler of the hart decides whether the interrupt can preempt it
+        unsafe { (self.msip)(hart, true) };
+    }
+
+    /// The highest priority pending interrupt above the threshold of `hart`
+    fn next(&self, hart: &Hart) -> Option<usize> {
+        let pending = hart.pending.load(Ordering::Acquire);
+        let threshold = hart.threshold.load(Ordering::Relaxed);
+
+        let mut next: Option<(usize, u8)> = None;
+        for interrupt in (0..N).filter(|i| pending & (1 << i) != 0) {
+            let priority = self.priorities[interrupt].load(Ordering::Relaxed);
+
+            // On equal priorities the lowest interrupt number goes first
+            if priority > threshold && next.is_none_or(|(_, next)| priority > next) {
+                next = Some((interrupt, priority));
+            }
+        }
+
+        next.map(|(interrupt, _)| interrupt)
+    }
+
+    /// Runs the pending interrupts that preempt the current hart, from its MSIP handler.
+    ///
+    /// # Safety
+    ///
+    /// Called with interrupts disabled, as in a trap handler.
+    pub unsafe fn dispatch(&self) {
+        let (id, hart) = self.current();
+        unsafe { (self.msip)(id, false) };
+
+        while let Some(interrupt) = self.next(hart) {
+            hart.pending.fetch_and(!(1 << interrupt), Ordering::AcqRel);
+
+            let previous = hart.threshold.load(Ordering::Relaxed);
+            let priority = self.priorities[interrupt].load(Ordering::Relaxed);
+            hart.threshold.store(priority, Ordering::Relaxed);
+
+            // Higher priority interrupts pended by the handler preempt it
+            unsafe { riscv::interrupt::nested(|| (self.handlers[interrupt])()) };
+
+            hart.threshold.store(previous, Ordering::Relaxed);
+        }
+    }
+
+    /// Lock implementation raising the threshold of the current hart to `ceiling`
+    ///
+    /// # Safety
+    ///
+    /// `ptr` is only accessed from tasks of the current hart, all with a priority up to `ceiling`.
+    #[inline]
+    pub unsafe fn lock<T, R>(&self, ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+        let (id, hart) = self.current();
+        let previous = hart.threshold.load(Ordering::Relaxed);
+        if ceiling <= previous {
+            return f(unsafe { &mut *ptr });
+        }
+
+        hart.threshold.store(ceiling, Ordering::Relaxed);
+        compiler_fence(Ordering::SeqCst);
+
+        let r = f(unsafe { &mut *ptr });
+
+        compiler_fence(Ordering::SeqCst);
+        hart.threshold.store(previous, Ordering::Relaxed);
+
+        // Run the interrupts pended while the threshold was raised
+        if hart.pending.load(Ordering::Acquire) != 0 {
+            unsafe { (self.msip)(id, true) };
+        }
+
+        r
+    }
+
+    /// Lock implementation for resources shared across harts, also taking `spin`
+    ///
+    /// Panics if the current hart holds the spin lock of a resource declared after this one, or
+    /// this one, be it in an outer lock or in a task it preempted.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` is only accessed through `spin`, from tasks with a priority up to `ceiling` on each
+    /// hart.
+    #[inline]
+    pub unsafe fn lock_spin<T, R>(
+        &self,
+        spin: &Spin,
+        ptr: *mut T,
+        ceiling: u8,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        let (_, hart) = self.current();
+
+        unsafe {
+            self.lock(ptr, ceiling, |resource| {
+                // Tasks preempting this one restore the order before it resumes
+                let held = hart.spin.load(Ordering::Relaxed);
+                assert!(
+                    spin.order > held,
+                    "resources shared across harts must be locked in the order they are declared in"
+                );
+                hart.spin.store(spin.order, Ordering::Relaxed);
+
+                spin.acquire();
+                let r = f(resource);
+                spin.release();
+
+                hart.spin.store(held, Ordering::Relaxed);
+                r
+            })
+        }
+    }
+
+    /// Disables the interrupts of the current hart
+    #[inline]
+    pub fn disable(&self) {
+        riscv::interrupt::disable();
+        unsafe { mie::clear_msoft() };
+    }
+
+    /// Enables the interrupts of the current hart. On the boot hart, also starts the other harts.
+    ///
+    /// # Safety
+    ///
+    /// Called once all resources have been initialized.
+    pub unsafe fn enable(&self) {
+        let (id, hart) = self.current();
+
+        if id == self.ids[0] {
+            self.started.store(true, Ordering::Release);
+            for other in &self.ids[1..] {
+                unsafe { (self.msip)(*other, true) };
+            }
+        }
+
+        // Interrupts pended on this hart before it was started
+        if hart.pending.load(Ordering::Acquire) != 0 {
+            unsafe { (self.msip)(id, true) };
+        }
+
+        unsafe {
+            mie::set_msoft();
+            riscv::interrupt::enable();
+        }
+    }
+
+    /// Runs a hart other than the boot hart, once `#[init]` has returned.
+    ///
+    /// # Safety
+    ///
+    /// Called once, on one of the other harts of the app.
+    pub unsafe fn run_secondary(&self) -> ! {
+        while !self.started.load(Ordering::Acquire) {
+            hint::spin_loop();
+        }
+
+        unsafe { self.enable() };
+
+        loop {
+            riscv::asm::wfi();
+        }
+    }
+}
+
+/// A spin lock protecting a resource shared across harts
+pub struct Spin {
+    locked: AtomicBool,
+    /// Position of the resource in the declaration order, starting at 1
+    order: usize,
+}
+
+impl Spin {
+    /// An unlocked spin lock, for the resource declared at position `order`, starting at 1
+    pub const fn new(order: usize) -> Self {
+        Spin {
+            locked: AtomicBool::new(false),
+            order,
+        }
+    }
+
+    #[inline]
+    fn acquire(&self) {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+    }
+
+    #[inline]
+    fn release(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+/// `_mp_hook` of `riscv-rt` for multi-hart apps: the first of `harts` initializes the RAM, the
+/// others wait for their MSIP, raised once `#[init]` has run, and harts not in `harts` sleep.
+///
+/// # Safety
+///
+/// Called by `riscv-rt` before the RAM is initialized, so it does not use any `static`.
+pub unsafe fn mp_hook(hart: usize, harts: &[usize], msip: Msip) -> bool {
+    if hart == harts[0] {
+        return true;
+    }
+
+    if harts.contains(&hart) {
+        // `wfi` returns on the pending MSIP, even with interrupts disabled globally
+        unsafe { mie::set_msoft() };
+        while !mip::read().msoft() {
+            riscv::asm::wfi();
+        }
+        unsafe { msip(hart, false) };
+
+        return false;
+    }
+
+    loop {
+        riscv::asm::wfi();
+    }
+}
+
+/// Run a function at the priority of the running handler, which `dispatch` already raised the
+/// threshold to
+#[inline(always)]
+pub fn run<F>(_priority: u8, f: F)
+where
+    F: FnOnce(),
+{
+    f();
+}
@@ -0,0 +1,60 @@
+//! Stack overflow detection for `#[app(stack_guard = ..)]`
+//!
+//! The hardware is configured to fault as soon as the stack grows into the lowest `stack_guard`
+//! bytes of the stack. Those bytes are left for the fault handler, which calls the application's
+//! `#[on_stack_overflow]` function with the priority of the task that was running.
+
+use super::atomic::{AtomicU8, Ordering};
+
+#[cfg(all(feature = "thumbv8main-backend", target_arch = "arm"))]
+mod msplim;
+#[cfg(all(feature = "thumbv8main-backend", target_arch = "arm"))]
+pub use msplim::*;
+
+#[cfg(any(implementation = "riscv-slic", implementation = "riscv-plic"))]
+mod pmp;
+#[cfg(any(implementation = "riscv-slic", implementation = "riscv-plic"))]
+pub use pmp::*;
+
+#[cfg(not(any(
+    all(feature = "thumbv8main-backend", target_arch = "arm"),
+    implementation = "riscv-slic",
+    implementation = "riscv-plic"
+)))]
+mod unsupported;
+#[cfg(not(any(
+    all(feature = "thumbv8main-backend", target_arch = "arm"),
+    implementation = "riscv-slic",
+    implementation = "riscv-plic"
+)))]
+pub use unsupported::*;
+
+/// Priority of the task currently running, `0` for `#[init]` and `#[idle]`.
+static RUNNING_PRIORITY: AtomicU8 = AtomicU8::new(0);
+
+unsafe extern "Rust" {
+    /// Defined by `#[app]`, calls the `#[on_stack_overflow]` function.
+    fn __rtic_on_stack_overflow(priority: u8) -> !;
+}
+
+/// Marks `priority` as running, returns the priority that was preempted.
+#[inline(always)]
+pub fn enter(priority: u8) -> u8 {
+    // Anything preempting us between the load and the store restores the value before returning.
+    let preempted = RUNNING_PRIORITY.load(Ordering::Relaxed);
+    RUNNING_PRIORITY.store(priority, Ordering::Relaxed);
+    preempted
+}
+
+/// Marks the `preempted` priority as running again.
+#[inline(always)]
+pub fn exit(preempted: u8) {
+    RUNNING_PRIORITY.store(preempted, Ordering::Relaxed);
+}
+
+/// Hands a detected stack overflow over to the application.
+#[allow(dead_code)]
+fn overflow() -> ! {
+    // SAFETY: only referenced from the handlers `#[app]` emits together with the hook.
+    unsafe { __rtic_on_stack_overflow(RUNNING_PRIORITY.load(Ordering::Relaxed)) }
+}
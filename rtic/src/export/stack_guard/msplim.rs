@@ -0,0 +1,66 @@
+use super::overflow;
+use crate::stack::bottom;
+use core::arch::{asm, naked_asm};
+use cortex_m::peripheral::SCB;
+
+pub const SUPPORTED: bool = true;
+
+/// The smallest `stack_guard` reserve, without the stack of the fault handler itself.
+///
+/// MSPLIM checks the stack pointer, so nothing is stored below the limit before the fault handler
+/// lifts it.
+pub const MIN_RESERVE: usize = 0;
+
+/// SHCSR.USGFAULTENA
+const USAGE_FAULT_ENABLE: u32 = 1 << 18;
+
+/// CFSR.STKOF
+const STACK_OVERFLOW: u32 = 1 << 20;
+
+/// Sets MSPLIM `reserve` bytes above the bottom of the stack and enables the UsageFault exception.
+///
+/// # Safety
+///
+/// Must be called before `#[init]` while running on the main stack, with the stack pointer above
+/// the new limit.
+pub unsafe fn enable(reserve: usize) {
+    // MSPLIM ignores the lowest 3 bits
+    let limit = (bottom() + reserve).next_multiple_of(8);
+
+    unsafe {
+        (*SCB::PTR).shcsr.modify(|r| r | USAGE_FAULT_ENABLE);
+        asm!("msr MSPLIM, {}", in(reg) limit, options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// UsageFault handler, `#[app]` points the vector table here.
+///
+/// The stack pointer sits at the limit when an overflow is detected, so the limit is lifted before
+/// anything is pushed onto the reserve below it.
+#[unsafe(naked)]
+pub unsafe extern "C" fn usage_fault() -> ! {
+    naked_asm!(
+        "movs r0, #0",
+        "msr MSPLIM, r0",
+        "b {}",
+        sym usage_fault_inner,
+    )
+}
+
+extern "C" fn usage_fault_inner() -> ! {
+    // SAFETY: read-only access to a status register
+    if unsafe { (*SCB::PTR).cfsr.read() } & STACK_OVERFLOW != 0 {
+        overflow();
+    }
+
+    // Any other usage fault is handled the way it would have been without `stack_guard`
+    unsafe extern "C" {
+        fn DefaultHandler();
+    }
+
+    unsafe { DefaultHandler() };
+
+    loop {
+        core::hint::spin_loop();
+    }
+}
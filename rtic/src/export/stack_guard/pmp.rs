@@ -0,0 +1,71 @@
+use super::overflow;
+use crate::stack::bottom;
+use riscv::register::{Permission, Range, mtval, pmpaddr0, pmpaddr1, pmpcfg0};
+
+pub const SUPPORTED: bool = true;
+
+/// Size of the frame the trap entry of `riscv-rt` stores: 16 registers, 10 on the E variants.
+const TRAP_FRAME: usize = (if cfg!(target_feature = "e") { 10 } else { 16 }) * size_of::<usize>();
+
+/// Size of the region writes are denied to.
+///
+/// Kept small, as only the stores of the trap entry tell an overflow apart from other store faults,
+/// see [`MIN_RESERVE`].
+const GUARD_SIZE: usize = 64;
+
+/// The smallest `stack_guard` reserve, without the stack of the fault handler itself.
+///
+/// Unlike MSPLIM, the PMP checks stores rather than the stack pointer, and the trap entry stores
+/// its frame right below the stack pointer of the store that faulted, which may still be in the
+/// guard region. Each store of the frame that hits the guard region faults again, and the nested
+/// trap entry moves the stack pointer down by another frame. The frame that is eventually stored
+/// lies right below the guard region, and starts at most two frames below it: either the previous
+/// frame overlapped the guard region, or the faulting function had at most `GUARD_SIZE` bytes of
+/// its own frame below the store that faulted.
+///
+/// The reserve must hold those two frames plus the stack of the `StoreFault` handler and of
+/// `#[on_stack_overflow]`. The nested traps overwrite `mepc` and `mcause`, only `mtval` still
+/// points into the guard region. An overflow is also only detected when a store hits the guard
+/// region: a function with more than `GUARD_SIZE` bytes of stack frame between its stores can
+/// step over it, into the reserve or below the stack, unnoticed.
+pub const MIN_RESERVE: usize = 2 * TRAP_FRAME;
+
+/// Denies writes to the `GUARD_SIZE` bytes right above the lowest `reserve` bytes of the stack.
+///
+/// This uses, and locks until the next reset, PMP entries 0 and 1.
+///
+/// # Safety
+///
+/// Must be called before `#[init]`, with the stack pointer above the guard region.
+pub unsafe fn enable(reserve: usize) {
+    // TOR addresses are in units of 4 bytes
+    let start = (bottom() + reserve).next_multiple_of(4);
+
+    unsafe {
+        pmpaddr0::write(start >> 2);
+        pmpaddr1::write((start + GUARD_SIZE) >> 2);
+        // Locked, so the entry applies to machine mode as well. Reads stay allowed for
+        // `rtic::stack::high_water_mark`.
+        pmpcfg0::set_pmp(1, Range::TOR, Permission::R, true);
+    }
+}
+
+/// StoreFault handler, `#[app]` points the vector table here.
+///
+/// # Safety
+///
+/// Must only be called by the trap handler, with the trap frame it saved.
+pub unsafe fn store_fault(trap_frame: *const ()) {
+    let address = mtval::read();
+
+    if (pmpaddr0::read() << 2..pmpaddr1::read() << 2).contains(&address) {
+        overflow();
+    }
+
+    // Any other store fault is handled the way it would have been without `stack_guard`
+    unsafe extern "C" {
+        fn ExceptionHandler(trap_frame: *const ());
+    }
+
+    unsafe { ExceptionHandler(trap_frame) };
+}
@@ -0,0 +1,16 @@
+/// `#[app]` rejects `stack_guard` at compile time through this constant.
+pub const SUPPORTED: bool = false;
+
+pub const MIN_RESERVE: usize = 0;
+
+pub unsafe fn enable(_reserve: usize) {
+    unreachable!()
+}
+
+pub unsafe extern "C" fn usage_fault() -> ! {
+    unreachable!()
+}
+
+pub unsafe fn store_fault(_trap_frame: *const ()) {
+    unreachable!()
+}
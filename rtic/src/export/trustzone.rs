@@ -0,0 +1,49 @@
+//! TrustZone support for secure apps, `#[app(backend = { non_secure_interrupts = .. })]`
+//!
+//! The app runs in the secure state. Interrupts handed to the non-secure image are retargeted
+//! through NVIC_ITNS, and the non-secure image spawns the `non_secure_callable` tasks through
+//! their `cmse-nonsecure-entry` functions.
+//!
+//! AIRCR.PRIS is set so that the non-secure image can't get in the way of the ceilings of the
+//! app: the priorities of non-secure exceptions, BASEPRI_NS and PRIMASK_NS are then mapped to the
+//! lower half of the priority range (0x80 to 0xFF). Locks write the banked BASEPRI_S, which the
+//! non-secure image can't lower, and tasks with a hardware priority below 0x80 can't be masked
+//! nor preempted by the non-secure image.
+
+use cortex_m::interrupt::InterruptNumber;
+
+/// `#[app]` rejects TrustZone arguments at compile time through this constant.
+pub const SUPPORTED: bool = cfg!(feature = "thumbv8main-backend");
+
+const AIRCR: *mut u32 = 0xE000_ED0C as *mut u32;
+const NVIC_ITNS: *mut u32 = 0xE000_E380 as *mut u32;
+
+const AIRCR_VECTKEY: u32 = 0x05FA << 16;
+const AIRCR_PRIS: u32 = 1 << 14;
+
+/// Maps the priorities of the non-secure state to the lower half of the priority range.
+///
+/// # Safety
+///
+/// Must be called from the secure state, before interrupts are enabled.
+pub unsafe fn prioritize_secure() {
+    unsafe {
+        // The upper half reads VECTKEYSTAT, it must be written with VECTKEY
+        let aircr = AIRCR.read_volatile() & 0xFFFF;
+        AIRCR.write_volatile(AIRCR_VECTKEY | aircr | AIRCR_PRIS);
+    }
+}
+
+/// Makes `interrupt` target the non-secure state.
+///
+/// # Safety
+///
+/// Must be called from the secure state, and `interrupt` must not be used by the app.
+pub unsafe fn target_non_secure<I: InterruptNumber>(interrupt: I) {
+    let nr = usize::from(interrupt.number());
+
+    unsafe {
+        let itns = NVIC_ITNS.add(nr / 32);
+        itns.write_volatile(itns.read_volatile() | (1 << (nr % 32)));
+    }
+}
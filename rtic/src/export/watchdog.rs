@@ -0,0 +1,65 @@
+//! The supervisor of `watchdog`, see [`crate::watchdog`]
+
+use super::atomic::{AtomicBool, Ordering};
+use crate::watchdog::Watchdog;
+
+/// Whether each supervised task checked in since the last check of the supervisor.
+pub struct Checkins<const N: usize> {
+    checked_in: [AtomicBool; N],
+    names: [&'static str; N],
+}
+
+impl<const N: usize> Checkins<N> {
+    /// `names` are the names of the supervised tasks, indexed by their id.
+    pub const fn new(names: [&'static str; N]) -> Self {
+        Checkins {
+            checked_in: [const { AtomicBool::new(false) }; N],
+            names,
+        }
+    }
+
+    /// Called by `cx.checkin()`.
+    #[inline(always)]
+    pub fn check_in(&self, task: usize) {
+        self.checked_in[task].store(true, Ordering::Relaxed);
+    }
+
+    /// Starts a new window, passing each task that didn't check in during the last one to
+    /// `starved`. Returns whether every task checked in.
+    fn take(&self, mut starved: impl FnMut(&'static str)) -> bool {
+        let mut all_checked_in = true;
+
+        for (checked_in, name) in self.checked_in.iter().zip(self.names) {
+            if !checked_in.swap(false, Ordering::Relaxed) {
+                all_checked_in = false;
+                starved(name);
+            }
+        }
+
+        all_checked_in
+    }
+
+    /// The body of the supervisor task.
+    ///
+    /// The watchdog is fed every half `timeout`, and the check-ins are checked every `timeout`, so
+    /// that a healthy app feeds it well before it expires. Once a task starves the watchdog is no
+    /// longer fed, so the supervisor waits for the reset.
+    pub async fn supervise<W: Watchdog>(
+        &self,
+        timeout: W::Duration,
+        on_starvation: fn(&'static str),
+    ) -> ! {
+        let half = timeout / 2;
+
+        loop {
+            W::wait(half).await;
+            W::feed();
+
+            W::wait(half).await;
+            if !self.take(on_starvation) {
+                core::future::pending::<()>().await;
+            }
+            W::feed();
+        }
+    }
+}
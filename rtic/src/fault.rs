@@ -0,0 +1,53 @@
+//! Reporting the interrupted tasks on a fault
+//!
+//! Enabled per application with an `#[on_fault]` function. Hardware tasks, async task dispatchers,
+//! `#[init]` and `#[idle]` record which task runs at their priority level, a single store when a
+//! task starts and another one when it returns. When the processor faults, the priority levels
+//! with a task running make up the stack of preempted tasks, which the fault handler passes to
+//! `#[on_fault]` along with the [`FaultFrame`]:
+//!
+//! ```ignore
+//! #[on_fault]
+//! fn on_fault(frame: &rtic::fault::FaultFrame, tasks: &[rtic::fault::ActiveTask]) -> ! {
+//!     // `tasks` goes from the lowest priority level to the task that faulted
+//!     for task in tasks {
+//!         log::error!("{} at priority {}", task.name, task.priority);
+//!     }
+//!     log::error!("fault at {:#010x}", frame.pc);
+//!
+//!     cortex_m::peripheral::SCB::sys_reset()
+//! }
+//! ```
+//!
+//! Only the Cortex-M backends support `#[on_fault]`, whose handler is the `HardFault` exception.
+
+/// The registers stacked by the processor when the fault was taken.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct FaultFrame {
+    /// General purpose register r0
+    pub r0: u32,
+    /// General purpose register r1
+    pub r1: u32,
+    /// General purpose register r2
+    pub r2: u32,
+    /// General purpose register r3
+    pub r3: u32,
+    /// General purpose register r12
+    pub r12: u32,
+    /// Link register
+    pub lr: u32,
+    /// Address of the instruction that faulted, or of the next one
+    pub pc: u32,
+    /// Program status register
+    pub xpsr: u32,
+}
+
+/// A task that was running, or preempted, when the fault was taken.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ActiveTask {
+    /// The priority level the task runs at, `0` for `#[init]` and `#[idle]`
+    pub priority: u8,
+    /// The name of the task
+    pub name: &'static str,
+}
@@ -0,0 +1,34 @@
+//! Running apps on the host, with the `host-backend` feature
+//!
+//! The host backend simulates the interrupt controller so that an app can run, for example in a
+//! `cargo test`, on the machine it is built on. `#[app]` generates `pub unsafe fn main() -> !` in
+//! the app module instead of the `main` of the target, and, unless `interrupts = ..` names an
+//! enumeration of its own, an `Interrupt` enumeration of the interrupts the app binds and
+//! dispatches from.
+//!
+//! ```ignore
+//! #[test]
+//! fn button_press() {
+//!     rtic::host::start(app::main);
+//!
+//!     rtic::host::pend(app::Interrupt::GPIOA);
+//!     rtic::host::wait_idle();
+//! }
+//! ```
+//!
+//! Interrupts are serviced by a scheduler thread, in the order of their priorities. A handler is
+//! preempted by higher priority handlers when it pends an interrupt or releases a lock, rather
+//! than at any instruction as on hardware. `#[init]` and `#[idle]` run on a thread of their own.
+//!
+//! Apps use `static` variables for their resources and executors, so only one app can be started
+//! per process; give each app test its own integration test file.
+
+pub use crate::export::{pend, start, wait_idle, wfi};
+
+/// An interrupt that can be pended with [`pend`].
+///
+/// Implemented by the `Interrupt` enumeration generated by `#[app]`.
+pub trait InterruptNumber: Copy {
+    /// The number of the interrupt, unique among the interrupts of the app.
+    fn number(self) -> u16;
+}
@@ -32,8 +32,11 @@
 )]
 #![allow(clippy::inline_always)]
 
+#[cfg(any(implementation = "host", implementation = "sim"))]
+extern crate std;
+
 pub use rtic_core::{Exclusive, Mutex, prelude as mutex_prelude};
-pub use rtic_macros::app;
+pub use rtic_macros::{app, component};
 
 /// module `mutex::prelude` provides `Mutex` and multi-lock variants. Recommended over `mutex_prelude`
 pub mod mutex {
@@ -44,7 +47,33 @@ pub mod mutex {
 #[doc(hidden)]
 pub mod export;
 
+pub mod backend;
+pub mod fault;
+#[cfg(implementation = "host")]
+pub mod host;
+#[cfg(feature = "meta-section")]
+pub mod meta;
+pub mod persistent;
+#[cfg(implementation = "sim")]
+pub mod sim;
+pub mod slot;
+#[cfg(not(any(
+    implementation = "custom",
+    implementation = "host",
+    implementation = "sim"
+)))]
+pub mod stack;
+#[cfg(feature = "executor-stats")]
+pub mod stats;
+pub mod task_id;
+pub mod watchdog;
+mod yield_now;
+
+pub use backend::Backend;
+#[cfg(not(any(implementation = "custom", implementation = "riscv-plic")))]
 pub use export::pend;
+pub use task_id::{TaskId, task_id_from_waker};
+pub use yield_now::{YieldNow, yield_now};
 
 use core::cell::UnsafeCell;
 
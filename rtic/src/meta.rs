@@ -0,0 +1,147 @@
+//! Metadata on the tasks and resources of an app, for debuggers
+//!
+//! Enabled with the `meta-section` feature. `#[app]` then emits a `.rtic_meta` section describing
+//! every task and shared resource of the app, which a debugger can read from the ELF to show the
+//! state of the app from its RAM. The `rtic-meta` crate parses the section on the host.
+//!
+//! The section holds a [`Meta`]: a [`Header`], the [`Task`]s, the [`Resource`]s and a table of
+//! strings, in that order and in the byte order of the target. Addresses have the size of a pointer
+//! of the target, given in the header along with the size of a task and of a resource entry. New
+//! fields are only appended to the entries, anything else bumps [`VERSION`].
+//!
+//! Tasks are listed in the order of their [`TaskId`](crate::TaskId): `#[init]`, `#[idle]`,
+//! hardware tasks and software tasks.
+
+use core::ptr;
+
+/// The first bytes of the section
+pub const MAGIC: [u8; 4] = *b"RTIC";
+
+/// The version of the layout of the section
+pub const VERSION: u32 = 1;
+
+/// `Task::kind` of `#[init]`
+pub const KIND_INIT: u8 = 0;
+/// `Task::kind` of `#[idle]`
+pub const KIND_IDLE: u8 = 1;
+/// `Task::kind` of hardware tasks
+pub const KIND_HARDWARE: u8 = 2;
+/// `Task::kind` of software tasks
+pub const KIND_SOFTWARE: u8 = 3;
+
+/// The contents of the `.rtic_meta` section.
+#[repr(C)]
+pub struct Meta<const TASKS: usize, const RESOURCES: usize, const STRINGS: usize> {
+    /// Describes the rest of the section
+    pub header: Header,
+    /// One entry per task
+    pub tasks: [Task; TASKS],
+    /// One entry per shared resource
+    pub resources: [Resource; RESOURCES],
+    /// The UTF-8 strings `Str`s point into
+    pub strings: [u8; STRINGS],
+}
+
+impl<const TASKS: usize, const RESOURCES: usize, const STRINGS: usize>
+    Meta<TASKS, RESOURCES, STRINGS>
+{
+    /// The section for the given tasks, resources and strings.
+    pub const fn new(
+        tasks: [Task; TASKS],
+        resources: [Resource; RESOURCES],
+        strings: [u8; STRINGS],
+    ) -> Self {
+        Meta {
+            header: Header {
+                magic: MAGIC,
+                version: VERSION,
+                tasks: TASKS as u16,
+                task_size: size_of::<Task>() as u16,
+                resources: RESOURCES as u16,
+                resource_size: size_of::<Resource>() as u16,
+                strings: STRINGS as u32,
+                address_size: size_of::<Address>() as u32,
+            },
+            tasks,
+            resources,
+            strings,
+        }
+    }
+}
+
+/// The start of the section.
+#[repr(C)]
+pub struct Header {
+    /// [`MAGIC`]
+    pub magic: [u8; 4],
+    /// [`VERSION`]
+    pub version: u32,
+    /// Number of tasks
+    pub tasks: u16,
+    /// Size of a task entry, in bytes
+    pub task_size: u16,
+    /// Number of resources
+    pub resources: u16,
+    /// Size of a resource entry, in bytes
+    pub resource_size: u16,
+    /// Size of the string table, in bytes
+    pub strings: u32,
+    /// Size of an address, in bytes
+    pub address_size: u32,
+}
+
+/// A task of the app.
+#[repr(C)]
+pub struct Task {
+    /// The `running` flag of the executor of a software task, null for other tasks
+    pub running: Address,
+    /// The `pending` flag of the executor of a software task, null for other tasks
+    pub pending: Address,
+    /// The name of the task
+    pub name: Str,
+    /// The interrupts of a hardware task, separated by commas, empty for other tasks
+    pub binds: Str,
+    /// The priority of the task, 0 for `#[init]`
+    pub priority: u8,
+    /// One of the `KIND_*` constants
+    pub kind: u8,
+}
+
+/// A shared resource of the app.
+#[repr(C)]
+pub struct Resource {
+    /// Where the resource is stored
+    pub address: Address,
+    /// The size of the resource, in bytes
+    pub size: u32,
+    /// The name of the resource
+    pub name: Str,
+    /// The priority ceiling of the resource
+    pub ceiling: u8,
+}
+
+/// A string of the string table.
+#[repr(C)]
+pub struct Str {
+    /// Offset of the string in the table
+    pub offset: u32,
+    /// Length of the string, in bytes
+    pub len: u32,
+}
+
+/// An address in the memory of the target.
+#[repr(transparent)]
+pub struct Address(*const u8);
+
+// SAFETY: an `Address` is only ever read by a debugger, never dereferenced.
+unsafe impl Sync for Address {}
+
+impl Address {
+    /// No address.
+    pub const NULL: Address = Address(ptr::null());
+
+    /// The address of `ptr`.
+    pub const fn of<T>(ptr: *const T) -> Self {
+        Address(ptr.cast())
+    }
+}
@@ -0,0 +1,50 @@
+//! Types of `#[persistent]` resources
+//!
+//! A `#[persistent]` resource is read back from memory that survived a reset, and its seal is a
+//! CRC over the bytes of its value. Both are only sound for plain old data, so the type of such a
+//! resource must implement [`Persistent`]:
+//!
+//! ```ignore
+//! #[derive(Clone, Copy)]
+//! #[repr(C)]
+//! struct LastFault {
+//!     pc: u32,
+//!     count: u32,
+//! }
+//!
+//! // SAFETY: `repr(C)`, two `u32` fields and no padding
+//! unsafe impl rtic::persistent::Persistent for LastFault {}
+//! ```
+
+/// A type a `#[persistent]` resource can hold across a warm reset.
+///
+/// Implemented for the integer and floating point types, and arrays of `Persistent` types.
+///
+/// # Safety
+///
+/// The type must be plain old data:
+///
+/// - every bit pattern must be a valid value of the type, as the memory it is read back from may
+///   hold anything after a reset. This rules out `bool`, `char`, enums, references and types with
+///   invariants, such as `NonZeroU32`;
+/// - it must not have padding bytes, which are uninitialized and can't be part of the seal.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be the type of a `#[persistent]` resource",
+    label = "not plain old data",
+    note = "`#[persistent]` resources must implement `rtic::persistent::Persistent`: no padding, and every bit pattern a valid value"
+)]
+pub unsafe trait Persistent: Sized {}
+
+macro_rules! persistent {
+    ($($ty:ty),*) => {
+        $(
+            unsafe impl Persistent for $ty {}
+        )*
+    };
+}
+
+persistent!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+
+unsafe impl<T: Persistent, const N: usize> Persistent for [T; N] {}
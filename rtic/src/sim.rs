@@ -0,0 +1,205 @@
+//! Deterministic simulation of apps, with the `sim-backend` feature
+//!
+//! The sim backend runs an app on the thread of a test, in virtual time. Interrupts only preempt
+//! the running code at the points where the generated code calls into the backend: when a
+//! handler starts running (`rtic::export::run`) and when a lock is taken or released. Each point
+//! advances the virtual time by one tick.
+//!
+//! A [`Sim`] scripts the interrupts injected in a run: at given virtual instants, or at every lock
+//! release. [`Sim::explore`] additionally runs the app once for every way of injecting interrupts at
+//! the first points of the run, which is how races between tasks are found. A failing schedule is
+//! printed together with its seed, which [`Sim::replay`] runs again.
+//!
+//! ```ignore
+//! #[test]
+//! fn no_lost_updates() {
+//!     Sim::new()
+//!         .at(10, app::Interrupt::BUTTON)
+//!         .explore(app::main, &[app::Interrupt::TIMER], 6, || {
+//!             assert_eq!(app::total(), 2);
+//!         });
+//! }
+//! ```
+//!
+//! `#[app]` generates `pub unsafe fn main() -> !` in the app module, which the simulation runs.
+//! `#[idle]` has to call [`wfi`] when it has nothing left to do: the virtual time then jumps to the
+//! next scripted interrupt, and the run ends when there is none. Every run starts the app from
+//! `#[init]` again, with fresh executors and task local resources.
+//!
+//! Apps use `static` variables for their resources, so simulations of apps in the same process
+//! run one at a time.
+
+use std::{
+    eprintln, panic,
+    string::String,
+    sync::{Mutex, PoisonError},
+    vec::Vec,
+};
+
+use crate::export::{Config, Interrupt, simulate};
+pub use crate::export::{now, wfi};
+
+/// An interrupt that can be injected by a [`Sim`].
+///
+/// Implemented by the `Interrupt` enumeration generated by `#[app]`. The `Debug` representation
+/// names the interrupt in traces.
+pub trait InterruptNumber: Copy + core::fmt::Debug {
+    /// The number of the interrupt, unique among the interrupts of the app.
+    fn number(self) -> u16;
+}
+
+/// Serializes the simulations of a process, which share the `static`s of their apps
+static SIMULATION: Mutex<()> = Mutex::new(());
+
+/// The interrupts injected into the runs of an app.
+#[derive(Clone)]
+pub struct Sim {
+    at: Vec<(u64, Interrupt)>,
+    on_unlock: Vec<Interrupt>,
+    max_points: u64,
+}
+
+impl Default for Sim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sim {
+    /// A simulation without scripted interrupts.
+    pub fn new() -> Self {
+        Sim {
+            at: Vec::new(),
+            on_unlock: Vec::new(),
+            max_points: 100_000,
+        }
+    }
+
+    /// Pends `interrupt` at the virtual instant `at`, or at the first point after it.
+    pub fn at(mut self, at: u64, interrupt: impl InterruptNumber) -> Self {
+        let index = self.at.partition_point(|(instant, _)| *instant <= at);
+        self.at.insert(index, (at, Interrupt::new(interrupt)));
+        self
+    }
+
+    /// Pends `interrupt` every time a lock is released.
+    pub fn on_lock_release(mut self, interrupt: impl InterruptNumber) -> Self {
+        self.on_unlock.push(Interrupt::new(interrupt));
+        self
+    }
+
+    /// Fails a run that goes through more than `points` points without reaching [`wfi`] with no
+    /// scripted interrupt left, 100 000 by default.
+    pub fn max_points(mut self, points: u64) -> Self {
+        self.max_points = points;
+        self
+    }
+
+    /// Runs the app once, returning the trace of the interrupts pended and serviced.
+    ///
+    /// # Panics
+    ///
+    /// If the app panics, after printing the trace.
+    pub fn run(&self, main: unsafe fn() -> !) -> Vec<String> {
+        self.simulate(main, Vec::new(), &mut || {}, None)
+    }
+
+    /// Runs the app once for each schedule of `interrupts` over the first `points` points, and
+    /// calls `check` after each run.
+    ///
+    /// A schedule pends at most one of `interrupts` at each point, so there are
+    /// `(interrupts.len() + 1).pow(points)` of them. Each one is identified by a seed, whose digits in
+    /// base `interrupts.len() + 1` tell which interrupt each point pends, if any.
+    ///
+    /// # Panics
+    ///
+    /// If the app or `check` panics, after printing the seed and the trace of the schedule.
+    pub fn explore<I: InterruptNumber>(
+        &self,
+        main: unsafe fn() -> !,
+        interrupts: &[I],
+        points: u32,
+        mut check: impl FnMut(),
+    ) {
+        let schedules = (interrupts.len() as u64 + 1)
+            .checked_pow(points)
+            .expect("too many schedules, explore fewer points");
+
+        for seed in 0..schedules {
+            self.replay_with(main, interrupts, points, seed, &mut check);
+        }
+    }
+
+    /// Runs the app with the schedule `seed` of [`Sim::explore`], returning the trace.
+    ///
+    /// # Panics
+    ///
+    /// If the app panics, after printing the trace.
+    pub fn replay<I: InterruptNumber>(
+        &self,
+        main: unsafe fn() -> !,
+        interrupts: &[I],
+        points: u32,
+        seed: u64,
+    ) -> Vec<String> {
+        self.replay_with(main, interrupts, points, seed, &mut || {})
+    }
+
+    fn replay_with<I: InterruptNumber>(
+        &self,
+        main: unsafe fn() -> !,
+        interrupts: &[I],
+        points: u32,
+        seed: u64,
+        check: &mut dyn FnMut(),
+    ) -> Vec<String> {
+        let radix = interrupts.len() as u64 + 1;
+        let mut digits = seed;
+        let schedule = (0..points)
+            .map(|_| {
+                let digit = digits % radix;
+                digits /= radix;
+                digit
+                    .checked_sub(1)
+                    .map(|i| Interrupt::new(interrupts[i as usize]))
+            })
+            .collect();
+
+        self.simulate(main, schedule, check, Some(seed))
+    }
+
+    fn simulate(
+        &self,
+        main: unsafe fn() -> !,
+        schedule: Vec<Option<Interrupt>>,
+        check: &mut dyn FnMut(),
+        seed: Option<u64>,
+    ) -> Vec<String> {
+        let _simulation = SIMULATION.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let config = Config {
+            at: self.at.clone(),
+            on_unlock: self.on_unlock.clone(),
+            schedule,
+            max_points: self.max_points,
+        };
+
+        let (trace, mut panic) = simulate(main, config);
+        if panic.is_none() {
+            panic = panic::catch_unwind(panic::AssertUnwindSafe(check)).err();
+        }
+
+        if let Some(panic) = panic {
+            match seed {
+                Some(seed) => eprintln!("rtic::sim: schedule {seed} failed, trace:"),
+                None => eprintln!("rtic::sim: the run failed, trace:"),
+            }
+            for line in &trace {
+                eprintln!("  {line}");
+            }
+            panic::resume_unwind(panic);
+        }
+
+        trace
+    }
+}
@@ -0,0 +1,98 @@
+//! In-place initialization of resources
+//!
+//! Resources marked `#[init_in_place]` in the `#[shared]` or `#[local]` struct are not built by
+//! `#[init]` and then moved into their `static`. Instead `init::Context::slots` hands out a
+//! [`Slot`] pointing at the resource's storage, and the field of the returned struct takes the
+//! [`Initialized`] proof that the slot was written.
+//!
+//! After `#[init]` returns, and before interrupts are enabled, every proof is checked against the
+//! storage of its resource, so that the proof of one slot can't stand in for another of the same
+//! type.
+
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+/// Uninitialized storage of a resource, given to `#[init]`.
+pub struct Slot<T: 'static> {
+    slot: &'static mut MaybeUninit<T>,
+}
+
+impl<T> Slot<T> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub unsafe fn new(slot: *mut MaybeUninit<T>) -> Self {
+        Slot {
+            slot: unsafe { &mut *slot },
+        }
+    }
+
+    /// Writes `value` into the slot.
+    ///
+    /// Whether `value` is built directly in the resource's storage is up to the optimizer, for
+    /// large resources prefer initializing the slot piecewise through [`Slot::as_mut_ptr`].
+    #[inline(always)]
+    pub fn write(self, value: T) -> Initialized<T> {
+        Initialized {
+            value: self.slot.write(value),
+        }
+    }
+
+    /// Pointer to the storage of the resource.
+    #[inline(always)]
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.slot.as_mut_ptr()
+    }
+
+    /// The storage of the resource.
+    #[inline(always)]
+    pub fn as_uninit(&mut self) -> &mut MaybeUninit<T> {
+        self.slot
+    }
+
+    /// Claims that the slot has been fully initialized.
+    ///
+    /// # Safety
+    ///
+    /// The resource must have been fully initialized through [`Slot::as_mut_ptr`] or
+    /// [`Slot::as_uninit`], as with [`MaybeUninit::assume_init`].
+    #[inline(always)]
+    pub unsafe fn assume_init(self) -> Initialized<T> {
+        Initialized {
+            value: unsafe { self.slot.assume_init_mut() },
+        }
+    }
+}
+
+/// Proof that a [`Slot`] has been written, returned from `#[init]` in place of the resource.
+///
+/// It also gives access to the resource until `#[init]` returns.
+#[must_use = "the resource is only initialized if this is returned from `#[init]`"]
+pub struct Initialized<T: 'static> {
+    value: &'static mut T,
+}
+
+impl<T> Initialized<T> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn verify(self, slot: *mut MaybeUninit<T>) {
+        assert!(
+            ptr::eq(ptr::from_mut(self.value), slot.cast()),
+            "an `#[init_in_place]` resource was returned with the slot of another resource"
+        );
+    }
+}
+
+impl<T> Deref for Initialized<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<T> DerefMut for Initialized<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
@@ -0,0 +1,149 @@
+//! Stack usage measurement
+//!
+//! Enabled per application with `#[app(.., stack_usage = true)]`. Before `#[init]` runs the unused
+//! part of the stack is painted with a known pattern, [`high_water_mark`] later scans for the
+//! deepest word that has been overwritten since.
+//!
+//! Hardware tasks and async task dispatchers additionally record how deep the stack already was
+//! when their priority level was entered, see [`entry_depth`]. Comparing the entry depths of
+//! different levels shows how much stack each level adds on top of the ones it preempts.
+//!
+//! The stack bounds are taken from linker symbols defined by the runtime crate, so this module
+//! only exists on the backends whose runtime provides them:
+//!
+//! | Backends                                    | Runtime                | Top            | Bottom       |
+//! |---------------------------------------------|------------------------|----------------|--------------|
+//! | `thumbv6`, `thumbv7`, `thumbv8*`            | `cortex-m-rt` >= 0.7.5 | `_stack_start` | `_stack_end` |
+//! | `riscv-esp32c3`, `riscv-esp32c6`            | `esp-hal` 1.0          | `_stack_start` | `_stack_end` |
+//! | `riscv-clint`, `riscv-mecall`, `riscv-plic` | `riscv-rt` 0.13        | `_stack_start` | `_estack`    |
+//!
+//! The custom, host and sim backends don't have it, `stack_usage = true` is rejected there.
+
+use crate::export::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Number of priority levels [`entry_depth`] keeps track of.
+///
+/// Applications using a higher priority together with `stack_usage = true` fail to compile.
+pub const PRIORITY_LEVELS: usize = 16;
+
+/// Pattern written to the unused part of the stack.
+const PAINT: u32 = 0xCCCC_CCCC;
+
+/// Space left untouched below the stack frame of [`paint`], which is in use while painting.
+const PAINT_MARGIN: usize = 64;
+
+static PAINTED: AtomicBool = AtomicBool::new(false);
+
+static ENTRY_DEPTH: [AtomicUsize; PRIORITY_LEVELS] =
+    [const { AtomicUsize::new(0) }; PRIORITY_LEVELS];
+
+unsafe extern "C" {
+    static _stack_start: u32;
+    #[cfg(not(any(implementation = "riscv-slic", implementation = "riscv-plic")))]
+    static _stack_end: u32;
+    #[cfg(any(implementation = "riscv-slic", implementation = "riscv-plic"))]
+    static _estack: u32;
+}
+
+/// Highest address of the stack, the stack grows downwards from here.
+#[inline(always)]
+pub(crate) fn top() -> usize {
+    (&raw const _stack_start) as usize
+}
+
+/// Lowest address of the stack.
+#[inline(always)]
+pub(crate) fn bottom() -> usize {
+    #[cfg(not(any(implementation = "riscv-slic", implementation = "riscv-plic")))]
+    let bottom = &raw const _stack_end;
+    #[cfg(any(implementation = "riscv-slic", implementation = "riscv-plic"))]
+    let bottom = &raw const _estack;
+
+    bottom as usize
+}
+
+/// Approximation of the stack pointer of the caller.
+#[inline(always)]
+fn stack_pointer() -> usize {
+    let marker = 0u8;
+    core::hint::black_box(&raw const marker) as usize
+}
+
+/// Total size of the stack in bytes.
+pub fn size() -> usize {
+    top() - bottom()
+}
+
+/// Largest amount of stack, in bytes, used since the stack was painted.
+///
+/// Returns `None` if the application was not compiled with `stack_usage = true`.
+///
+/// The stack is scanned from its lowest address upwards, this takes time proportional to the
+/// amount of stack that has never been used. It is best called from `#[idle]`.
+pub fn high_water_mark() -> Option<usize> {
+    if !PAINTED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let top = top();
+    let mut p = bottom() as *const u32;
+
+    // SAFETY: `p` stays within the stack region, the painted words below the stack pointer are
+    // not owned by anyone.
+    while (p as usize) < top && unsafe { p.read_volatile() } == PAINT {
+        p = p.wrapping_add(1);
+    }
+
+    Some(top - p as usize)
+}
+
+/// Deepest stack usage, in bytes, observed when a task at `priority` started running.
+///
+/// Returns `None` if no hardware task or async task dispatcher has run at that priority, or if
+/// `priority` is not below [`PRIORITY_LEVELS`].
+pub fn entry_depth(priority: u8) -> Option<usize> {
+    let depth = ENTRY_DEPTH.get(priority as usize)?.load(Ordering::Relaxed);
+
+    (depth != 0).then_some(depth)
+}
+
+/// Paints the stack from its lowest address up to a little below the current stack frame.
+///
+/// # Safety
+///
+/// Must be called at most once, before `#[init]`, with interrupts disabled.
+#[doc(hidden)]
+#[inline(never)]
+pub unsafe fn paint() {
+    let limit = (stack_pointer() - PAINT_MARGIN) & !(core::mem::align_of::<u32>() - 1);
+    let mut p = bottom() as *mut u32;
+
+    while (p as usize) < limit {
+        // SAFETY: everything between the bottom of the stack and `limit` is unused at this point.
+        unsafe { p.write_volatile(PAINT) };
+        p = p.wrapping_add(1);
+    }
+
+    PAINTED.store(true, Ordering::Relaxed);
+}
+
+/// Records the current stack depth for priority level `PRIORITY`.
+#[doc(hidden)]
+#[inline(always)]
+pub fn enter<const PRIORITY: u8>() {
+    const {
+        assert!(
+            (PRIORITY as usize) < PRIORITY_LEVELS,
+            "`stack_usage` supports priorities up to `rtic::stack::PRIORITY_LEVELS - 1`"
+        )
+    };
+
+    let depth = top() - stack_pointer();
+    let slot = &ENTRY_DEPTH[PRIORITY as usize];
+
+    // Only code running at `PRIORITY` writes this slot and it can't preempt itself, so a plain
+    // load/store pair does not race.
+    if depth > slot.load(Ordering::Relaxed) {
+        slot.store(depth, Ordering::Relaxed);
+    }
+}
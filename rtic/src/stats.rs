@@ -0,0 +1,33 @@
+//! Executor statistics of the async tasks
+//!
+//! Enabled with the `executor-stats` feature. The executor of every software task then counts
+//! what happens to it, and the application gets a `task::stats()` function per software task, as
+//! well as an `app::task_stats()` iterator over all of them:
+//!
+//! ```ignore
+//! for (name, stats) in app::task_stats() {
+//!     log::info!("{}: {} polls for {} wakeups", name, stats.polls, stats.wakeups);
+//! }
+//! ```
+//!
+//! The counters are updated with relaxed atomic increments and wrap around on overflow. A task
+//! whose `spurious_wakeups` grow much faster than its `wakeups` shares its dispatcher with tasks
+//! that wake often, one whose `wakeups` grow much faster than its `completions` may be woken in a
+//! loop.
+
+/// A snapshot of the counters of one async task's executor.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TaskStats {
+    /// Number of times the task was spawned
+    pub spawns: u32,
+    /// Number of times the future of the task was polled
+    pub polls: u32,
+    /// Number of times the task was woken, including the wake-up of each spawn
+    pub wakeups: u32,
+    /// Number of times the dispatcher ran while the task was running but hadn't been woken
+    pub spurious_wakeups: u32,
+    /// Number of times the future of the task returned
+    pub completions: u32,
+    /// Number of spawn attempts that found the task still running
+    pub failed_allocations: u32,
+}
@@ -0,0 +1,67 @@
+//! Identifying the task a waker belongs to
+//!
+//! The wakers RTIC hands to async tasks point at a `static` holding the id of their task, next to
+//! the function pending its dispatcher. A primitive that stores wakers, such as a wait queue, can
+//! then tell which tasks are blocked on it:
+//!
+//! ```ignore
+//! if let Some(task) = rtic::task_id_from_waker(cx.waker()) {
+//!     log::trace!("{} waits for the channel", task.name());
+//! }
+//! ```
+//!
+//! The ids are given by `#[app]`, in the order `#[init]`, `#[idle]`, the hardware tasks and the
+//! software tasks, as declared. They are the same ids `#[on_fault]` uses.
+
+use core::{fmt, hash, task::Waker};
+
+/// The identity of a task of the application.
+#[derive(Clone, Copy)]
+pub struct TaskId {
+    index: u16,
+    names: &'static [&'static str],
+}
+
+impl TaskId {
+    pub(crate) const fn new(index: u16, names: &'static [&'static str]) -> Self {
+        TaskId { index, names }
+    }
+
+    /// The index of the task, stable for a given application.
+    pub fn index(self) -> u16 {
+        self.index
+    }
+
+    /// The name of the task, as declared in the application.
+    pub fn name(self) -> &'static str {
+        self.names[usize::from(self.index)]
+    }
+}
+
+impl PartialEq for TaskId {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl Eq for TaskId {}
+
+impl hash::Hash for TaskId {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl fmt::Debug for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TaskId")
+            .field(&self.index)
+            .field(&self.name())
+            .finish()
+    }
+}
+
+/// The task `waker` wakes, or `None` if it wasn't created by an executor of RTIC.
+pub fn task_id_from_waker(waker: &Waker) -> Option<TaskId> {
+    crate::export::executor::task_id(waker)
+}
@@ -0,0 +1,47 @@
+//! Supervision of tasks by a watchdog
+//!
+//! With `#[app(watchdog = Wdt, timeout = ..)]` the app feeds the hardware watchdog `Wdt` on
+//! behalf of the tasks marked `supervised`. A supervisor, running as an async task at the highest
+//! priority of the async tasks, feeds the watchdog every half `timeout`. Every `timeout` it also
+//! checks that each supervised task called `cx.checkin()` since the last check. When tasks missed
+//! their window, the optional `#[on_starvation]` function gets the name of each of them and the
+//! watchdog is no longer fed, so that it resets the device:
+//!
+//! ```ignore
+//! #[app(device = pac, dispatchers = [UART0], watchdog = Wdt, timeout = 500.millis())]
+//! mod app {
+//!     #[task(priority = 1, supervised)]
+//!     async fn blink(cx: blink::Context) {
+//!         loop {
+//!             cx.checkin();
+//!             Mono::delay(100.millis()).await;
+//!         }
+//!     }
+//!
+//!     #[on_starvation]
+//!     fn on_starvation(task: &'static str) {
+//!         defmt::error!("{} starved", task);
+//!     }
+//! }
+//! ```
+
+use core::{future::Future, ops::Div};
+
+/// A hardware watchdog, fed by the supervisor of the app.
+pub trait Watchdog {
+    /// The type of the `timeout` argument of `#[app]`.
+    type Duration: Copy + Div<u32, Output = Self::Duration>;
+
+    /// Starts the watchdog, after `#[init]` returns and before interrupts are enabled.
+    ///
+    /// The watchdog must reset the device if it isn't fed for longer than `timeout`. The
+    /// supervisor feeds it every half `timeout`, the other half is the slack for the supervisor
+    /// being delayed by higher priority tasks.
+    fn start(timeout: Self::Duration);
+
+    /// Feeds the watchdog.
+    fn feed();
+
+    /// Waits for `timeout`, usually through the `delay` of a monotonic.
+    fn wait(timeout: Self::Duration) -> impl Future<Output = ()>;
+}
@@ -0,0 +1,42 @@
+//! Handing the CPU to the other async tasks of the same priority
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Yields to the other async tasks of the calling task's priority.
+///
+/// The task wakes itself and returns to its dispatcher. Every other task of the priority that is
+/// ready at that point is polled once before the task resumes. Tasks of a higher priority preempt
+/// the dispatcher as usual, tasks of a lower priority don't run in between.
+///
+/// Without `#[app(fair_dispatch = true)]` each run of a dispatcher polls the ready tasks in the
+/// order they are declared, so the first ones are always served first. With it the runs are
+/// rounds that start after the task polled last in the previous round, so ready tasks take turns
+/// no matter where they are declared.
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// The future of [`yield_now`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
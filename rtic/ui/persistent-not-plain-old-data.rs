@@ -0,0 +1,31 @@
+#![no_main]
+
+#[rtic::app(device = lm3s6965)]
+mod app {
+    #[shared]
+    struct Shared {
+        // not every bit pattern is a valid `bool`
+        #[persistent]
+        armed: bool,
+    }
+
+    #[local]
+    struct Local {
+        // `(u8, u32)` has padding bytes
+        #[persistent]
+        last: (u8, u32),
+    }
+
+    #[init]
+    fn init(_cx: init::Context) -> (Shared, Local) {
+        (
+            Shared { armed: false },
+            Local { last: (0, 0) },
+        )
+    }
+
+    #[idle(shared = [armed], local = [last])]
+    fn idle(_cx: idle::Context) -> ! {
+        loop {}
+    }
+}
@@ -0,0 +1,22 @@
+#![no_main]
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_cx: init::Context) -> (Shared, Local) {
+        (Shared {}, Local {})
+    }
+
+    // a binding named like the task is not a `sample::` path and keeps its name
+    #[task(instances = [fast<4>, slow<8>])]
+    async fn sample<const N: usize>(_: sample::Context) {
+        let sample: usize = N;
+        let _: () = sample;
+    }
+}
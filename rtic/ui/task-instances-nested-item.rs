@@ -0,0 +1,25 @@
+#![no_main]
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_cx: init::Context) -> (Shared, Local) {
+        (Shared {}, Local {})
+    }
+
+    // the generic parameter of a nested item shadows the one of the task
+    #[task(instances = [small<u8>])]
+    async fn convert<S: Into<u32>>(_: convert::Context, value: S) {
+        fn widen<S>(value: S) -> u32 {
+            value
+        }
+
+        let _ = widen(value);
+    }
+}
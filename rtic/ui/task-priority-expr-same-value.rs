@@ -0,0 +1,24 @@
+#![no_main]
+
+const HIGH: u8 = 2;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_cx: init::Context) -> (Shared, Local) {
+        (Shared {}, Local {})
+    }
+
+    #[task(priority = 2)]
+    async fn a(_: a::Context) {}
+
+    // `HIGH` is 2 as well, but is given its own dispatcher
+    #[task(priority = crate::HIGH)]
+    async fn b(_: b::Context) {}
+}
@@ -0,0 +1,21 @@
+#![no_main]
+
+const TOO_HIGH: u8 = 4 + 5;
+
+#[rtic::app(device = lm3s6965)]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_cx: init::Context) -> (Shared, Local) {
+        (Shared {}, Local {})
+    }
+
+    // the expression starts with a literal, it's evaluated all the same
+    #[task(binds = GPIOA, priority = 1 + crate::TOO_HIGH)]
+    fn gpioa(_: gpioa::Context) {}
+}
@@ -0,0 +1,21 @@
+#![no_main]
+
+const IDLE: u8 = 0;
+
+#[rtic::app(device = lm3s6965, dispatchers = [SSI0])]
+mod app {
+    #[shared]
+    struct Shared {}
+
+    #[local]
+    struct Local {}
+
+    #[init]
+    fn init(_cx: init::Context) -> (Shared, Local) {
+        (Shared {}, Local {})
+    }
+
+    // a priority given as an expression is always dispatched from an interrupt
+    #[task(priority = crate::IDLE)]
+    async fn background(_: background::Context) {}
+}
@@ -10,6 +10,7 @@ pub enum Package {
     Rtic,
     RticCommon,
     RticMacros,
+    RticMeta,
     RticMonotonics,
     RticSync,
     RticTime,
@@ -27,6 +28,7 @@ impl Package {
             Package::Rtic => "rtic",
             Package::RticCommon => "rtic-common",
             Package::RticMacros => "rtic-macros",
+            Package::RticMeta => "rtic-meta",
             Package::RticMonotonics => "rtic-monotonics",
             Package::RticSync => "rtic-sync",
             Package::RticTime => "rtic-time",
@@ -35,6 +37,9 @@ impl Package {
         name.to_string()
     }
 
+    /// All packages built for the target of a backend
+    ///
+    /// `rtic-meta` runs on the host and is only tested when asked for.
     pub fn all() -> Vec<Self> {
         vec![
             Self::Rtic,
@@ -121,6 +126,14 @@ impl TestMetadata {
                 deny_warnings: true,
                 loom,
             },
+            // The ELF tests are ignored here, CI runs them where `thumbv7m-none-eabi` is installed
+            Package::RticMeta => CargoCommand::Test {
+                package: Some(package.name()),
+                features: None,
+                test: None,
+                deny_warnings: true,
+                loom,
+            },
             Package::RticMonotonics => CargoCommand::Test {
                 package: Some(package.name()),
                 features: None,
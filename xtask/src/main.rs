@@ -51,6 +51,9 @@ const ARMV8MMAIN: Target = Target::new("thumbv8m.main-none-eabi", false);
 const RISCV32IMC: Target = Target::new("riscv32imc-unknown-none-elf", false);
 const RISCV32IMAC: Target = Target::new("riscv32imac-unknown-none-elf", false);
 
+/// Examples that bring their own backend and are only built with their `required-features`
+const CUSTOM_BACKEND_EXAMPLES: &[&str] = &["custom-backend"];
+
 fn main() -> anyhow::Result<()> {
     // if there's an `xtask` folder, we're *probably* at the root of this repo (we can't just
     // check the name of `env::current_dir()` because people might clone it into a different name)
@@ -105,6 +108,7 @@ fn main() -> anyhow::Result<()> {
         .map(|p| p.path())
         .filter(|p| p.display().to_string().ends_with(".rs"))
         .map(|path| path.file_stem().unwrap().to_str().unwrap().to_string())
+        .filter(|name| !CUSTOM_BACKEND_EXAMPLES.contains(&name.as_str()))
         .collect();
 
     let example = globals.example.clone();